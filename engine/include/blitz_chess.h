@@ -0,0 +1,77 @@
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef BLITZ_CHESS_H
+#define BLITZ_CHESS_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define BC_OK 0
+
+// A handle or string argument is null.
+#define BC_ERR_NULL -1
+
+// A string argument is not valid UTF-8.
+#define BC_ERR_UTF8 -2
+
+// The FEN or UCI string does not parse.
+#define BC_ERR_PARSE -3
+
+// The move is not legal in the current position.
+#define BC_ERR_ILLEGAL -4
+
+// The output buffer cannot hold the string and its terminating NUL.
+#define BC_ERR_BUFFER -5
+
+// The engine panicked; the handle should be freed.
+#define BC_ERR_PANIC -6
+
+// Opaque to C: a game in progress and the search that plays in it.
+typedef struct BcGame BcGame;
+
+// A new game at the standard initial position, or null if allocation panicked. Release it
+// with `bc_free`.
+BcGame *bc_new_game(void);
+
+// Free a handle from `bc_new_game`. Null is ignored.
+//
+// # Safety
+// `handle` must be null or a live handle from `bc_new_game`, not used again afterwards.
+void bc_free(BcGame *handle);
+
+// Start a new game from a FEN string. On error the current game is kept.
+//
+// # Safety
+// `handle` must be a live handle and `fen` a NUL-terminated string, or either may be null.
+int32_t bc_set_fen(BcGame *handle, const char *fen);
+
+// Write the current position as FEN into `buf`, returning its length without the NUL.
+//
+// # Safety
+// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+int32_t bc_fen(const BcGame *handle, char *buf, size_t len);
+
+// Write the legal moves as space-separated UCI into `buf`, returning its length without the
+// NUL. A position without legal moves writes the empty string.
+//
+// # Safety
+// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+int32_t bc_legal_moves(const BcGame *handle, char *buf, size_t len);
+
+// Play a legal move given in UCI notation.
+//
+// # Safety
+// `handle` must be a live handle and `uci_move` a NUL-terminated string, or either may be null.
+int32_t bc_make_move(BcGame *handle, const char *uci_move);
+
+// Search to `depth` and write the best move in UCI notation into `buf`, `0000` when there is
+// none, returning its length without the NUL. The move is not played.
+//
+// # Safety
+// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+int32_t bc_best_move(BcGame *handle, uint32_t depth, char *buf, size_t len);
+
+#endif  /* BLITZ_CHESS_H */
@@ -0,0 +1,159 @@
+//! Attack bitboards for every piece type.
+//!
+//! Leaper attacks come from tables built at compile time. Slider attacks use magic bitboards
+//! with the `magic` feature (on by default) and otherwise walk the rays directly.
+
+use crate::bits::{bit, SquareSet};
+use crate::board::{Color, Piece, PieceType, Square};
+
+// ============================================================================
+// Leapers
+// ============================================================================
+
+pub const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (-2, -1), (-2, 1), (-1, -2), (-1, 2),
+    ( 1, -2), ( 1, 2), ( 2, -1), ( 2, 1),
+];
+
+pub const KING_OFFSETS: [(i8, i8); 8] = [
+    (-1, -1), (-1, 0), (-1, 1), ( 0, -1),
+    ( 0,  1), ( 1, -1), ( 1, 0), ( 1,  1),
+];
+
+const KNIGHT_ATTACKS: [u64; 64] = leaper_table(&KNIGHT_OFFSETS);
+const KING_ATTACKS: [u64; 64] = leaper_table(&KING_OFFSETS);
+const PAWN_ATTACKS: [[u64; 64]; 2] = [leaper_table(&[(1, -1), (1, 1)]), leaper_table(&[(-1, -1), (-1, 1)])];
+
+pub const fn knight_attacks(sq: Square) -> SquareSet { SquareSet::from_bits(KNIGHT_ATTACKS[sq.index()]) }
+pub const fn king_attacks(sq: Square) -> SquareSet { SquareSet::from_bits(KING_ATTACKS[sq.index()]) }
+
+/// Squares a pawn of `color` standing on `sq` captures onto.
+pub const fn pawn_attacks(color: Color, sq: Square) -> SquareSet {
+    SquareSet::from_bits(PAWN_ATTACKS[color as usize][sq.index()])
+}
+
+const fn leaper_table(offsets: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut index = 0;
+    while index < 64 {
+        let sq = Square::from_index(index);
+        let mut i = 0;
+        while i < offsets.len() {
+            if let Some(to) = sq.offset(offsets[i].0, offsets[i].1) { table[index] |= bit(to); }
+            i += 1;
+        }
+        index += 1;
+    }
+    table
+}
+
+// ============================================================================
+// Sliders
+// ============================================================================
+
+pub const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+pub const ROOK_DIRECTIONS:   [(i8, i8); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Squares a bishop on `sq` attacks, up to and including the first blocker on each ray.
+#[cfg(feature = "magic")]
+pub fn bishop_attacks(sq: Square, occupancy: SquareSet) -> SquareSet {
+    SquareSet::from_bits(crate::magic::bishop_attacks(sq, occupancy.bits()))
+}
+
+/// Squares a rook on `sq` attacks, up to and including the first blocker on each ray.
+#[cfg(feature = "magic")]
+pub fn rook_attacks(sq: Square, occupancy: SquareSet) -> SquareSet {
+    SquareSet::from_bits(crate::magic::rook_attacks(sq, occupancy.bits()))
+}
+
+/// Squares a bishop on `sq` attacks, up to and including the first blocker on each ray.
+#[cfg(not(feature = "magic"))]
+pub fn bishop_attacks(sq: Square, occupancy: SquareSet) -> SquareSet { ray_attacks(sq, occupancy, &BISHOP_DIRECTIONS) }
+
+/// Squares a rook on `sq` attacks, up to and including the first blocker on each ray.
+#[cfg(not(feature = "magic"))]
+pub fn rook_attacks(sq: Square, occupancy: SquareSet) -> SquareSet { ray_attacks(sq, occupancy, &ROOK_DIRECTIONS) }
+
+pub fn queen_attacks(sq: Square, occupancy: SquareSet) -> SquareSet {
+    bishop_attacks(sq, occupancy) | rook_attacks(sq, occupancy)
+}
+
+/// Squares `piece` attacks from `sq`, with sliders stopped by `occupancy`.
+pub fn piece_attacks(piece: Piece, sq: Square, occupancy: SquareSet) -> SquareSet {
+    match piece.piece_type() {
+        PieceType::Knight => knight_attacks(sq),
+        PieceType::Bishop => bishop_attacks(sq, occupancy),
+        PieceType::Rook   => rook_attacks(sq, occupancy),
+        PieceType::Queen  => queen_attacks(sq, occupancy),
+        PieceType::Pawn   => pawn_attacks(piece.color(), sq),
+        PieceType::King   => king_attacks(sq),
+    }
+}
+
+/// Reference slider attacks by walking each ray. Slow, but obviously correct; the magic tables
+/// are built from and checked against it.
+pub const fn ray_attacks(sq: Square, occupancy: SquareSet, directions: &[(i8, i8)]) -> SquareSet {
+    let mut attacks = 0;
+    let mut i = 0;
+    while i < directions.len() {
+        let (dr, df) = directions[i];
+        let mut cursor = sq;
+        while let Some(to) = cursor.offset(dr, df) {
+            attacks |= bit(to);
+            if occupancy.contains(to) { break; }
+            cursor = to;
+        }
+        i += 1;
+    }
+    SquareSet::from_bits(attacks)
+}
+
+// ============================================================================
+// Lines
+// ============================================================================
+
+static BETWEEN: [[u64; 64]; 64] = line_table(false);
+static LINE: [[u64; 64]; 64] = line_table(true);
+
+/// Squares strictly between two squares on a rank, file, or diagonal; empty when they are not
+/// aligned or are neighbours.
+pub fn between(a: Square, b: Square) -> SquareSet { SquareSet::from_bits(BETWEEN[a.index()][b.index()]) }
+
+/// The whole rank, file, or diagonal through two squares, edge to edge and including both;
+/// empty when they are not aligned.
+pub fn line(a: Square, b: Square) -> SquareSet { SquareSet::from_bits(LINE[a.index()][b.index()]) }
+
+/// For every pair of aligned squares, the squares between them, or with `whole` the full line.
+const fn line_table(whole: bool) -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            let (from, to) = (Square::from_index(a), Square::from_index(b));
+            if let Some(direction) = from.direction_to(to) {
+                let (dr, df) = direction.step();
+                if whole {
+                    table[a][b] = bit(from) | ray(from, dr, df, None) | ray(from, -dr, -df, None);
+                } else {
+                    table[a][b] = ray(from, dr, df, Some(to));
+                }
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+}
+
+/// Squares from `from` one way to the edge, excluding `from`, or up to but excluding `stop`.
+const fn ray(from: Square, dr: i8, df: i8, stop: Option<Square>) -> u64 {
+    let mut mask = 0;
+    let mut cursor = from;
+    while let Some(sq) = cursor.offset(dr, df) {
+        if let Some(stop) = stop && stop.index() == sq.index() { break; }
+        mask |= bit(sq);
+        cursor = sq;
+    }
+    mask
+}
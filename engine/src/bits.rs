@@ -0,0 +1,185 @@
+//! Bitboard helpers. Bit `n` of a `u64` stands for the square with index `n` (a1 = 0, h8 = 63).
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub, SubAssign};
+
+use crate::board::Square;
+use crate::display::render_square_set;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A set of squares, one bit each. Iteration runs from a1 upward, along each rank in turn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SquareSet(u64);
+
+// ============================================================================
+// Raw Bitboards
+// ============================================================================
+
+/// The single bit for a square.
+pub const fn bit(sq: Square) -> u64 {
+    1 << sq.index()
+}
+
+/// The squares whose bits are set, from a1 upward.
+pub fn squares(mut bits: u64) -> impl Iterator<Item = Square> {
+    gen move {
+        while bits != 0 {
+            yield Square::from_index(bits.trailing_zeros() as usize);
+            bits &= bits - 1;
+        }
+    }
+}
+
+// ============================================================================
+// SquareSet — Construction
+// ============================================================================
+
+impl SquareSet {
+    pub const EMPTY: SquareSet = SquareSet(0);
+    pub const FULL: SquareSet = SquareSet(!0);
+
+    pub const fn from_bits(bits: u64) -> Self { SquareSet(bits) }
+
+    pub const fn from_square(sq: Square) -> Self { SquareSet(bit(sq)) }
+
+    pub const fn from_squares(squares: &[Square]) -> Self {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < squares.len() {
+            bits |= bit(squares[i]);
+            i += 1;
+        }
+        SquareSet(bits)
+    }
+
+    /// Every square of a rank, 0 being the first.
+    pub const fn rank(rank: u8) -> Self { SquareSet(0xFF << (rank * 8)) }
+
+    /// Every square of a file, 0 being the a-file.
+    pub const fn file(file: u8) -> Self { SquareSet(0x0101_0101_0101_0101 << file) }
+}
+
+// ============================================================================
+// SquareSet — Queries & Mutations
+// ============================================================================
+
+impl SquareSet {
+    pub const fn bits(self) -> u64 { self.0 }
+
+    pub const fn contains(self, sq: Square) -> bool { self.0 & bit(sq) != 0 }
+
+    pub const fn len(self) -> u32 { self.0.count_ones() }
+
+    pub const fn is_empty(self) -> bool { self.0 == 0 }
+
+    /// Whether the sets share a square.
+    pub const fn intersects(self, other: SquareSet) -> bool { self.0 & other.0 != 0 }
+
+    /// The lowest square, a1 first.
+    pub const fn first(self) -> Option<Square> {
+        if self.0 == 0 { None } else { Some(Square::from_index(self.0.trailing_zeros() as usize)) }
+    }
+
+    pub const fn insert(&mut self, sq: Square) { self.0 |= bit(sq); }
+
+    pub const fn remove(&mut self, sq: Square) { self.0 &= !bit(sq); }
+
+    /// Remove and return the lowest square.
+    pub const fn pop(&mut self) -> Option<Square> {
+        let first = self.first();
+        self.0 &= self.0.wrapping_sub(1);
+        first
+    }
+}
+
+impl IntoIterator for SquareSet {
+    type Item = Square;
+    type IntoIter = SquareSetIter;
+
+    fn into_iter(self) -> SquareSetIter { SquareSetIter(self) }
+}
+
+/// The squares of a set, from a1 upward.
+#[derive(Clone, Debug)]
+pub struct SquareSetIter(SquareSet);
+
+impl Iterator for SquareSetIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> { self.0.pop() }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for SquareSetIter {}
+
+impl FromIterator<Square> for SquareSet {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Self {
+        iter.into_iter().fold(SquareSet::EMPTY, |set, sq| set | SquareSet::from_square(sq))
+    }
+}
+
+impl From<Square> for SquareSet {
+    fn from(sq: Square) -> Self { SquareSet::from_square(sq) }
+}
+
+// ============================================================================
+// SquareSet — Set Operations
+// ============================================================================
+
+impl BitOr for SquareSet {
+    type Output = SquareSet;
+    fn bitor(self, other: SquareSet) -> SquareSet { SquareSet(self.0 | other.0) }
+}
+
+impl BitAnd for SquareSet {
+    type Output = SquareSet;
+    fn bitand(self, other: SquareSet) -> SquareSet { SquareSet(self.0 & other.0) }
+}
+
+/// Squares in exactly one of the sets.
+impl BitXor for SquareSet {
+    type Output = SquareSet;
+    fn bitxor(self, other: SquareSet) -> SquareSet { SquareSet(self.0 ^ other.0) }
+}
+
+/// Squares of the first set not in the second.
+impl Sub for SquareSet {
+    type Output = SquareSet;
+    fn sub(self, other: SquareSet) -> SquareSet { SquareSet(self.0 & !other.0) }
+}
+
+impl Not for SquareSet {
+    type Output = SquareSet;
+    fn not(self) -> SquareSet { SquareSet(!self.0) }
+}
+
+impl BitOrAssign for SquareSet {
+    fn bitor_assign(&mut self, other: SquareSet) { self.0 |= other.0; }
+}
+
+impl BitAndAssign for SquareSet {
+    fn bitand_assign(&mut self, other: SquareSet) { self.0 &= other.0; }
+}
+
+impl BitXorAssign for SquareSet {
+    fn bitxor_assign(&mut self, other: SquareSet) { self.0 ^= other.0; }
+}
+
+impl SubAssign for SquareSet {
+    fn sub_assign(&mut self, other: SquareSet) { self.0 &= !other.0; }
+}
+
+// ============================================================================
+// SquareSet — Display
+// ============================================================================
+
+impl Display for SquareSet {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_square_set(self, f) }
+}
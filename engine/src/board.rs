@@ -1,17 +1,22 @@
 //! Chess board representation and core data structures.
 
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::num::NonZeroU8;
-use std::ops::{Add, Index, IndexMut, Not, Sub};
+use std::ops::{Add, Index, Not, Sub};
+use std::str::FromStr;
 
-use crate::display::{render_board, render_piece, render_square};
+use crate::bits::{bit, SquareSet};
+use crate::display::{
+    debug_piece, render_board, render_board_with, render_color, render_piece, render_piece_type, render_square,
+};
 
 // ============================================================================
 // Type Definitions
 // ============================================================================
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PieceType {
     Knight = 0b000,
     Bishop = 0b001,
@@ -22,17 +27,30 @@ pub enum PieceType {
 }
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Color { White = 0, Black = 1 }
 
 #[repr(i8)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Lateral {
     Left        = -1,
     Straight    = 0,
     Right       = 1,
 }
 
+/// The eight ways a rank, file, or diagonal runs, North being toward the eighth rank.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
 #[repr(transparent)]
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Piece(NonZeroU8);    // niche optimization
@@ -41,8 +59,44 @@ pub struct Piece(NonZeroU8);    // niche optimization
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Square(u8);
 
+/// Why a `Color` or `PieceType` failed to parse from text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardParseError {
+    /// Not `w` or `b`.
+    BadColor(String),
+    /// Not a single piece letter from `PNBRQK`, in either case.
+    BadPieceType(String),
+}
+
+/// Mailbox plus bitboards. The two views are kept in sync by the mutators, so squares are
+/// only writable through them.
 #[derive(Clone)]
-pub struct Board { squares: [Option<Piece>; 64] }
+pub struct Board {
+    squares: [Option<Piece>; 64],
+    bitboards: Bitboards,
+}
+
+/// How to draw a board: `board.display().ascii().flipped().highlight(squares)`. Without
+/// options it draws exactly what `Display for Board` does.
+#[derive(Copy, Clone, Debug)]
+pub struct BoardDisplay<'a> {
+    pub(crate) board: &'a Board,
+    /// Piece letters, `KQRBNP` for White and `kqrbnp` for Black, and `.` for empty squares.
+    pub(crate) ascii: bool,
+    /// From Black's side: rank 1 at the top and the h-file on the left.
+    pub(crate) flipped: bool,
+    /// Squares drawn between brackets, or on a colored background in ANSI mode.
+    pub(crate) highlight: SquareSet,
+    #[cfg(feature = "color")]
+    pub(crate) ansi: bool,
+}
+
+/// One bit per square (a1 = bit 0) for each color and piece type, plus per-color occupancy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bitboards {
+    pieces: [[u64; 6]; 2],
+    occupancy: [u64; 2],
+}
 
 // ============================================================================
 // Color
@@ -50,15 +104,50 @@ pub struct Board { squares: [Option<Piece>; 64] }
 
 // --- Traits --- //
 impl Color {
+    /// Rank of the king and rooks at the start.
     pub const fn home_rank(self) -> u8 {
         match self { Color::White => 0, Color::Black => 7 }
     }
+
+    /// Rank the pawns start on, and may double-push from.
+    pub const fn pawn_rank(self) -> u8 {
+        match self { Color::White => 1, Color::Black => 6 }
+    }
+
+    /// Rank this color's pawns promote on.
+    pub const fn promotion_rank(self) -> u8 {
+        self.opponent().home_rank()
+    }
+
+    /// Rank direction this color's pawns advance in.
+    pub const fn forward(self) -> i8 {
+        match self { Color::White => 1, Color::Black => -1 }
+    }
+
+    pub const fn opponent(self) -> Self {
+        match self { Color::White => Color::Black, Color::Black => Color::White }
+    }
 }
 
 impl Not for Color {
     type Output = Self;
-    fn not(self) -> Self {
-        match self { Color::White => Color::Black, Color::Black => Color::White }
+    fn not(self) -> Self { self.opponent() }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_color(self, f) }
+}
+
+impl FromStr for Color {
+    type Err = BoardParseError;
+
+    /// `w` or `b`, as in the FEN side-to-move field.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "w" => Ok(Color::White),
+            "b" => Ok(Color::Black),
+            _ => Err(BoardParseError::BadColor(s.to_string())),
+        }
     }
 }
 
@@ -68,10 +157,36 @@ impl Not for Color {
 
 impl Square {
 
+    // --- Named Squares --- //
+    pub const A1: Self = Self( 0); pub const B1: Self = Self( 1); pub const C1: Self = Self( 2); pub const D1: Self = Self( 3);
+    pub const E1: Self = Self( 4); pub const F1: Self = Self( 5); pub const G1: Self = Self( 6); pub const H1: Self = Self( 7);
+    pub const A2: Self = Self( 8); pub const B2: Self = Self( 9); pub const C2: Self = Self(10); pub const D2: Self = Self(11);
+    pub const E2: Self = Self(12); pub const F2: Self = Self(13); pub const G2: Self = Self(14); pub const H2: Self = Self(15);
+    pub const A3: Self = Self(16); pub const B3: Self = Self(17); pub const C3: Self = Self(18); pub const D3: Self = Self(19);
+    pub const E3: Self = Self(20); pub const F3: Self = Self(21); pub const G3: Self = Self(22); pub const H3: Self = Self(23);
+    pub const A4: Self = Self(24); pub const B4: Self = Self(25); pub const C4: Self = Self(26); pub const D4: Self = Self(27);
+    pub const E4: Self = Self(28); pub const F4: Self = Self(29); pub const G4: Self = Self(30); pub const H4: Self = Self(31);
+    pub const A5: Self = Self(32); pub const B5: Self = Self(33); pub const C5: Self = Self(34); pub const D5: Self = Self(35);
+    pub const E5: Self = Self(36); pub const F5: Self = Self(37); pub const G5: Self = Self(38); pub const H5: Self = Self(39);
+    pub const A6: Self = Self(40); pub const B6: Self = Self(41); pub const C6: Self = Self(42); pub const D6: Self = Self(43);
+    pub const E6: Self = Self(44); pub const F6: Self = Self(45); pub const G6: Self = Self(46); pub const H6: Self = Self(47);
+    pub const A7: Self = Self(48); pub const B7: Self = Self(49); pub const C7: Self = Self(50); pub const D7: Self = Self(51);
+    pub const E7: Self = Self(52); pub const F7: Self = Self(53); pub const G7: Self = Self(54); pub const H7: Self = Self(55);
+    pub const A8: Self = Self(56); pub const B8: Self = Self(57); pub const C8: Self = Self(58); pub const D8: Self = Self(59);
+    pub const E8: Self = Self(60); pub const F8: Self = Self(61); pub const G8: Self = Self(62); pub const H8: Self = Self(63);
+
     // --- Construction --- //
     pub const fn from_coords(rank: u8, file: u8) -> Self { Square((rank << 3) | file) }
     pub const fn from_index(index: usize) -> Self { Square(index as u8) }
 
+    /// Parse a lowercase coordinate such as `"e4"`.
+    pub const fn from_algebraic(text: &str) -> Option<Self> {
+        match text.as_bytes() {
+            &[f @ b'a'..=b'h', r @ b'1'..=b'8'] => Some(Self::from_coords(r - b'1', f - b'a')),
+            _ => None,
+        }
+    }
+
     // --- Extraction --- //
     pub const fn value(self) -> u8 { self.0 }
     pub const fn rank(self) -> u8 { self.0 >> 3 }
@@ -87,12 +202,29 @@ impl Square {
         if Self::in_bounds(r, f) { Some(Self::from_coords(r as u8, f as u8)) } else { None }
     }
 
+    /// The square on the same file, across the middle of the board: a1 and a8 swap.
+    pub const fn flip_rank(self) -> Self { Square(self.0 ^ 56) }
+
+    /// The square on the same rank, across the middle of the board: a1 and h1 swap.
+    pub const fn flip_file(self) -> Self { Square(self.0 ^ 7) }
+
     pub const fn forward(self, color: Color, steps: i8, lateral: Lateral) -> Option<Self> {
-        let (dr, df) = match color {
-            Color::White => (steps, lateral as i8),
-            Color::Black => (-steps, -(lateral as i8)),
-        };
-        self.offset(dr, df)
+        // Lateral is from the mover's point of view, so it mirrors along with the ranks
+        self.offset(steps * color.forward(), lateral as i8 * color.forward())
+    }
+
+    /// The way from this square to `other` along a rank, file, or diagonal; None when they
+    /// share none, or are the same square.
+    pub const fn direction_to(self, other: Square) -> Option<Direction> {
+        let (dr, df) = (other.rank() as i8 - self.rank() as i8, other.file() as i8 - self.file() as i8);
+        if (dr == 0 && df == 0) || (dr != 0 && df != 0 && dr.abs() != df.abs()) { return None; }
+        Direction::from_step(dr.signum(), df.signum())
+    }
+
+    /// King steps from this square to `other`: the larger of the rank and file differences.
+    pub const fn distance(self, other: Square) -> u8 {
+        let (dr, df) = (self.rank().abs_diff(other.rank()), self.file().abs_diff(other.file()));
+        if dr > df { dr } else { df }
     }
 }
 
@@ -117,6 +249,10 @@ impl Display for Square {
     fn fmt(&self, f: &mut Formatter) -> FmtResult { render_square(self, f) }
 }
 
+impl Debug for Square {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_square(self, f) }
+}
+
 impl Add<(i8, i8)> for Square {
     type Output = Option<Square>;
     fn add(self, (dr, df): (i8, i8)) -> Option<Square> {
@@ -131,6 +267,55 @@ impl Sub<Square> for Square {
     }
 }
 
+// ============================================================================
+// Direction
+// ============================================================================
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North, Direction::NorthEast, Direction::East, Direction::SouthEast,
+        Direction::South, Direction::SouthWest, Direction::West, Direction::NorthWest,
+    ];
+
+    /// One step this way, as (rank, file) deltas.
+    pub const fn step(self) -> (i8, i8) {
+        match self {
+            Direction::North     => ( 1,  0),
+            Direction::NorthEast => ( 1,  1),
+            Direction::East      => ( 0,  1),
+            Direction::SouthEast => (-1,  1),
+            Direction::South     => (-1,  0),
+            Direction::SouthWest => (-1, -1),
+            Direction::West      => ( 0, -1),
+            Direction::NorthWest => ( 1, -1),
+        }
+    }
+
+    /// The direction of a single king step, given as (rank, file) deltas of -1, 0, or 1.
+    pub const fn from_step(dr: i8, df: i8) -> Option<Direction> {
+        match (dr, df) {
+            ( 1,  0) => Some(Direction::North),
+            ( 1,  1) => Some(Direction::NorthEast),
+            ( 0,  1) => Some(Direction::East),
+            (-1,  1) => Some(Direction::SouthEast),
+            (-1,  0) => Some(Direction::South),
+            (-1, -1) => Some(Direction::SouthWest),
+            ( 0, -1) => Some(Direction::West),
+            ( 1, -1) => Some(Direction::NorthWest),
+            _ => None,
+        }
+    }
+
+    pub const fn opposite(self) -> Direction {
+        Direction::ALL[(self as usize + 4) % 8]
+    }
+
+    /// Whether bishops move this way, rather than rooks.
+    pub const fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+}
+
 // ============================================================================
 // PieceType
 // ============================================================================
@@ -156,6 +341,69 @@ impl PieceType {
             _ => unreachable!(),
         }
     }
+
+    /// Lowercase promotion letter as used by long algebraic notation.
+    pub(crate) const fn from_promotion_char(c: char) -> Option<Self> {
+        match c {
+            'n' => Some(Self::Knight),
+            'b' => Some(Self::Bishop),
+            'r' => Some(Self::Rook),
+            'q' => Some(Self::Queen),
+            _ => None,
+        }
+    }
+
+    // --- Letters --- //
+    /// Uppercase piece letter, `PNBRQK`.
+    pub const fn to_char_upper(self) -> char {
+        match self {
+            Self::Pawn   => 'P',
+            Self::Knight => 'N',
+            Self::Bishop => 'B',
+            Self::Rook   => 'R',
+            Self::Queen  => 'Q',
+            Self::King   => 'K',
+        }
+    }
+
+    /// Piece letter as FEN writes it: uppercase for White, lowercase for Black.
+    pub const fn to_char(self, color: Color) -> char {
+        match color {
+            Color::White => self.to_char_upper(),
+            Color::Black => self.to_char_upper().to_ascii_lowercase(),
+        }
+    }
+
+    /// Piece type for a letter of either case.
+    pub const fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'P' => Some(Self::Pawn),
+            'N' => Some(Self::Knight),
+            'B' => Some(Self::Bishop),
+            'R' => Some(Self::Rook),
+            'Q' => Some(Self::Queen),
+            'K' => Some(Self::King),
+            _ => None,
+        }
+    }
+}
+
+// --- Traits --- //
+impl Display for PieceType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_piece_type(self, f) }
+}
+
+impl FromStr for PieceType {
+    type Err = BoardParseError;
+
+    /// A single piece letter of either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Self::from_char(c).ok_or_else(|| BoardParseError::BadPieceType(s.to_string())),
+            _ => Err(BoardParseError::BadPieceType(s.to_string())),
+        }
+    }
 }
 
 // ============================================================================
@@ -185,6 +433,19 @@ impl Piece {
     // --- Type Checks --- //
     pub const fn is_pawn(self) -> bool { self.piece_type() as u8 == PieceType::Pawn as u8 }
     pub const fn is_king(self) -> bool { self.piece_type() as u8 == PieceType::King as u8 }
+
+    // --- Raw Encoding --- //
+    pub(crate) const fn bits(self) -> u8 { self.0.get() }
+
+    /// The piece with this encoding, or None for bits no piece produces.
+    pub(crate) const fn from_bits(bits: u8) -> Option<Self> {
+        let valid = Self::OCCUPIED_BIT | Self::COLOR_BIT | Self::PIECE_MASK;
+        if bits & Self::OCCUPIED_BIT == 0 || bits & !valid != 0 || bits & Self::PIECE_MASK > PieceType::King as u8 {
+            return None;
+        }
+        // SAFETY: OCCUPIED_BIT was checked above
+        Some(unsafe { Piece(NonZeroU8::new_unchecked(bits)) })
+    }
 }
 
 // --- Traits --- //
@@ -192,34 +453,178 @@ impl Display for Piece {
     fn fmt(&self, f: &mut Formatter) -> FmtResult { render_piece(self, f) }
 }
 
+impl Debug for Piece {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { debug_piece(self, f) }
+}
+
 // ============================================================================
 // Board
 // ============================================================================
 
 impl Board {
     // --- Construction --- //
-    pub const fn new() -> Self { Board { squares: [None; 64] } }
+    pub const fn new() -> Self { Board { squares: [None; 64], bitboards: Bitboards::new() } }
+
+    /// The standard initial position.
+    pub const fn start() -> Self {
+        const BACK_RANK: [PieceType; 8] = [
+            PieceType::Rook, PieceType::Knight, PieceType::Bishop, PieceType::Queen,
+            PieceType::King, PieceType::Bishop, PieceType::Knight, PieceType::Rook,
+        ];
+
+        let mut board = Board::new();
+        let mut file = 0;
+        while file < 8 {
+            board.insert(Piece::new(BACK_RANK[file as usize], Color::White), Square::from_coords(0, file));
+            board.insert(Piece::new(PieceType::Pawn, Color::White), Square::from_coords(1, file));
+            board.insert(Piece::new(PieceType::Pawn, Color::Black), Square::from_coords(6, file));
+            board.insert(Piece::new(BACK_RANK[file as usize], Color::Black), Square::from_coords(7, file));
+            file += 1;
+        }
+        board
+    }
+
+    /// A builder for drawing the board other than the default way.
+    pub fn display(&self) -> BoardDisplay<'_> {
+        BoardDisplay {
+            board: self,
+            ascii: false,
+            flipped: false,
+            highlight: SquareSet::EMPTY,
+            #[cfg(feature = "color")]
+            ansi: false,
+        }
+    }
+
+    // --- Transforms --- //
+    /// Ranks reversed, the first swapping with the eighth; pieces keep their colors.
+    pub fn flipped_vertical(&self) -> Self {
+        self.transformed(|sq, piece| (sq.flip_rank(), piece))
+    }
+
+    /// Files reversed, the a-file swapping with the h-file.
+    pub fn mirrored_horizontal(&self) -> Self {
+        self.transformed(|sq, piece| (sq.flip_file(), piece))
+    }
+
+    /// Ranks reversed and every piece given to the other side: the same position from the
+    /// other side of the board.
+    pub fn color_swapped(&self) -> Self {
+        self.transformed(|sq, piece| (sq.flip_rank(), Piece::new(piece.piece_type(), !piece.color())))
+    }
+
+    fn transformed(&self, map: impl Fn(Square, Piece) -> (Square, Piece)) -> Self {
+        let mut board = Board::new();
+        for (sq, piece) in self.pieces() {
+            let (sq, piece) = map(sq, piece);
+            board.insert(piece, sq);
+        }
+        board
+    }
 
     // --- Queries --- //
+    /// Every piece with its square, from a1 upward.
     pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
-        (0..64).map(Square::from_index).filter_map(|sq| self[sq].map(|p| (sq, p)))
+        self.pieces_in(self.occupied())
+    }
+
+    /// The pieces of one color, from a1 upward.
+    pub fn pieces_of_color(&self, color: Color) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        self.pieces_in(self.occupancy(color))
+    }
+
+    pub const fn count(&self, piece_type: PieceType, color: Color) -> u8 {
+        self.pieces_of(color, piece_type).len() as u8
+    }
+
+    /// None only on boards that are not legal positions.
+    pub const fn king_square(&self, color: Color) -> Option<Square> {
+        self.pieces_of(color, PieceType::King).first()
+    }
+
+    fn pieces_in(&self, set: SquareSet) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        set.into_iter().map(|sq| (sq, self[sq].expect("bitboards match the mailbox")))
+    }
+
+    pub const fn bitboards(&self) -> &Bitboards { &self.bitboards }
+
+    /// Squares holding any piece of `color`.
+    pub const fn occupancy(&self, color: Color) -> SquareSet {
+        SquareSet::from_bits(self.bitboards.occupancy[color as usize])
+    }
+
+    /// Squares holding any piece at all.
+    pub const fn occupied(&self) -> SquareSet {
+        SquareSet::from_bits(self.bitboards.occupancy[0] | self.bitboards.occupancy[1])
+    }
+
+    /// Squares holding `color`'s pieces of `piece_type`.
+    pub const fn pieces_of(&self, color: Color, piece_type: PieceType) -> SquareSet {
+        SquareSet::from_bits(self.bitboards.pieces[color as usize][piece_type as usize])
     }
 
     // --- Mutations --- //
+    /// Put `piece` on the square, returning whatever stood there.
+    pub fn set_piece(&mut self, piece: Piece, s: impl Into<Square>) -> Option<Piece> {
+        self.insert(piece, s.into())
+    }
+
+    /// Empty the square, returning whatever stood there.
+    pub fn remove_piece(&mut self, s: impl Into<Square>) -> Option<Piece> {
+        self.take(s.into())
+    }
+
+    /// Move the piece on `from` to `to`, returning whatever was captured there. Moving from an
+    /// empty square changes nothing.
     pub fn move_piece(&mut self, from: impl Into<Square>, to: impl Into<Square>) -> Option<Piece> {
-        self[from].lift().and_then(|piece| self[to].place(piece))
+        let to = to.into();
+        self.take(from.into()).and_then(|piece| self.insert(piece, to))
+    }
+
+    const fn insert(&mut self, piece: Piece, s: Square) -> Option<Piece> {
+        let previous = self.take(s);
+        self.squares[s.index()] = Some(piece);
+        self.bitboards.toggle(piece, s);
+        previous
+    }
+
+    const fn take(&mut self, s: Square) -> Option<Piece> {
+        let previous = self.squares[s.index()].take();
+        if let Some(piece) = previous { self.bitboards.toggle(piece, s); }
+        previous
     }
 }
 
-// --- Traits --- //
-pub trait SlotExt {
-    fn place(&mut self, piece: Piece) -> Option<Piece>;
-    fn lift(&mut self) -> Option<Piece>;
+// ============================================================================
+// Bitboards
+// ============================================================================
+
+impl Bitboards {
+    pub const fn new() -> Self { Bitboards { pieces: [[0; 6]; 2], occupancy: [0; 2] } }
+
+    /// Rebuild from the mailbox alone, for checking the incrementally maintained copy.
+    pub fn from_board(board: &Board) -> Self {
+        let mut bitboards = Bitboards::new();
+        for sq in (0..64).map(Square::from_index) {
+            if let Some(piece) = board[sq] { bitboards.toggle(piece, sq); }
+        }
+        bitboards
+    }
+
+    const fn toggle(&mut self, piece: Piece, s: Square) {
+        let bit = bit(s);
+        self.pieces[piece.color() as usize][piece.piece_type() as usize] ^= bit;
+        self.occupancy[piece.color() as usize] ^= bit;
+    }
 }
 
-impl SlotExt for Option<Piece> {
-    fn place(&mut self, piece: Piece) -> Option<Piece> { self.replace(piece) }
-    fn lift(&mut self) -> Option<Piece> { self.take() }
+impl Default for Bitboards {
+    fn default() -> Self { Bitboards::new() }
+}
+
+// --- Traits --- //
+impl Default for Board {
+    fn default() -> Self { Board::new() }
 }
 
 impl<T: Into<Square>> Index<T> for Board {
@@ -228,10 +633,91 @@ impl<T: Into<Square>> Index<T> for Board {
     fn index(&self, s: T) -> &Option<Piece> { &self.squares[s.into().index()] }
 }
 
-impl<T: Into<Square>> IndexMut<T> for Board {
-    fn index_mut(&mut self, s: T) -> &mut Option<Piece> { &mut self.squares[s.into().index()] }
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_board(self, f) }
 }
 
-impl Display for Board {
+impl Debug for Board {
     fn fmt(&self, f: &mut Formatter) -> FmtResult { render_board(self, f) }
 }
+
+// ============================================================================
+// BoardDisplay
+// ============================================================================
+
+impl BoardDisplay<'_> {
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    pub fn flipped(mut self) -> Self {
+        self.flipped = true;
+        self
+    }
+
+    /// Mark these squares, as for the targets of a selected piece. Adds to any marked before.
+    pub fn highlight(mut self, squares: impl Into<SquareSet>) -> Self {
+        self.highlight |= squares.into();
+        self
+    }
+
+    /// Mark highlighted squares with an ANSI background color instead of brackets.
+    #[cfg(feature = "color")]
+    pub fn ansi(mut self) -> Self {
+        self.ansi = true;
+        self
+    }
+}
+
+impl Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_board_with(self, f) }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for BoardParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            BoardParseError::BadColor(s)     => write!(f, "invalid color '{}'", s),
+            BoardParseError::BadPieceType(s) => write!(f, "invalid piece type '{}'", s),
+        }
+    }
+}
+
+impl Error for BoardParseError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_fall_off_the_board_instead_of_wrapping() {
+        assert_eq!(Square::H1.offset(0, 1), None);
+        assert_eq!(Square::A8.offset(1, -1), None);
+        assert_eq!(Square::A1.offset(0, -1), None);
+        assert_eq!(Square::H8.offset(-1, 1), None);
+        assert_eq!(Square::A2.offset(-2, 0), None);
+        assert_eq!(Square::H1.offset(1, 0), Some(Square::H2));
+        assert_eq!(Square::A8.offset(-1, 1), Some(Square::B7));
+        assert_eq!(Square::E4.offset(2, -1), Some(Square::D6));
+    }
+
+    #[test]
+    fn algebraic_names_match_the_named_squares() {
+        assert_eq!(Square::from_algebraic("a1"), Some(Square::A1));
+        assert_eq!(Square::from_algebraic("e4"), Some(Square::E4));
+        assert_eq!(Square::from_algebraic("h8"), Some(Square::H8));
+        for text in ["", "e", "e9", "i1", "E4", "e44"] {
+            assert_eq!(Square::from_algebraic(text), None, "{:?}", text);
+        }
+        assert_eq!((Square::A1.value(), Square::H1.value(), Square::A8.value(), Square::E4.value()), (0, 7, 56, 28));
+        assert_eq!(Square::E4.to_string(), "e4");
+    }
+}
@@ -0,0 +1,320 @@
+//! Building positions piece by piece, with the checks a FEN string does not get: one king a
+//! side, no pawns on the back ranks, material that promotions can account for, a believable
+//! en passant square, and the side not to move out of check.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::mobility::is_square_attacked;
+use crate::state::{AttackCache, State};
+use crate::zobrist::hash_state;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A position under construction. Starts from an empty board with White to move, no castling
+/// rights, no en passant square, and the clocks at `0` and `1`.
+#[derive(Clone, Debug)]
+pub struct StateBuilder {
+    board: Board,
+    to_move: Color,
+    castling: String,
+    en_passant: Option<String>,
+    halfmove_clock: u8,
+    fullmove_number: u16,
+    /// The first bad square or doubly filled square, reported by `build`.
+    error: Option<PositionError>,
+}
+
+/// Why a position cannot arise in a game, or could not be put together at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// A square name is not `a1` to `h8`.
+    BadSquare(String),
+    /// Two pieces were placed on the same square.
+    Occupied(Square),
+    MissingKing(Color),
+    TooManyKings(Color),
+    /// A pawn stands on the first or last rank.
+    PawnOnBackRank(Square),
+    TooManyPawns(Color),
+    /// More pieces of some kind than the starting set plus a promotion per missing pawn allows.
+    TooManyPromotions(Color),
+    /// The castling text is not a valid FEN castling field.
+    BadCastlingField(String),
+    /// A castling right is held without the king and rook on their home squares.
+    CastlingWithoutPieces(Color, CastlingSide),
+    /// The en passant square is not on the right rank, is not empty along with the square the
+    /// pawn left, or has no pawn of the side not to move just beyond it.
+    BadEnPassant(Square),
+    /// The side to move could take the opposing king.
+    OpponentInCheck,
+}
+
+// ============================================================================
+// StateBuilder
+// ============================================================================
+
+impl StateBuilder {
+    pub fn new() -> Self {
+        StateBuilder {
+            board: Board::new(),
+            to_move: Color::White,
+            castling: "-".to_string(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            error: None,
+        }
+    }
+
+    /// Place a piece on a square named in algebraic notation.
+    pub fn piece(mut self, piece_type: PieceType, color: Color, square: &str) -> Self {
+        match Square::from_algebraic(square) {
+            Some(sq) if self.board[sq].is_some() => { self.error.get_or_insert(PositionError::Occupied(sq)); }
+            Some(sq) => { self.board.set_piece(Piece::new(piece_type, color), sq); }
+            None => { self.error.get_or_insert(PositionError::BadSquare(square.to_string())); }
+        }
+        self
+    }
+
+    pub fn to_move(mut self, color: Color) -> Self {
+        self.to_move = color;
+        self
+    }
+
+    /// Castling rights as a FEN castling field: `KQkq`, a subset, `-`, or Chess960 rook files.
+    /// Read against the finished board, so pieces may be placed before or after.
+    pub fn castling(mut self, rights: &str) -> Self {
+        self.castling = rights.to_string();
+        self
+    }
+
+    /// The square a pawn of the side not to move just skipped over.
+    pub fn en_passant(mut self, square: &str) -> Self {
+        self.en_passant = Some(square.to_string());
+        self
+    }
+
+    pub fn halfmove_clock(mut self, plies: u8) -> Self {
+        self.halfmove_clock = plies;
+        self
+    }
+
+    pub fn fullmove_number(mut self, number: u16) -> Self {
+        self.fullmove_number = number;
+        self
+    }
+
+    /// The position, if it passes `validate`.
+    pub fn build(self) -> Result<State, PositionError> {
+        if let Some(err) = self.error { return Err(err); }
+        let castling_rights = CastlingRights::from_fen_field(&self.castling, &self.board)
+            .map_err(|_| PositionError::BadCastlingField(self.castling.clone()))?;
+        let en_passant = match &self.en_passant {
+            Some(text) => Some(Square::from_algebraic(text).ok_or_else(|| PositionError::BadSquare(text.clone()))?),
+            None => None,
+        };
+
+        let mut state = State {
+            board: self.board,
+            to_move: self.to_move,
+            castling_rights,
+            en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: 0,
+            attacks: AttackCache::default(),
+        };
+        state.hash = hash_state(&state);
+        validate(&state)?;
+        Ok(state)
+    }
+}
+
+impl Default for StateBuilder {
+    fn default() -> Self { Self::new() }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Check that a position could arise in a game, as far as can be told without replaying one.
+/// Violations are looked for in the order `PositionError` lists them.
+pub fn validate(state: &State) -> Result<(), PositionError> {
+    let board = &state.board;
+
+    for color in [Color::White, Color::Black] {
+        match board.count(PieceType::King, color) {
+            0 => return Err(PositionError::MissingKing(color)),
+            1 => {}
+            _ => return Err(PositionError::TooManyKings(color)),
+        }
+    }
+
+    let pawns = board.pieces_of(Color::White, PieceType::Pawn) | board.pieces_of(Color::Black, PieceType::Pawn);
+    if let Some(sq) = pawns.into_iter().find(|sq| sq.rank() == 0 || sq.rank() == 7) {
+        return Err(PositionError::PawnOnBackRank(sq));
+    }
+
+    for color in [Color::White, Color::Black] {
+        let pawns = board.count(PieceType::Pawn, color);
+        if pawns > 8 { return Err(PositionError::TooManyPawns(color)); }
+        // Each piece beyond the starting set must be a promoted pawn
+        let promoted: u8 = [(PieceType::Queen, 1), (PieceType::Rook, 2), (PieceType::Bishop, 2), (PieceType::Knight, 2)]
+            .into_iter()
+            .map(|(piece_type, start)| board.count(piece_type, color).saturating_sub(start))
+            .sum();
+        if promoted > 8 - pawns { return Err(PositionError::TooManyPromotions(color)); }
+    }
+
+    for color in [Color::White, Color::Black] {
+        for side in [CastlingSide::Kingside, CastlingSide::Queenside] {
+            if !state.castling_rights.has(color, side) { continue; }
+            let king = board.king_square(color).filter(|king| king.rank() == color.home_rank());
+            let rook = board[state.castling_rights.rook_square(color, side)];
+            if king.is_none() || rook != Some(Piece::new(PieceType::Rook, color)) {
+                return Err(PositionError::CastlingWithoutPieces(color, side));
+            }
+        }
+    }
+
+    if let Some(ep) = state.en_passant {
+        let pusher = !state.to_move;
+        let origin = ep.offset(-pusher.forward(), 0);
+        let pawn = ep.offset(pusher.forward(), 0);
+        let expected_rank = (pusher.pawn_rank() as i8 + pusher.forward()) as u8;
+        let believable = ep.rank() == expected_rank
+            && board[ep].is_none()
+            && origin.is_some_and(|sq| board[sq].is_none())
+            && pawn.is_some_and(|sq| board[sq] == Some(Piece::new(PieceType::Pawn, pusher)));
+        if !believable { return Err(PositionError::BadEnPassant(ep)); }
+    }
+
+    let opponent = !state.to_move;
+    let king = board.king_square(opponent).expect("both kings were counted");
+    if is_square_attacked(board, king, state.to_move) { return Err(PositionError::OpponentInCheck); }
+    Ok(())
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PositionError::BadSquare(s)                  => write!(f, "invalid square '{}'", s),
+            PositionError::Occupied(sq)                  => write!(f, "two pieces placed on {}", sq),
+            PositionError::MissingKing(color)            => write!(f, "{:?} has no king", color),
+            PositionError::TooManyKings(color)           => write!(f, "{:?} has more than one king", color),
+            PositionError::PawnOnBackRank(sq)            => write!(f, "pawn on the back rank at {}", sq),
+            PositionError::TooManyPawns(color)           => write!(f, "{:?} has more than 8 pawns", color),
+            PositionError::TooManyPromotions(color)      => write!(f, "{:?} has more promoted pieces than missing pawns", color),
+            PositionError::BadCastlingField(s)           => write!(f, "invalid castling rights '{}'", s),
+            PositionError::CastlingWithoutPieces(color, side) => {
+                write!(f, "{:?} {:?} castling right without the king and rook in place", color, side)
+            }
+            PositionError::BadEnPassant(sq)              => write!(f, "impossible en passant square {}", sq),
+            PositionError::OpponentInCheck               => write!(f, "the side not to move is in check"),
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kings on e1 and e8, White to move.
+    fn kings() -> StateBuilder {
+        StateBuilder::new().piece(PieceType::King, Color::White, "e1").piece(PieceType::King, Color::Black, "e8")
+    }
+
+    #[test]
+    fn a_built_position_matches_its_fen() {
+        let state = kings()
+            .piece(PieceType::Rook, Color::White, "h1")
+            .piece(PieceType::Pawn, Color::White, "e4")
+            .piece(PieceType::Pawn, Color::Black, "d4")
+            .to_move(Color::Black)
+            .castling("K")
+            .en_passant("e3")
+            .halfmove_clock(0)
+            .fullmove_number(12)
+            .build()
+            .unwrap();
+        let fen = State::from_fen("4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 12").unwrap();
+        assert_eq!(state.to_fen(), fen.to_fen());
+        assert_eq!(state.hash(), fen.hash());
+    }
+
+    #[test]
+    fn kings_must_number_one_a_side() {
+        let white_king_only = StateBuilder::new().piece(PieceType::King, Color::White, "e1");
+        assert_eq!(white_king_only.build().unwrap_err(), PositionError::MissingKing(Color::Black));
+        let two_kings = kings().piece(PieceType::King, Color::White, "a1");
+        assert_eq!(two_kings.build().unwrap_err(), PositionError::TooManyKings(Color::White));
+        let two_kings = kings().piece(PieceType::King, Color::Black, "a8");
+        assert_eq!(two_kings.build().unwrap_err(), PositionError::TooManyKings(Color::Black));
+    }
+
+    #[test]
+    fn pawns_may_not_stand_on_either_back_rank() {
+        let back = kings().piece(PieceType::Pawn, Color::White, "a1");
+        assert_eq!(back.build().unwrap_err(), PositionError::PawnOnBackRank(Square::A1));
+        let promoting = kings().piece(PieceType::Pawn, Color::Black, "h8");
+        assert_eq!(promoting.build().unwrap_err(), PositionError::PawnOnBackRank(Square::H8));
+    }
+
+    #[test]
+    fn material_must_be_reachable_by_promotions() {
+        let nine_pawns = (0..8).fold(kings(), |builder, file| builder.piece(PieceType::Pawn, Color::White, &format!("{}2", (b'a' + file) as char)))
+            .piece(PieceType::Pawn, Color::White, "a3");
+        assert_eq!(nine_pawns.build().unwrap_err(), PositionError::TooManyPawns(Color::White));
+        // Two extra queens need two missing pawns
+        let queens = ["a4", "b4", "c4"].into_iter().fold(kings(), |builder, sq| builder.piece(PieceType::Queen, Color::Black, sq));
+        let queens = (0..7).fold(queens, |builder, file| builder.piece(PieceType::Pawn, Color::Black, &format!("{}7", (b'a' + file) as char)));
+        assert_eq!(queens.build().unwrap_err(), PositionError::TooManyPromotions(Color::Black));
+    }
+
+    #[test]
+    fn en_passant_needs_a_pawn_that_just_double_pushed() {
+        let pushed = kings().piece(PieceType::Pawn, Color::White, "e4").to_move(Color::Black);
+        assert!(pushed.clone().en_passant("e3").build().is_ok());
+        // Wrong rank, no pawn beyond it, the square it left still occupied, or the wrong side to move
+        assert_eq!(pushed.clone().en_passant("e6").build().unwrap_err(), PositionError::BadEnPassant(Square::E6));
+        assert_eq!(pushed.clone().en_passant("d3").build().unwrap_err(), PositionError::BadEnPassant(Square::D3));
+        let blocked = pushed.clone().piece(PieceType::Knight, Color::White, "e2").en_passant("e3");
+        assert_eq!(blocked.build().unwrap_err(), PositionError::BadEnPassant(Square::E3));
+        assert_eq!(pushed.to_move(Color::White).en_passant("e3").build().unwrap_err(), PositionError::BadEnPassant(Square::E3));
+        assert_eq!(kings().en_passant("e9").build().unwrap_err(), PositionError::BadSquare("e9".to_string()));
+    }
+
+    #[test]
+    fn the_side_not_to_move_may_not_be_in_check() {
+        let checking = kings().piece(PieceType::Rook, Color::White, "a8");
+        assert_eq!(checking.clone().build().unwrap_err(), PositionError::OpponentInCheck);
+        // Being in check on the move is fine
+        assert!(checking.to_move(Color::Black).build().unwrap().in_check());
+    }
+
+    #[test]
+    fn placement_and_castling_mistakes_are_named() {
+        let doubled = kings().piece(PieceType::Rook, Color::White, "e1");
+        assert_eq!(doubled.build().unwrap_err(), PositionError::Occupied(Square::E1));
+        let off_board = kings().piece(PieceType::Rook, Color::White, "j1");
+        assert_eq!(off_board.build().unwrap_err(), PositionError::BadSquare("j1".to_string()));
+        assert_eq!(kings().castling("K").build().unwrap_err(), PositionError::CastlingWithoutPieces(Color::White, CastlingSide::Kingside));
+        assert_eq!(kings().castling("X").build().unwrap_err(), PositionError::BadCastlingField("X".to_string()));
+    }
+}
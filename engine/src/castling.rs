@@ -1,18 +1,39 @@
 //! Castling types and logic.
 
-use crate::board::{Color, Square};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::display::render_castling;
 
 // ============================================================================
 // Type Definitions
 // ============================================================================
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CastlingSide { Kingside = 0, Queenside = 1 }
 
+/// The four rights in the low bits, then the home file of each right's rook, three bits apiece.
+/// Rook files only differ from the a- and h-files in Chess960.
 #[repr(transparent)]
 #[derive(Copy, Clone, PartialEq, Eq)]
-pub struct CastlingRights(u8);
+pub struct CastlingRights(u16);
+
+/// Why a castling field failed to parse. Letters are accepted in any order, so `qK` is valid;
+/// a repeated right is not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CastlingParseError {
+    /// The field is empty; no rights are written `-`.
+    Empty,
+    /// A character other than `KQkq` or a rook file, or `-` mixed with letters.
+    BadChar(char),
+    /// A right listed more than once.
+    Duplicate(char),
+    /// A rook file was given but that side's king is not on its home rank.
+    NoKing(char),
+}
 
 // ============================================================================
 // Castling Side
@@ -21,32 +42,22 @@ pub struct CastlingRights(u8);
 impl CastlingSide {
 
     // --- Constant Files --- //
+    /// The king's file in the standard starting position.
     pub const KING_FILE: u8 = 4;
 
     const KING_TARGETS:  [u8; 2] = [6, 2];
     const ROOK_SOURCES:  [u8; 2] = [7, 0];
     const ROOK_TARGETS:  [u8; 2] = [5, 3];
-    const CORRIDORS:     [&'static [u8]; 2] = [&[5, 6], &[1, 2, 3]];
-    const KING_PATHS:    [&'static [u8]; 2] = [&[5, 6], &[3, 2]];
 
     // --- File accessors --- //
     pub const fn king_target_file(self) -> u8 { Self::KING_TARGETS[self as usize] }
+    /// The rook's home file in the standard starting position.
     pub const fn rook_source_file(self) -> u8 { Self::ROOK_SOURCES[self as usize] }
     pub const fn rook_target_file(self) -> u8 { Self::ROOK_TARGETS[self as usize] }
-    pub const fn corridor_files(self) -> &'static [u8] { Self::CORRIDORS[self as usize] }
-    pub const fn king_path_files(self) -> &'static [u8] { Self::KING_PATHS[self as usize] }
-
-    /// Returns the castling side if this file is a rook home file.
-    pub const fn from_rook_file(file: u8) -> Option<Self> {
-        if file == Self::ROOK_SOURCES[Self::Kingside as usize] { return Some(Self::Kingside); }
-        if file == Self::ROOK_SOURCES[Self::Queenside as usize] { return Some(Self::Queenside); }
-        None
-    }
 
-    /// Returns the castling side if this square is a rook's home square for the given color.
-    pub fn from_rook_square(square: Square, color: Color) -> Option<Self> {
-        if square.rank() != color.home_rank() { return None; }
-        Self::from_rook_file(square.file())
+    /// Which side a rook on `rook_file` castles to, given the king's file.
+    pub const fn of_rook(rook_file: u8, king_file: u8) -> Self {
+        if rook_file > king_file { Self::Kingside } else { Self::Queenside }
     }
 }
 
@@ -56,11 +67,17 @@ impl CastlingSide {
 
 impl CastlingRights {
 
-    const fn bit_position(c: Color, s: CastlingSide) -> u8 { (c as u8) * 2 + (s as u8) }
+    const RIGHTS_MASK: u16 = 0b1111;
+
+    const fn bit_position(c: Color, s: CastlingSide) -> u16 { (c as u16) * 2 + (s as u16) }
+    const fn file_shift(c: Color, s: CastlingSide) -> u16 { 4 + 3 * Self::bit_position(c, s) }
+
+    /// Standard rook files: h-file kingside and a-file (zero) queenside, for both colors.
+    const STANDARD_FILES: u16 = (7 << 4) | (7 << 10);
 
     // --- Construction --- //
-    pub const fn none() -> Self { CastlingRights(0) }
-    pub const fn all() -> Self { CastlingRights(0b1111) }
+    pub const fn none() -> Self { CastlingRights(Self::STANDARD_FILES) }
+    pub const fn all() -> Self { CastlingRights(Self::STANDARD_FILES | Self::RIGHTS_MASK) }
 
     // --- Query --- //
     pub const fn has(self, color: Color, side: CastlingSide) -> bool {
@@ -68,12 +85,21 @@ impl CastlingRights {
         (self.0 & bit) != 0
     }
 
-    pub const fn is_empty(self) -> bool { self.0 == 0 }
+    pub const fn is_empty(self) -> bool { self.0 & Self::RIGHTS_MASK == 0 }
 
     pub const fn any(self, color: Color) -> bool {
         self.has(color, CastlingSide::Kingside) || self.has(color, CastlingSide::Queenside)
     }
 
+    /// Home file of the rook that castles on this side, whether or not the right is held.
+    pub const fn rook_file(self, color: Color, side: CastlingSide) -> u8 {
+        ((self.0 >> Self::file_shift(color, side)) & 0b111) as u8
+    }
+
+    pub const fn rook_square(self, color: Color, side: CastlingSide) -> Square {
+        Square::from_coords(color.home_rank(), self.rook_file(color, side))
+    }
+
     // --- Modifications --- //
     pub const fn gain(self, color: Color, side: CastlingSide) -> Self {
         let bit = 1 << Self::bit_position(color, side);
@@ -90,11 +116,105 @@ impl CastlingRights {
             .lose(color, CastlingSide::Queenside)
     }
 
-    /// Lose rights if the given square is a rook's home square for this color.
+    /// Set the home file of the rook castling on this side, as Chess960 positions require.
+    pub const fn with_rook_file(self, color: Color, side: CastlingSide, file: u8) -> Self {
+        let shift = Self::file_shift(color, side);
+        CastlingRights((self.0 & !(0b111 << shift)) | ((file as u16 & 0b111) << shift))
+    }
+
+    /// Lose rights if the given square is the home square of one of this color's castling rooks.
     pub fn lose_for_rook_at(self, square: Square, color: Color) -> Self {
-        match CastlingSide::from_rook_square(square, color) {
-            Some(side) => self.lose(color, side),
-            None => self,
+        [CastlingSide::Kingside, CastlingSide::Queenside]
+            .into_iter()
+            .filter(|&side| self.has(color, side) && self.rook_square(color, side) == square)
+            .fold(self, |rights, side| rights.lose(color, side))
+    }
+
+    // --- Raw Encoding --- //
+    pub(crate) const fn bits(self) -> u16 { self.0 }
+    pub(crate) const fn from_bits(bits: u16) -> Self { Self(bits) }
+}
+
+// --- Parsing --- //
+impl CastlingRights {
+    /// Parse a FEN castling field against the board it belongs to. Besides `KQkq`, this accepts
+    /// the Chess960 notations: Shredder-FEN rook files (`HAha`) and X-FEN, where `K` and `Q`
+    /// name the outermost rook on that side of the king.
+    pub fn from_fen_field(text: &str, board: &Board) -> Result<Self, CastlingParseError> {
+        parse_field(text, Some(board))
+    }
+}
+
+fn parse_field(text: &str, board: Option<&Board>) -> Result<CastlingRights, CastlingParseError> {
+    if text.is_empty() { return Err(CastlingParseError::Empty); }
+    if text == "-" { return Ok(CastlingRights::none()); }
+
+    let mut rights = CastlingRights::none();
+    for c in text.chars() {
+        let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+        let king_file = board
+            .and_then(|board| board.king_square(color))
+            .filter(|king| king.rank() == color.home_rank())
+            .map(|king| king.file());
+
+        let (side, file) = match (PieceType::from_char(c), c.to_ascii_lowercase()) {
+            (Some(PieceType::King), _) => (CastlingSide::Kingside, None),
+            (Some(PieceType::Queen), _) => (CastlingSide::Queenside, None),
+            (_, 'a'..='h') if board.is_some() => {
+                let king_file = king_file.ok_or(CastlingParseError::NoKing(c))?;
+                let file = c.to_ascii_lowercase() as u8 - b'a';
+                (CastlingSide::of_rook(file, king_file), Some(file))
+            }
+            _ => return Err(CastlingParseError::BadChar(c)),
+        };
+        let file = file
+            .or_else(|| outermost_rook(board?, color, side, king_file?))
+            .unwrap_or(side.rook_source_file());
+
+        if rights.has(color, side) { return Err(CastlingParseError::Duplicate(c)); }
+        rights = rights.gain(color, side).with_rook_file(color, side, file);
+    }
+    Ok(rights)
+}
+
+/// File of the rook farthest from the king on one side of it, on the home rank.
+fn outermost_rook(board: &Board, color: Color, side: CastlingSide, king_file: u8) -> Option<u8> {
+    let rook = Some(Piece::new(PieceType::Rook, color));
+    let rank = color.home_rank();
+    match side {
+        CastlingSide::Kingside => (king_file + 1..8).rev().find(|&file| board[(rank, file)] == rook),
+        CastlingSide::Queenside => (0..king_file).find(|&file| board[(rank, file)] == rook),
+    }
+}
+
+// --- Traits --- //
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_castling(self, f) }
+}
+
+impl Debug for CastlingRights {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_castling(self, f) }
+}
+
+impl FromStr for CastlingRights {
+    type Err = CastlingParseError;
+
+    /// `-` or a duplicate-free set of `KQkq` letters in any order, with standard rook files.
+    /// Rook-file letters need the board; see `from_fen_field`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_field(s, None)
+    }
+}
+
+impl Display for CastlingParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            CastlingParseError::Empty        => write!(f, "empty castling field"),
+            CastlingParseError::BadChar(c)   => write!(f, "invalid castling character '{}'", c),
+            CastlingParseError::Duplicate(c) => write!(f, "castling right '{}' listed twice", c),
+            CastlingParseError::NoKing(c)    => write!(f, "castling file '{}' without a king on its home rank", c),
         }
     }
 }
+
+impl Error for CastlingParseError {}
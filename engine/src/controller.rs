@@ -0,0 +1,171 @@
+//! The game and search state shared by the protocol front ends. Each front end parses its own
+//! commands and words its own output; the controller keeps the game, runs searches on a worker
+//! thread, and hands the outcome back in the protocol's words.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::game::Game;
+use crate::search::{Search, SearchInfo, SearchLimits, SearchResult};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// How a front end words what the search reports.
+pub(crate) trait Report: Clone + Send + 'static {
+    /// The line sent after each completed iteration, or None to stay quiet.
+    fn info(&self, info: &SearchInfo) -> Option<String>;
+
+    /// The line, or lines, announcing the move chosen. `game` already has the move played if
+    /// the search was started to play it.
+    fn result(&self, result: &SearchResult, game: &Game) -> String;
+}
+
+/// Owns the game and the search between commands, lending the search to a worker thread for
+/// each `go` so the transposition table carries over from one move to the next.
+pub(crate) struct EngineController<W> {
+    game: Game,
+    idle: Option<Search>,
+    running: Option<JoinHandle<io::Result<(Search, Game)>>>,
+    /// Whether the running search plays its move in the game, as an XBoard engine does.
+    plays_move: bool,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    /// Raised by `abort`: the search ends without playing or announcing its move.
+    discard: Arc<AtomicBool>,
+    output: Arc<Mutex<W>>,
+}
+
+// ============================================================================
+// EngineController
+// ============================================================================
+
+impl<W: Write + Send + 'static> EngineController<W> {
+    pub(crate) fn new(output: W) -> Self {
+        let search = Search::new();
+        let (stop, ponder) = (search.stop_handle(), search.ponder_handle());
+        EngineController {
+            game: Game::new(),
+            idle: Some(search),
+            running: None,
+            plays_move: false,
+            stop,
+            ponder,
+            discard: Arc::new(AtomicBool::new(false)),
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    /// The game, once a running search that plays its move has played it. A search that only
+    /// reports keeps running, so the game may change under it.
+    pub(crate) fn game(&mut self) -> io::Result<&mut Game> {
+        if self.plays_move { self.wait()?; }
+        Ok(&mut self.game)
+    }
+
+    /// Start searching the current position once any search still running has finished. With
+    /// `plays_move` the chosen move is played in the game before `report` announces it. A
+    /// ponder search holds its result back until `ponderhit` or `stop`, even if it finishes
+    /// first.
+    pub(crate) fn go(&mut self, limits: SearchLimits, report: impl Report, plays_move: bool) -> io::Result<()> {
+        self.wait()?;
+        let mut search = self.idle.take().expect("search is idle after wait");
+        let mut game = self.game.clone();
+        let output = Arc::clone(&self.output);
+        // Raised here rather than by the search thread, so an early `ponderhit` is not lost
+        if limits.ponder { self.ponder.store(true, Ordering::Relaxed); }
+        let (ponder, discard) = (Arc::clone(&self.ponder), Arc::clone(&self.discard));
+
+        let (info_output, info_report) = (Arc::clone(&self.output), report.clone());
+        search.set_info_callback(move |info| {
+            // A failed write surfaces with the result; there is nowhere to report it from here
+            if let Some(line) = info_report.info(&info) { let _ = send(&info_output, &line); }
+        });
+
+        let think = move || {
+            let result = search.best_move_in(&game, limits);
+            while ponder.load(Ordering::Relaxed) { thread::sleep(Duration::from_millis(1)); }
+            if discard.load(Ordering::Relaxed) { return Ok((search, game)); }
+            if plays_move && let Some(mv) = result.best_move {
+                game.play(mv).expect("the search returns legal moves");
+            }
+            send(&output, &report.result(&result, &game))?;
+            Ok((search, game))
+        };
+        self.plays_move = plays_move;
+        // wasm32 has no threads, so there a search runs to completion before the next command
+        if cfg!(target_arch = "wasm32") {
+            let (search, game) = think()?;
+            self.finish(search, game);
+        } else {
+            self.running = Some(thread::spawn(think));
+        }
+        Ok(())
+    }
+
+    /// Search `game`'s current position on this thread, once any running search has finished,
+    /// and return the result without announcing it. The table carries over as it does between
+    /// `go`s, so analysing the positions of a game in order reuses what each search learned.
+    pub(crate) fn analyse(&mut self, game: &Game, limits: SearchLimits) -> io::Result<SearchResult> {
+        Ok(self.wait()?.best_move_in(game, limits))
+    }
+
+    /// The opponent played the predicted move: the ponder search goes on under the clock.
+    pub(crate) fn ponderhit(&self) {
+        self.ponder.store(false, Ordering::Relaxed);
+    }
+
+    /// Interrupt the running search, if any, and wait for its result.
+    pub(crate) fn stop(&mut self) -> io::Result<()> {
+        self.ponder.store(false, Ordering::Relaxed);
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait()?;
+        // The flag may have gone up after the search had already finished on its own
+        self.stop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Interrupt the running search, if any, leaving its move unplayed and unannounced.
+    pub(crate) fn abort(&mut self) -> io::Result<()> {
+        self.discard.store(true, Ordering::Relaxed);
+        self.stop()?;
+        self.discard.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The search, unless one is still running. A search thread that has finished is collected
+    /// first, so this never blocks.
+    pub(crate) fn idle(&mut self) -> io::Result<Option<&mut Search>> {
+        if self.running.as_ref().is_some_and(|handle| handle.is_finished()) { self.wait()?; }
+        Ok(if self.running.is_none() { self.idle.as_mut() } else { None })
+    }
+
+    /// Block until no search is running, then hand out the idle search.
+    pub(crate) fn wait(&mut self) -> io::Result<&mut Search> {
+        if let Some(handle) = self.running.take() {
+            let (search, game) = handle.join().expect("search thread panicked")?;
+            self.finish(search, game);
+        }
+        Ok(self.idle.as_mut().expect("search is idle after wait"))
+    }
+
+    fn finish(&mut self, search: Search, game: Game) {
+        self.idle = Some(search);
+        if self.plays_move { self.game = game; }
+        self.plays_move = false;
+    }
+
+    pub(crate) fn send(&self, line: &str) -> io::Result<()> {
+        send(&self.output, line)
+    }
+}
+
+fn send(output: &Mutex<impl Write>, line: &str) -> io::Result<()> {
+    let mut output = output.lock().expect("output lock poisoned");
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
@@ -2,7 +2,10 @@
 
 use std::fmt::{Formatter, Result};
 
-use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::bits::SquareSet;
+use crate::board::{Board, BoardDisplay, Color, Piece, PieceType, Square};
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::mv::{Move, MoveType};
 
 // ============================================================================
 // Unicode Piece Characters
@@ -24,6 +27,12 @@ const BLACK_PAWN    : char = '♟';
 
 const EMPTY         : char = '·';
 
+/// Background for highlighted squares in ANSI mode, and the reset after each.
+#[cfg(feature = "color")]
+const ANSI_HIGHLIGHT: &str = "\x1b[43m";
+#[cfg(feature = "color")]
+const ANSI_RESET: &str = "\x1b[0m";
+
 // ============================================================================
 // Public Rendering Functions
 // ============================================================================
@@ -32,30 +41,117 @@ pub fn render_square(square: &Square, f: &mut Formatter) -> Result {
     write!(f, "{}{}", (b'a' + square.file()) as char, square.rank() + 1)
 }
 
+pub fn render_color(color: &Color, f: &mut Formatter) -> Result {
+    write!(f, "{}", match color { Color::White => 'w', Color::Black => 'b' })
+}
+
+pub fn render_piece_type(piece_type: &PieceType, f: &mut Formatter) -> Result {
+    write!(f, "{}", piece_type.to_char_upper())
+}
+
+/// `KQkq` order, or `-` when no rights remain. If any castling rook stands off the a- and
+/// h-files, as in Chess960, every right is written as its rook's file instead (Shredder-FEN).
+pub fn render_castling(rights: &CastlingRights, f: &mut Formatter) -> Result {
+    if rights.is_empty() { return write!(f, "-"); }
+    let held = [Color::White, Color::Black]
+        .into_iter()
+        .flat_map(|color| [(color, CastlingSide::Kingside), (color, CastlingSide::Queenside)])
+        .filter(|&(color, side)| rights.has(color, side));
+    let shredder = held.clone().any(|(color, side)| rights.rook_file(color, side) != side.rook_source_file());
+
+    for (color, side) in held {
+        let c = match side {
+            _ if shredder => (b'A' + rights.rook_file(color, side)) as char,
+            CastlingSide::Kingside  => 'K',
+            CastlingSide::Queenside => 'Q',
+        };
+        write!(f, "{}", if color == Color::White { c } else { c.to_ascii_lowercase() })?;
+    }
+    Ok(())
+}
+
 pub fn render_piece(piece: &Piece, f: &mut Formatter) -> Result {
     write!(f, "{}", piece_char(piece))
 }
 
+/// Long algebraic notation, with castling written as the king's move to its castled square.
+pub fn render_move(mv: &Move, f: &mut Formatter) -> Result {
+    write!(f, "{}{}", mv.source(), mv.destination())?;
+    match mv.move_type() {
+        MoveType::Promotion => write!(f, "{}", promotion_char(mv.promoted_type())),
+        _ => Ok(()),
+    }
+}
+
+/// `White Knight`.
+pub fn debug_piece(piece: &Piece, f: &mut Formatter) -> Result {
+    write!(f, "{:?} {:?}", piece.color(), piece.piece_type())
+}
+
+/// `e2e4 (Normal)`, or `e7e8=Q (Promotion)` with the promoted piece spelled out.
+pub fn debug_move(mv: &Move, f: &mut Formatter) -> Result {
+    write!(f, "{}{}", mv.source(), mv.destination())?;
+    if mv.move_type() == MoveType::Promotion { write!(f, "={}", mv.promoted_type())?; }
+    write!(f, " ({:?})", mv.move_type())
+}
+
 pub fn render_board(board: &Board, f: &mut Formatter) -> Result {
+    render_board_with(&board.display(), f)
+}
+
+/// Unicode pieces from White's side unless asked otherwise. Brackets around a highlighted
+/// square take the place of the spaces beside it, so the columns stay aligned; two
+/// highlighted neighbours share a `|`.
+pub fn render_board_with(options: &BoardDisplay, f: &mut Formatter) -> Result {
+    let coords = if options.flipped { "  h g f e d c b a" } else { "  a b c d e f g h" };
+    let ranks: [u8; 8] = if options.flipped { [0, 1, 2, 3, 4, 5, 6, 7] } else { [7, 6, 5, 4, 3, 2, 1, 0] };
+    let files: [u8; 8] = if options.flipped { [7, 6, 5, 4, 3, 2, 1, 0] } else { [0, 1, 2, 3, 4, 5, 6, 7] };
+    #[cfg(feature = "color")]
+    let brackets = !options.ansi;
+    #[cfg(not(feature = "color"))]
+    let brackets = true;
+    let marked = |file: Option<&u8>, rank: u8| {
+        brackets && file.is_some_and(|&file| options.highlight.contains(Square::from_coords(rank, file)))
+    };
+
+    writeln!(f, "{}", coords)?;
+    for rank in ranks {
+        write!(f, "{}", rank + 1)?;
+        for (i, &file) in files.iter().enumerate() {
+            let before = i.checked_sub(1).map(|j| &files[j]);
+            write!(f, "{}", separator(marked(before, rank), marked(Some(&file), rank)))?;
+
+            let square = Square::from_coords(rank, file);
+            let cell = match options.board[square] {
+                Some(piece) if options.ascii => piece.piece_type().to_char(piece.color()),
+                Some(piece) => piece_char(&piece),
+                None if options.ascii => '.',
+                None => EMPTY,
+            };
+            #[cfg(feature = "color")]
+            if options.ansi && options.highlight.contains(square) {
+                write!(f, "{}{}{}", ANSI_HIGHLIGHT, cell, ANSI_RESET)?;
+                continue;
+            }
+            write!(f, "{}", cell)?;
+        }
+        writeln!(f, "{}{}", separator(marked(files.last(), rank), false), rank + 1)?;
+    }
+    write!(f, "{}", coords)
+}
+
+/// The board diagram with `X` on the set's squares, for debugging bitboards.
+pub fn render_square_set(set: &SquareSet, f: &mut Formatter) -> Result {
     const COORDS: &str = "  a b c d e f g h";
-    // Top coordinate row
     writeln!(f, "{}", COORDS)?;
-    // Render ranks from 8 down to 1 (White's perspective)
     for rank in (0..8).rev() {
         write!(f, "{} ", rank + 1)?;
-
         for file in 0..8 {
-            let square = Square::from_coords(rank, file);
-            match board[square] {
-                Some(piece) => write!(f, "{} ", piece)?,
-                None => write!(f, "{} ", EMPTY)?,
-            }
+            let member = set.contains(Square::from_coords(rank, file));
+            write!(f, "{} ", if member { 'X' } else { EMPTY })?;
         }
-
         writeln!(f, "{}", rank + 1)?;
     }
-
-    // Bottom coordinate row
     write!(f, "{}", COORDS)
 }
 
@@ -63,6 +159,26 @@ pub fn render_board(board: &Board, f: &mut Formatter) -> Result {
 // Private Helpers
 // ============================================================================
 
+/// What goes between two cells, given whether the left and right ones are highlighted.
+fn separator(left: bool, right: bool) -> char {
+    match (left, right) {
+        (true, true)  => '|',
+        (true, false) => ']',
+        (false, true) => '[',
+        (false, false) => ' ',
+    }
+}
+
+fn promotion_char(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Knight   => 'n',
+        PieceType::Bishop   => 'b',
+        PieceType::Rook     => 'r',
+        PieceType::Queen    => 'q',
+        _ => unreachable!(),
+    }
+}
+
 fn piece_char(piece: &Piece) -> char {
     match (piece.color(), piece.piece_type()) {
         (Color::White, PieceType::King)     => WHITE_KING,
@@ -0,0 +1,233 @@
+//! EPD (Extended Position Description) records and test-suite running.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::fen::FenError;
+use crate::mv::Move;
+use crate::notation::parse_san;
+use crate::search::{Search, SearchLimits, SearchOptions};
+use crate::state::State;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One EPD line: a position and its operations. Every opcode maps to its operands, with
+/// string operands unquoted.
+#[derive(Clone, Debug)]
+pub struct Epd {
+    state: State,
+    opcodes: BTreeMap<String, Vec<String>>,
+    best_moves: Vec<Move>,
+    avoid_moves: Vec<Move>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EpdError {
+    /// The four position fields do not form a valid position.
+    BadPosition(FenError),
+    /// A string operand is missing its closing quote.
+    UnterminatedString,
+    /// A `bm` or `am` operand is not a legal SAN move in the position.
+    BadMove { opcode: String, san: String },
+}
+
+/// How a search did on one suite position.
+#[derive(Clone, Debug)]
+pub struct SuiteEntry {
+    pub id: Option<String>,
+    pub best_move: Option<Move>,
+    /// Whether the move is among `bm` and not among `am`. Positions with neither are not
+    /// targets and never count as solved.
+    pub solved: bool,
+}
+
+/// Results of running a whole suite, in file order.
+#[derive(Clone, Debug, Default)]
+pub struct SuiteReport {
+    pub entries: Vec<SuiteEntry>,
+    /// Lines that failed to parse, with their 1-based line numbers.
+    pub errors: Vec<(usize, EpdError)>,
+}
+
+// ============================================================================
+// Epd — Parsing
+// ============================================================================
+
+impl Epd {
+    /// Parse placement, side, castling, and en passant, then `;`-terminated operations. The
+    /// `hmvc` and `fmvn` opcodes, when present, set the clocks.
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        let (position, operations) = split_fields(line.trim(), 4);
+        let opcodes = parse_operations(operations)?;
+
+        let clock = |opcode: &str| opcodes.get(opcode).and_then(|v| v.first()).map_or("", String::as_str);
+        let fen = format!("{} {} {}", position, clock("hmvc"), clock("fmvn"));
+        let state = State::from_fen(&fen).map_err(EpdError::BadPosition)?;
+
+        let best_moves = parse_moves(&state, &opcodes, "bm")?;
+        let avoid_moves = parse_moves(&state, &opcodes, "am")?;
+        Ok(Epd { state, opcodes, best_moves, avoid_moves })
+    }
+}
+
+/// The first `count` whitespace-separated fields, rejoined by single spaces, and the rest.
+fn split_fields(text: &str, count: usize) -> (String, &str) {
+    let mut fields = Vec::new();
+    let mut rest = text;
+    while fields.len() < count && !rest.is_empty() {
+        let (field, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        fields.push(field);
+        rest = tail.trim_start();
+    }
+    (fields.join(" "), rest)
+}
+
+fn parse_operations(text: &str) -> Result<BTreeMap<String, Vec<String>>, EpdError> {
+    let mut opcodes = BTreeMap::new();
+    let mut operands: Vec<String> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' => {
+                if !operands.is_empty() {
+                    let opcode = operands.remove(0);
+                    opcodes.insert(opcode, std::mem::take(&mut operands));
+                }
+            }
+            '"' => {
+                let mut operand = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => operand.push(c),
+                        None => return Err(EpdError::UnterminatedString),
+                    }
+                }
+                operands.push(operand);
+            }
+            _ => {
+                let mut operand = c.to_string();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != ';') { operand.push(c); }
+                operands.push(operand);
+            }
+        }
+    }
+    // Tolerate a final operation without its semicolon
+    if !operands.is_empty() {
+        let opcode = operands.remove(0);
+        opcodes.insert(opcode, operands);
+    }
+    Ok(opcodes)
+}
+
+fn parse_moves(state: &State, opcodes: &BTreeMap<String, Vec<String>>, opcode: &str) -> Result<Vec<Move>, EpdError> {
+    let Some(operands) = opcodes.get(opcode) else { return Ok(Vec::new()) };
+    operands.iter()
+        .map(|san| parse_san(san, state).map_err(|_| EpdError::BadMove {
+            opcode: opcode.to_string(),
+            san: san.clone(),
+        }))
+        .collect()
+}
+
+// ============================================================================
+// Epd — Accessors
+// ============================================================================
+
+impl Epd {
+    pub fn state(&self) -> &State { &self.state }
+
+    /// Operands of any opcode, or None when it is absent.
+    pub fn opcode(&self, opcode: &str) -> Option<&[String]> {
+        self.opcodes.get(opcode).map(Vec::as_slice)
+    }
+
+    pub fn best_moves(&self) -> &[Move] { &self.best_moves }
+    pub fn avoid_moves(&self) -> &[Move] { &self.avoid_moves }
+
+    pub fn id(&self) -> Option<&str> {
+        self.opcode("id")?.first().map(String::as_str)
+    }
+
+    /// Centipawn evaluation from the side to move's perspective.
+    pub fn ce(&self) -> Option<i32> {
+        self.opcode("ce")?.first()?.parse().ok()
+    }
+
+    /// Whether `mv` satisfies the record: among `bm` if given, and not among `am`.
+    pub fn is_solved_by(&self, mv: Move) -> bool {
+        if self.best_moves.is_empty() && self.avoid_moves.is_empty() { return false; }
+        (self.best_moves.is_empty() || self.best_moves.contains(&mv)) && !self.avoid_moves.contains(&mv)
+    }
+}
+
+// ============================================================================
+// Suite Running
+// ============================================================================
+
+/// Search every position of an EPD file with the same limits and fresh hash table, and report
+/// which `bm`/`am` targets were met. Blank lines and `#` comments are skipped.
+pub fn run_epd_suite(path: impl AsRef<Path>, limits: SearchLimits) -> io::Result<SuiteReport> {
+    run_epd_suite_with(path, limits, SearchOptions::default())
+}
+
+/// `run_epd_suite` with the given pruning switches, to compare a suite's results with some
+/// techniques turned off.
+pub fn run_epd_suite_with(path: impl AsRef<Path>, limits: SearchLimits, options: SearchOptions) -> io::Result<SuiteReport> {
+    let text = fs::read_to_string(path)?;
+    let mut search = Search::new();
+    search.set_options(options);
+    let mut report = SuiteReport::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        let epd = match Epd::parse(line) {
+            Ok(epd) => epd,
+            Err(err) => { report.errors.push((index + 1, err)); continue; }
+        };
+        search.clear_hash();
+        let best_move = search.best_move(epd.state(), limits.clone()).best_move;
+        report.entries.push(SuiteEntry {
+            id: epd.id().map(str::to_string),
+            best_move,
+            solved: best_move.is_some_and(|mv| epd.is_solved_by(mv)),
+        });
+    }
+    Ok(report)
+}
+
+impl SuiteReport {
+    pub fn solved(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.solved).count()
+    }
+
+    pub fn total(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for EpdError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            EpdError::BadPosition(e)          => write!(f, "invalid position: {}", e),
+            EpdError::UnterminatedString      => write!(f, "unterminated string operand"),
+            EpdError::BadMove { opcode, san } => write!(f, "invalid {} move '{}'", opcode, san),
+        }
+    }
+}
+
+impl Error for EpdError {}
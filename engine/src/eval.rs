@@ -0,0 +1,1720 @@
+//! Static evaluation: material and its imbalance, tapered piece-square tables, pawn structure, passed pawns, king
+//! safety and tropism, piece placement, threats, and space, split into named terms that `evaluate` sums and `explain` reports one by one.
+
+use std::fmt;
+
+use crate::attacks::{king_attacks, piece_attacks};
+use crate::bits::{bit, squares, SquareSet};
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::state::State;
+use crate::zobrist::{hash_pawns, piece_key};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A middlegame and an endgame value, blended by the game phase.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tapered {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+/// One evaluation term.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Material,
+    Imbalance,
+    PieceSquare,
+    PawnStructure,
+    PassedPawns,
+    KingSafety,
+    Tropism,
+    Pieces,
+    Threats,
+    Space,
+}
+
+/// A term's contribution for each side, before tapering.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TermScore {
+    pub term: Term,
+    pub white: Tapered,
+    pub black: Tapered,
+}
+
+/// Every term of an evaluation. `Display` prints it as a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalBreakdown {
+    pub terms: Vec<TermScore>,
+    /// From 0, a bare endgame, to `MAX_PHASE` with all the starting material.
+    pub phase: i32,
+    /// Out of `SCALE_NORMAL`, how much of the endgame total counts.
+    pub scale: u8,
+    /// Past `DAMPING_START`, the score fades as this nears the fifty-move rule.
+    pub halfmove_clock: u8,
+    pub to_move: Color,
+}
+
+/// Every tunable weight of the evaluation. `DEFAULT_PARAMS` holds the built-in values; the
+/// tuner adjusts a copy, which round-trips through `to_text` and `from_text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [i32; 6],
+    /// Per own piece of the row type, per own and then per enemy piece of the column type, by
+    /// `PieceType` discriminant up to the pawn.
+    pub imbalance_ours: [[i32; 5]; 5],
+    pub imbalance_theirs: [[i32; 5]; 5],
+    /// Per knight, per own pawn above five; below five it costs.
+    pub knight_pawns: i32,
+    /// Laid out as the board reads from White's side, as `table_index` expects.
+    pub mg_tables: [[i32; 64]; 6],
+    pub eg_tables: [[i32; 64]; 6],
+    pub doubled: Tapered,
+    pub isolated: Tapered,
+    pub backward: Tapered,
+    pub protected_passer: Tapered,
+    /// By rank from the pawn's own side.
+    pub passed: [Tapered; 8],
+    pub connected: [Tapered; 8],
+    /// Passed pawn modifiers, each scaled by `PASSER_RANK_SCALE`: per square of king distance
+    /// from the stop square, own then enemy; a path to promotion no piece stands on or enemy
+    /// attacks; a blockading enemy piece, and a knight on top; another passer beside it.
+    pub passer_king_distance: [Tapered; 2],
+    pub passer_free_path: Tapered,
+    pub passer_blockaded: Tapered,
+    pub passer_knight_blockade: Tapered,
+    pub passer_connected: Tapered,
+    /// Per file in front of a castled king, by the rank from its own side of the nearest own
+    /// pawn there, then of the nearest enemy one; 0 when there is none.
+    pub shield: [Tapered; 8],
+    pub storm: [Tapered; 8],
+    pub open_file: Tapered,
+    pub semi_open_file: Tapered,
+    /// An uncastled king on the d- or e-file, per open central file and enemy rook or queen,
+    /// while the enemy queen is on.
+    pub center_king: Tapered,
+    /// Attack units per king-zone square attacked.
+    pub attack_weights: [i32; 6],
+    /// Middlegame bonus per square of closeness to the enemy king, by piece type.
+    pub tropism: [i32; 6],
+    /// A knight, then a bishop, on an outpost; central files add `central_outpost`.
+    pub outpost: [Tapered; 2],
+    pub central_outpost: Tapered,
+    /// Per central pawn an enemy pawn fixes on the bishop's own square color.
+    pub bad_bishop: Tapered,
+    /// By attacker, then by victim from knight to queen: a pawn's attack on any piece, and any
+    /// other piece's on a more valuable one that no pawn defends.
+    pub threats: [[Tapered; 4]; 5],
+    /// Per own piece an enemy pawn attacks, on top of the pawn's threat.
+    pub pawn_attacked: Tapered,
+    /// Middlegame bonus per safe square of central space, in sixteenths per piece that can use it.
+    pub space: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    /// A parameter name that `EvalParams` does not have.
+    UnknownName(String),
+    /// A value that is not an integer, or a value before any name.
+    BadValue(String),
+    /// A parameter given with the wrong number of values.
+    WrongCount { name: String, expected: usize, found: usize },
+}
+
+/// Per-term, per-side sums filled in by a single pass over the position.
+struct Accumulator {
+    scores: [[Tapered; 2]; Term::COUNT],
+    phase: i32,
+}
+
+/// Pawn-structure and king shelter scores by pawns-only hash, with the king buckets mixed in.
+/// Pawns move rarely within a search, so most lookups hit. Each search thread keeps its own.
+pub struct PawnTable {
+    entries: Box<[PawnEntry]>,
+}
+
+/// Imbalance scores by the count of each piece type on each side, which `material_key` packs
+/// exactly. Each search thread keeps its own.
+pub struct MaterialTable {
+    entries: Box<[MaterialEntry]>,
+}
+
+#[derive(Copy, Clone, Default)]
+struct MaterialEntry {
+    key: u64,
+    scores: [i32; 2],
+}
+
+#[derive(Copy, Clone, Default)]
+struct PawnEntry {
+    key: u64,
+    scores: PawnScores,
+}
+
+/// Everything that depends on the pawns alone, given which bucket each king stands in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct PawnScores {
+    structure: [Tapered; 2],
+    shelter: [Tapered; 2],
+}
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Score the position in centipawns from the side to move's perspective.
+pub fn evaluate(state: &State) -> i32 {
+    evaluate_with(state, &DEFAULT_PARAMS)
+}
+
+/// `evaluate` with other weights.
+pub fn evaluate_with(state: &State, params: &EvalParams) -> i32 {
+    sum(state, accumulate(state, params, pawn_scores(&state.board, params), imbalance(&state.board, params)))
+}
+
+/// `evaluate`, with pawn structure and king shelter looked up in or added to `pawns`, and the
+/// imbalance in `material`. The score is identical.
+pub fn evaluate_cached(state: &State, pawns: &mut PawnTable, material: &mut MaterialTable) -> i32 {
+    sum(state, accumulate(state, &DEFAULT_PARAMS, pawns.probe(&state.board), material.probe(&state.board)))
+}
+
+/// The terms behind `evaluate`, which it always agrees with.
+pub fn explain(state: &State) -> EvalBreakdown {
+    let acc = accumulate(state, &DEFAULT_PARAMS, pawn_scores(&state.board, &DEFAULT_PARAMS), imbalance(&state.board, &DEFAULT_PARAMS));
+    let scale = scale_factor(state, acc.total().eg);
+    let terms = Term::ALL.iter().zip(acc.scores)
+        .map(|(&term, [white, black])| TermScore { term, white, black })
+        .collect();
+    EvalBreakdown { terms, phase: acc.phase, scale, halfmove_clock: state.halfmove_clock, to_move: state.to_move }
+}
+
+/// Material value of a piece type in centipawns. The king has none.
+pub const fn piece_value(piece_type: PieceType) -> i32 {
+    DEFAULT_PARAMS.piece_values[piece_type as usize]
+}
+
+// ============================================================================
+// Evaluation Terms
+// ============================================================================
+
+/// Every term, with pawn structure, king shelter, and imbalance as the caller supplies them.
+fn accumulate(state: &State, params: &EvalParams, pawns: PawnScores, imbalance: [i32; 2]) -> Accumulator {
+    let mut acc = Accumulator { scores: [[Tapered::default(); 2]; Term::COUNT], phase: 0 };
+    acc.scores[Term::Imbalance as usize] = imbalance.map(|score| Tapered::new(score, score));
+    acc.scores[Term::PawnStructure as usize] = pawns.structure;
+    acc.scores[Term::KingSafety as usize] = pawns.shelter;
+
+    for (sq, piece) in state.board.pieces() {
+        let kind = piece.piece_type() as usize;
+        let index = table_index(sq, piece.color());
+
+        let value = params.piece_values[kind];
+        acc.add(Term::Material, piece.color(), Tapered::new(value, value));
+        acc.add(Term::PieceSquare, piece.color(), Tapered::new(params.mg_tables[kind][index], params.eg_tables[kind][index]));
+        acc.phase += PHASE_WEIGHTS[kind];
+    }
+
+    for color in [Color::White, Color::Black] {
+        acc.add(Term::PassedPawns, color, passed_pawn_terms(&state.board, params, color));
+        acc.add(Term::KingSafety, color, king_safety(&state.board, params, color));
+        acc.add(Term::Tropism, color, tropism(&state.board, params, color));
+        acc.add(Term::Pieces, color, piece_placement(&state.board, params, color));
+        acc.add(Term::Threats, color, threats(&state.board, params, color));
+        acc.add(Term::Space, color, space(&state.board, params, color));
+    }
+
+    // Promotions can push the material count past the opening total
+    acc.phase = acc.phase.min(MAX_PHASE);
+    acc
+}
+
+fn sum(state: &State, acc: Accumulator) -> i32 {
+    let total = acc.total();
+    let score = total.taper_scaled(acc.phase, scale_factor(state, total.eg));
+    from_side_to_move(state.to_move, damped(score, state.halfmove_clock))
+}
+
+/// `score` faded linearly from its full value at `DAMPING_START` to nothing at the fifty-move
+/// rule, so that a won position that makes no progress looks less won than one that pushes a
+/// pawn or captures and resets the clock.
+fn damped(score: i32, halfmove_clock: u8) -> i32 {
+    if halfmove_clock <= DAMPING_START { return score; }
+    let left = 100 - (halfmove_clock as i32).min(100);
+    score * left / (100 - DAMPING_START as i32)
+}
+
+impl Accumulator {
+    /// Every term, White's less Black's.
+    fn total(&self) -> Tapered {
+        self.scores.iter().fold(Tapered::default(), |sum, [white, black]| sum + *white - *black)
+    }
+
+    fn add(&mut self, term: Term, color: Color, score: Tapered) {
+        let side = &mut self.scores[term as usize][color as usize];
+        *side = *side + score;
+    }
+}
+
+const fn from_side_to_move(to_move: Color, white_score: i32) -> i32 {
+    match to_move { Color::White => white_score, Color::Black => -white_score }
+}
+
+// ============================================================================
+// Tapered
+// ============================================================================
+
+impl Tapered {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Tapered { mg, eg }
+    }
+
+    /// Blend the two values, all middlegame at `MAX_PHASE` and all endgame at 0.
+    pub const fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+
+    /// `taper`, with the endgame value cut to `scale` out of `SCALE_NORMAL`. Scaling pulls the
+    /// blend toward a draw, never past it.
+    pub const fn taper_scaled(self, phase: i32, scale: u8) -> i32 {
+        let full = self.taper(phase);
+        let scaled = Tapered::new(self.mg, self.eg * scale as i32 / SCALE_NORMAL as i32).taper(phase);
+        if full.signum() * scaled.signum() < 0 { 0 } else { scaled }
+    }
+}
+
+impl std::ops::Add for Tapered {
+    type Output = Tapered;
+    fn add(self, other: Tapered) -> Tapered { Tapered::new(self.mg + other.mg, self.eg + other.eg) }
+}
+
+impl std::ops::Mul<i32> for Tapered {
+    type Output = Tapered;
+    fn mul(self, n: i32) -> Tapered { Tapered::new(self.mg * n, self.eg * n) }
+}
+
+impl std::ops::Sub for Tapered {
+    type Output = Tapered;
+    fn sub(self, other: Tapered) -> Tapered { Tapered::new(self.mg - other.mg, self.eg - other.eg) }
+}
+
+// ============================================================================
+// Term and EvalBreakdown
+// ============================================================================
+
+impl Term {
+    const COUNT: usize = 10;
+    pub const ALL: [Term; Self::COUNT] = [
+        Term::Material, Term::Imbalance, Term::PieceSquare, Term::PawnStructure, Term::PassedPawns, Term::KingSafety, Term::Tropism,
+        Term::Pieces, Term::Threats, Term::Space,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Term::Material      => "Material",
+            Term::Imbalance     => "Imbalance",
+            Term::PieceSquare   => "Piece-square",
+            Term::PawnStructure => "Pawns",
+            Term::PassedPawns   => "Passed pawns",
+            Term::KingSafety    => "King safety",
+            Term::Tropism       => "Tropism",
+            Term::Pieces        => "Pieces",
+            Term::Threats       => "Threats",
+            Term::Space         => "Space",
+        }
+    }
+}
+
+impl TermScore {
+    /// White's contribution less Black's.
+    pub fn total(&self) -> Tapered {
+        self.white - self.black
+    }
+}
+
+impl EvalBreakdown {
+    /// Sum of every term, White's perspective, before tapering.
+    pub fn total(&self) -> Tapered {
+        self.terms.iter().fold(Tapered::default(), |sum, term| sum + term.total())
+    }
+
+    /// The tapered total from White's perspective, its endgame part scaled and the whole
+    /// damped by the halfmove clock.
+    pub fn white_score(&self) -> i32 {
+        damped(self.total().taper_scaled(self.phase, self.scale), self.halfmove_clock)
+    }
+
+    /// The tapered total from the side to move's perspective; equal to `evaluate`.
+    pub fn score(&self) -> i32 {
+        from_side_to_move(self.to_move, self.white_score())
+    }
+}
+
+/// A table of middlegame and endgame values per term and side, in centipawns, followed by the
+/// phase, the endgame scale, the halfmove clock, and the final score.
+impl fmt::Display for EvalBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "         Term |    White    |    Black    |    Total")?;
+        writeln!(f, "              |   MG    EG  |   MG    EG  |   MG    EG")?;
+        writeln!(f, " -------------+-------------+-------------+------------")?;
+        for term in &self.terms {
+            let total = term.total();
+            writeln!(
+                f, " {:>12} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+                term.term.name(), term.white.mg, term.white.eg, term.black.mg, term.black.eg, total.mg, total.eg,
+            )?;
+        }
+        let total = self.total();
+        writeln!(f, " -------------+-------------+-------------+------------")?;
+        writeln!(f, " {:>12} |             |             | {:>5} {:>5}", "Total", total.mg, total.eg)?;
+        writeln!(f)?;
+        writeln!(f, "Phase: {}/{}", self.phase, MAX_PHASE)?;
+        writeln!(f, "Scale: {}/{}", self.scale, SCALE_NORMAL)?;
+        writeln!(f, "Halfmove clock: {}", self.halfmove_clock)?;
+        writeln!(f, "Evaluation: {:+} (white side)", self.white_score())?;
+        write!(f, "Evaluation: {:+} (side to move)", self.score())
+    }
+}
+
+// ============================================================================
+// EvalParams — Access
+// ============================================================================
+
+/// Names of the per-type tables in the text format, by `PieceType` discriminant.
+const MG_TABLE_NAMES: [&str; 6] = ["mg_knight", "mg_bishop", "mg_rook", "mg_queen", "mg_pawn", "mg_king"];
+const EG_TABLE_NAMES: [&str; 6] = ["eg_knight", "eg_bishop", "eg_rook", "eg_queen", "eg_pawn", "eg_king"];
+
+impl EvalParams {
+    /// Number of individual values, the indices `get` and `set` accept.
+    pub fn len(&self) -> usize {
+        let mut len = 0;
+        self.clone().visit(&mut |_, _| len += 1);
+        len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value at a flat index, in text-format order.
+    pub fn get(&self, index: usize) -> i32 {
+        let mut found = None;
+        self.clone().visit_indexed(&mut |i, _, value| if i == index { found = Some(*value) });
+        found.expect("parameter index out of range")
+    }
+
+    pub fn set(&mut self, index: usize, new_value: i32) {
+        self.visit_indexed(&mut |i, _, value| if i == index { *value = new_value });
+    }
+
+    /// The name of the parameter group the value at a flat index belongs to.
+    pub fn name(&self, index: usize) -> &'static str {
+        let mut found = None;
+        self.clone().visit_indexed(&mut |i, name, _| if i == index { found = Some(name) });
+        found.expect("parameter index out of range")
+    }
+
+    /// Every value in text-format order, with the name of its group. Tapered pairs are visited
+    /// middlegame first.
+    fn visit(&mut self, f: &mut impl FnMut(&'static str, &mut i32)) {
+        fn pair(f: &mut impl FnMut(&'static str, &mut i32), name: &'static str, score: &mut Tapered) {
+            f(name, &mut score.mg);
+            f(name, &mut score.eg);
+        }
+
+        for value in &mut self.piece_values { f("piece_values", value); }
+        for row in &mut self.imbalance_ours {
+            for value in row { f("imbalance_ours", value); }
+        }
+        for row in &mut self.imbalance_theirs {
+            for value in row { f("imbalance_theirs", value); }
+        }
+        f("knight_pawns", &mut self.knight_pawns);
+        for (name, table) in MG_TABLE_NAMES.iter().zip(&mut self.mg_tables) {
+            for value in table { f(name, value); }
+        }
+        for (name, table) in EG_TABLE_NAMES.iter().zip(&mut self.eg_tables) {
+            for value in table { f(name, value); }
+        }
+        pair(f, "doubled", &mut self.doubled);
+        pair(f, "isolated", &mut self.isolated);
+        pair(f, "backward", &mut self.backward);
+        pair(f, "protected_passer", &mut self.protected_passer);
+        for score in &mut self.passed { pair(f, "passed", score); }
+        for score in &mut self.connected { pair(f, "connected", score); }
+        for score in &mut self.passer_king_distance { pair(f, "passer_king_distance", score); }
+        pair(f, "passer_free_path", &mut self.passer_free_path);
+        pair(f, "passer_blockaded", &mut self.passer_blockaded);
+        pair(f, "passer_knight_blockade", &mut self.passer_knight_blockade);
+        pair(f, "passer_connected", &mut self.passer_connected);
+        for score in &mut self.shield { pair(f, "shield", score); }
+        for score in &mut self.storm { pair(f, "storm", score); }
+        pair(f, "open_file", &mut self.open_file);
+        pair(f, "semi_open_file", &mut self.semi_open_file);
+        pair(f, "center_king", &mut self.center_king);
+        for value in &mut self.attack_weights { f("attack_weights", value); }
+        for value in &mut self.tropism { f("tropism", value); }
+        for score in &mut self.outpost { pair(f, "outpost", score); }
+        pair(f, "central_outpost", &mut self.central_outpost);
+        pair(f, "bad_bishop", &mut self.bad_bishop);
+        for row in &mut self.threats {
+            for score in row { pair(f, "threats", score); }
+        }
+        pair(f, "pawn_attacked", &mut self.pawn_attacked);
+        f("space", &mut self.space);
+    }
+
+    fn visit_indexed(&mut self, f: &mut impl FnMut(usize, &'static str, &mut i32)) {
+        let mut index = 0;
+        self.visit(&mut |name, value| {
+            f(index, name, value);
+            index += 1;
+        });
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self { DEFAULT_PARAMS }
+}
+
+// ============================================================================
+// EvalParams — Text Format
+// ============================================================================
+
+impl EvalParams {
+    /// One group per line: its name, then its values. Tables continue over eight rows of
+    /// eight, a8 first.
+    pub fn to_text(&self) -> String {
+        let mut groups: Vec<(&'static str, Vec<i32>)> = Vec::new();
+        self.clone().visit(&mut |name, value| match groups.last_mut() {
+            Some((last, values)) if *last == name => values.push(*value),
+            _ => groups.push((name, vec![*value])),
+        });
+
+        let mut text = String::new();
+        for (name, values) in groups {
+            let row = |values: &[i32]| values.iter().map(|v| format!("{:>4}", v)).collect::<String>();
+            if values.len() == 64 {
+                text += name;
+                for rank in values.chunks(8) { text += &format!("\n   {}", row(rank)); }
+            } else {
+                text += &format!("{}{}", name, row(&values));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Parse `to_text` output. Groups may come in any order and whitespace is free; groups left
+    /// out keep their default values, and `#` starts a comment.
+    pub fn from_text(text: &str) -> Result<Self, ParamsError> {
+        let mut groups: Vec<(&str, Vec<i32>)> = Vec::new();
+        let tokens = text.lines().flat_map(|line| line.split('#').next().unwrap_or("").split_whitespace());
+        for token in tokens {
+            match token.parse::<i32>() {
+                Ok(value) => match groups.last_mut() {
+                    Some((_, values)) => values.push(value),
+                    None => return Err(ParamsError::BadValue(token.to_string())),
+                },
+                Err(_) if token.starts_with(|c: char| c.is_ascii_alphabetic()) => groups.push((token, Vec::new())),
+                Err(_) => return Err(ParamsError::BadValue(token.to_string())),
+            }
+        }
+
+        let mut params = DEFAULT_PARAMS;
+        let mut expected: Vec<(&'static str, usize)> = Vec::new();
+        params.clone().visit(&mut |name, _| match expected.last_mut() {
+            Some((last, count)) if *last == name => *count += 1,
+            _ => expected.push((name, 1)),
+        });
+        for (name, values) in &groups {
+            let &(_, count) = expected.iter().find(|(known, _)| known == name)
+                .ok_or_else(|| ParamsError::UnknownName(name.to_string()))?;
+            if values.len() != count {
+                return Err(ParamsError::WrongCount { name: name.to_string(), expected: count, found: values.len() });
+            }
+        }
+
+        let mut offsets: Vec<(&'static str, usize)> = Vec::new();
+        params.visit(&mut |name, value| {
+            let offset = match offsets.iter_mut().find(|(known, _)| *known == name) {
+                Some((_, offset)) => offset,
+                None => { offsets.push((name, 0)); &mut offsets.last_mut().unwrap().1 }
+            };
+            if let Some((_, values)) = groups.iter().rev().find(|(given, _)| *given == name) { *value = values[*offset]; }
+            *offset += 1;
+        });
+        Ok(params)
+    }
+}
+
+// ============================================================================
+// Pawn Structure — Detection
+// ============================================================================
+
+/// Pawns with no enemy pawn ahead of them on their own or an adjacent file.
+pub fn passed_pawns(state: &State, color: Color) -> SquareSet {
+    SquareSet::from_bits(passed(&state.board, color))
+}
+
+/// Pawns with a friendly pawn ahead of them on their file; the frontmost pawn of a file is not
+/// counted.
+pub fn doubled_pawns(state: &State, color: Color) -> SquareSet {
+    SquareSet::from_bits(doubled(&state.board, color))
+}
+
+/// Pawns with no friendly pawn on either adjacent file.
+pub fn isolated_pawns(state: &State, color: Color) -> SquareSet {
+    SquareSet::from_bits(isolated(&state.board, color))
+}
+
+/// Pawns that no friendly pawn can ever defend, and whose advance an enemy pawn stops.
+/// Isolated pawns are left to `isolated_pawns`.
+pub fn backward_pawns(state: &State, color: Color) -> SquareSet {
+    SquareSet::from_bits(backward(&state.board, color))
+}
+
+/// Pawns defended by a friendly pawn or standing beside one.
+pub fn connected_pawns(state: &State, color: Color) -> SquareSet {
+    SquareSet::from_bits(connected(&state.board, color))
+}
+
+fn passed(board: &Board, color: Color) -> u64 {
+    let enemy_front = front_span(color.opponent(), pawns(board, color.opponent()));
+    pawns(board, color) & !(enemy_front | east(enemy_front) | west(enemy_front))
+}
+
+fn doubled(board: &Board, color: Color) -> u64 {
+    let own = pawns(board, color);
+    own & front_span(color.opponent(), own)
+}
+
+fn isolated(board: &Board, color: Color) -> u64 {
+    let files = file_fill(pawns(board, color));
+    pawns(board, color) & !(east(files) | west(files))
+}
+
+fn backward(board: &Board, color: Color) -> u64 {
+    let own = pawns(board, color);
+    let stops = push(color, own);
+    let defensible = front_span(color, pawn_attacks(color, own)) | pawn_attacks(color, own);
+    let stopped = stops & pawn_attacks(color.opponent(), pawns(board, color.opponent())) & !defensible;
+    push(color.opponent(), stopped) & !isolated(board, color)
+}
+
+fn connected(board: &Board, color: Color) -> u64 {
+    let own = pawns(board, color);
+    own & (east(own) | west(own) | pawn_attacks(color, own))
+}
+
+// ============================================================================
+// Pawn Structure — Scoring
+// ============================================================================
+
+/// Each side's pawn structure and king shelter.
+fn pawn_scores(board: &Board, params: &EvalParams) -> PawnScores {
+    PawnScores {
+        structure: pawn_structure(board, params),
+        shelter: [shelter(board, params, Color::White), shelter(board, params, Color::Black)],
+    }
+}
+
+/// A key for what shelter depends on besides the pawns: each king's file, and whether it is
+/// castled. Any random key will do for a bucket, so it borrows the king's on the first two ranks.
+fn king_buckets(board: &Board) -> u64 {
+    [Color::White, Color::Black].into_iter()
+        .filter_map(|color| {
+            let king_sq = board.king_square(color)?;
+            let bucket = Square::from_coords(castled(color, king_sq) as u8, king_sq.file());
+            Some(piece_key(Piece::new(PieceType::King, color), bucket))
+        })
+        .fold(0, |key, bucket| key ^ bucket)
+}
+
+/// Each side's pawn-structure score.
+fn pawn_structure(board: &Board, params: &EvalParams) -> [Tapered; 2] {
+    [pawn_score(board, params, Color::White), pawn_score(board, params, Color::Black)]
+}
+
+fn pawn_score(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let count = |bits: u64| bits.count_ones() as i32;
+    let passed = passed(board, color);
+    let own_attacks = pawn_attacks(color, pawns(board, color));
+
+    let mut score = params.doubled * count(doubled(board, color))
+        + params.isolated * count(isolated(board, color))
+        + params.backward * count(backward(board, color))
+        + params.protected_passer * count(passed & own_attacks);
+    for sq in squares(passed) {
+        score = score + params.passed[relative_rank(sq, color)];
+    }
+    for sq in squares(connected(board, color)) {
+        score = score + params.connected[relative_rank(sq, color)];
+    }
+    score
+}
+
+impl PawnTable {
+    /// Entries in a table; a power of two.
+    const ENTRIES: usize = 1 << 14;
+
+    pub fn new() -> Self {
+        PawnTable { entries: vec![PawnEntry::default(); Self::ENTRIES].into_boxed_slice() }
+    }
+
+    fn probe(&mut self, board: &Board) -> PawnScores {
+        let key = hash_pawns(board) ^ king_buckets(board);
+        let entry = &mut self.entries[key as usize & (Self::ENTRIES - 1)];
+        // A key of zero is a position without pawns or kings, cheap to score, or an empty entry
+        if entry.key != key || key == 0 {
+            *entry = PawnEntry { key, scores: pawn_scores(board, &DEFAULT_PARAMS) };
+        }
+        entry.scores
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self { Self::new() }
+}
+
+// ============================================================================
+// Imbalance
+// ============================================================================
+
+/// Each side's adjustment to the plain sum of piece values: per pair of a piece and any other
+/// piece, own or enemy, the weight for those two types, plus the knight's worth rising with the
+/// pawns it can hop among. A function of the piece counts alone.
+fn imbalance(board: &Board, params: &EvalParams) -> [i32; 2] {
+    let counts = |color| MATERIAL_TYPES.map(|piece_type| board.count(piece_type, color) as i32);
+    let (white, black) = (counts(Color::White), counts(Color::Black));
+    let side = |ours: [i32; 5], theirs: [i32; 5]| {
+        let pairs: i32 = (0..5)
+            .map(|row| ours[row] * (0..5).map(|col| {
+                params.imbalance_ours[row][col] * ours[col] + params.imbalance_theirs[row][col] * theirs[col]
+            }).sum::<i32>())
+            .sum();
+        let (knights, pawns) = (ours[PieceType::Knight as usize], ours[PieceType::Pawn as usize]);
+        pairs + params.knight_pawns * knights * (pawns - 5)
+    };
+    [side(white, black), side(black, white)]
+}
+
+/// The count of every piece type but the king on each side, four bits apiece. Ten of a kind
+/// takes promoting every pawn, so none overflows.
+fn material_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for color in [Color::White, Color::Black] {
+        for piece_type in MATERIAL_TYPES {
+            key = key << 4 | board.count(piece_type, color) as u64;
+        }
+    }
+    key
+}
+
+impl MaterialTable {
+    /// Entries in a table; a power of two.
+    const ENTRIES: usize = 1 << 12;
+
+    pub fn new() -> Self {
+        MaterialTable { entries: vec![MaterialEntry::default(); Self::ENTRIES].into_boxed_slice() }
+    }
+
+    fn probe(&mut self, board: &Board) -> [i32; 2] {
+        let key = material_key(board);
+        // The key is exact, so only its bits need spreading over the table
+        let index = key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - Self::ENTRIES.trailing_zeros());
+        let entry = &mut self.entries[index as usize];
+        if entry.key != key {
+            *entry = MaterialEntry { key, scores: imbalance(board, &DEFAULT_PARAMS) };
+        }
+        entry.scores
+    }
+}
+
+impl Default for MaterialTable {
+    fn default() -> Self { Self::new() }
+}
+
+// --- Bitboard Helpers --- //
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+/// Files c to f.
+const CENTER_FILES: u64 = (FILE_A << 2) | (FILE_A << 3) | (FILE_A << 4) | (FILE_A << 5);
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+const fn pawns(board: &Board, color: Color) -> u64 {
+    board.pieces_of(color, PieceType::Pawn).bits()
+}
+
+const fn east(bits: u64) -> u64 { (bits << 1) & !FILE_A }
+const fn west(bits: u64) -> u64 { (bits >> 1) & !FILE_H }
+
+/// One step toward `color`'s promotion rank.
+const fn push(color: Color, bits: u64) -> u64 {
+    match color { Color::White => bits << 8, Color::Black => bits >> 8 }
+}
+
+const fn pawn_attacks(color: Color, pawns: u64) -> u64 {
+    let pushed = push(color, pawns);
+    east(pushed) | west(pushed)
+}
+
+/// Every square ahead of the given ones, from `color`'s side, excluding the squares themselves.
+const fn front_span(color: Color, bits: u64) -> u64 {
+    let mut fill = push(color, bits);
+    fill |= match color { Color::White => fill << 8, Color::Black => fill >> 8 };
+    fill |= match color { Color::White => fill << 16, Color::Black => fill >> 16 };
+    fill |= match color { Color::White => fill << 32, Color::Black => fill >> 32 };
+    fill
+}
+
+/// Every square `color`'s pawns attack now or could attack after advancing.
+const fn attack_span(color: Color, pawns: u64) -> u64 {
+    let attacks = pawn_attacks(color, pawns);
+    attacks | front_span(color, attacks)
+}
+
+/// Every square on a file holding one of the given squares.
+const fn file_fill(bits: u64) -> u64 {
+    bits | front_span(Color::White, bits) | front_span(Color::Black, bits)
+}
+
+/// Rank counted from `color`'s own back rank.
+const fn relative_rank(sq: Square, color: Color) -> usize {
+    match color {
+        Color::White => sq.rank() as usize,
+        Color::Black => 7 - sq.rank() as usize,
+    }
+}
+
+// ============================================================================
+// Passed Pawns
+// ============================================================================
+
+/// What the pawn-structure term cannot see of `color`'s passed pawns, since it depends on the
+/// pieces: how near each king is, whether the way to promotion is clear, who blockades it, and
+/// whether another passer stands beside it. Every modifier grows with how far the pawn is.
+fn passed_pawn_terms(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let passed = passed(board, color);
+    if passed == 0 { return Tapered::default(); }
+    let enemy_attacks = attacked_by(board, color.opponent());
+    let kings = [board.king_square(color), board.king_square(color.opponent())];
+
+    let mut score = Tapered::default();
+    for sq in squares(passed) {
+        let scale = PASSER_RANK_SCALE[relative_rank(sq, color)];
+        if scale == 0 { continue; }
+        let stop = push(color, bit(sq));
+        let stop_sq = Square::from_index(stop.trailing_zeros() as usize);
+
+        for (weight, king) in params.passer_king_distance.into_iter().zip(kings) {
+            let Some(king) = king else { continue };
+            score = score + weight * (king.distance(stop_sq).min(5) as i32 * scale);
+        }
+        let path = front_span(color, bit(sq));
+        if path & (board.occupied().bits() | enemy_attacks) == 0 {
+            score = score + params.passer_free_path * scale;
+        }
+        if let Some(blocker) = board[stop_sq].filter(|piece| piece.color() != color) {
+            score = score + params.passer_blockaded * scale;
+            if blocker.piece_type() == PieceType::Knight { score = score + params.passer_knight_blockade * scale; }
+        }
+        if (east(bit(sq)) | west(bit(sq))) & file_fill(passed) != 0 {
+            score = score + params.passer_connected * scale;
+        }
+    }
+    score
+}
+
+/// Every square some piece of `color` attacks.
+fn attacked_by(board: &Board, color: Color) -> u64 {
+    board.pieces_of_color(color)
+        .fold(0, |attacks, (sq, piece)| attacks | piece_attacks(piece, sq, board.occupied()).bits())
+}
+
+// ============================================================================
+// King Safety
+// ============================================================================
+
+/// Attack on `color`'s king, and the cost of leaving it in the middle of open central files;
+/// shelter comes from the pawn table. The default weights are middlegame only, so the term
+/// fades out with the material and the king is free to centralize.
+fn king_safety(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let Some(king_sq) = board.king_square(color) else { return Tapered::default() };
+    Tapered::new(-attack_penalty(board, params, color, king_sq), 0) + center_king(board, params, color, king_sq)
+}
+
+/// Back rank, off the d- and e-files.
+const fn castled(color: Color, king_sq: Square) -> bool {
+    relative_rank(king_sq, color) == 0 && !matches!(king_sq.file(), 3 | 4)
+}
+
+/// Pawn shield and storm on the files in front of a castled king, and open files around any
+/// king. Pawns and the king's bucket decide it all, so it lives in the pawn table.
+fn shelter(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let Some(king_sq) = board.king_square(color) else { return Tapered::default() };
+    let (own, enemy) = (pawns(board, color), pawns(board, color.opponent()));
+    let nearest = |bits: u64| squares(bits).map(|sq| relative_rank(sq, color)).min().unwrap_or(0);
+
+    let mut score = Tapered::default();
+    for file in king_sq.file().saturating_sub(1)..=(king_sq.file() + 1).min(7) {
+        let mask = FILE_A << file;
+        if (own | enemy) & mask == 0 {
+            score = score + params.open_file;
+        } else if own & mask == 0 {
+            score = score + params.semi_open_file;
+        }
+        if castled(color, king_sq) {
+            score = score + params.shield[nearest(own & mask)] + params.storm[nearest(enemy & mask)];
+        }
+    }
+    score
+}
+
+/// A king still on the d- or e-file with pawnless central files beside it, while the enemy
+/// queen is on to use them, worse for every enemy heavy piece.
+fn center_king(board: &Board, params: &EvalParams, color: Color, king_sq: Square) -> Tapered {
+    let enemy = color.opponent();
+    if !matches!(king_sq.file(), 3 | 4) || board.count(PieceType::Queen, enemy) == 0 { return Tapered::default(); }
+    let all = pawns(board, color) | pawns(board, enemy);
+    let open = (2..6).filter(|file| all & (FILE_A << file) == 0).count() as i32;
+    let heavy = (board.count(PieceType::Rook, enemy) + board.count(PieceType::Queen, enemy)) as i32;
+    params.center_king * (open * heavy)
+}
+
+/// Attack units from every enemy piece bearing on the king zone, fed through `SAFETY_TABLE`.
+/// A lone attacker is no threat on its own, so it costs nothing.
+fn attack_penalty(board: &Board, params: &EvalParams, color: Color, king_sq: Square) -> i32 {
+    let zone = king_zone(color, king_sq);
+    let (mut attackers, mut units) = (0, 0);
+    for (sq, piece) in board.pieces_of_color(color.opponent()) {
+        let weight = params.attack_weights[piece.piece_type() as usize];
+        if weight == 0 { continue; }
+        let hits = (piece_attacks(piece, sq, board.occupied()).bits() & zone).count_ones() as i32;
+        if hits > 0 {
+            attackers += 1;
+            units += weight * hits;
+        }
+    }
+    if attackers < 2 { return 0; }
+    SAFETY_TABLE[(units as usize).min(SAFETY_TABLE.len() - 1)]
+}
+
+/// `color`'s pieces drawing near the enemy king: per piece, its type's weight for each square
+/// of distance short of the farthest a piece can be. Middlegame only, like the zone attacks it
+/// complements.
+fn tropism(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let Some(king_sq) = board.king_square(color.opponent()) else { return Tapered::default() };
+    let closeness = board.pieces_of_color(color)
+        .map(|(sq, piece)| params.tropism[piece.piece_type() as usize] * (7 - sq.distance(king_sq) as i32))
+        .sum();
+    Tapered::new(closeness, 0)
+}
+
+/// The king's square and its neighbours, plus the three squares two ranks in front of it.
+fn king_zone(color: Color, king_sq: Square) -> u64 {
+    let front = push(color, push(color, bit(king_sq)));
+    bit(king_sq) | king_attacks(king_sq).bits() | front | east(front) | west(front)
+}
+
+// ============================================================================
+// Pieces
+// ============================================================================
+
+/// Knights and bishops on outposts, and bishops hemmed in by their own central pawns where an
+/// enemy pawn fixes them. An outpost is a square on the fifth to seventh rank that a friendly pawn defends and no
+/// enemy pawn can ever attack.
+fn piece_placement(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let (own, enemy) = (pawns(board, color), pawns(board, color.opponent()));
+    let outposts = pawn_attacks(color, own) & !attack_span(color.opponent(), enemy);
+    let fixed = own & push(color.opponent(), enemy) & CENTER_FILES;
+
+    let mut score = Tapered::default();
+    for (outpost, piece_type) in params.outpost.into_iter().zip([PieceType::Knight, PieceType::Bishop]) {
+        for sq in board.pieces_of(color, piece_type) {
+            if outposts & bit(sq) == 0 || !(4..=6).contains(&relative_rank(sq, color)) { continue; }
+            score = score + outpost;
+            if CENTER_FILES & bit(sq) != 0 { score = score + params.central_outpost; }
+        }
+    }
+    for sq in board.pieces_of(color, PieceType::Bishop) {
+        let same_color = if LIGHT_SQUARES & bit(sq) != 0 { LIGHT_SQUARES } else { !LIGHT_SQUARES };
+        score = score + params.bad_bishop * (fixed & same_color).count_ones() as i32;
+    }
+    score
+}
+
+// ============================================================================
+// Threats
+// ============================================================================
+
+/// `color`'s attacks on enemy pieces worth taking: any piece a pawn hits, and pieces worth more
+/// than their attacker that no pawn defends, less the own pieces enemy pawns hit. Kings neither
+/// make nor suffer threats here; king safety covers them.
+fn threats(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let enemy = color.opponent();
+    let enemy_pawn_attacks = pawn_attacks(enemy, pawns(board, enemy));
+    let pieces = |color| board.occupancy(color).bits() & !pawns(board, color) & !board.pieces_of(color, PieceType::King).bits();
+
+    let mut score = params.pawn_attacked * (pieces(color) & enemy_pawn_attacks).count_ones() as i32;
+    for (sq, piece) in board.pieces_of_color(color).filter(|(_, piece)| !piece.is_king()) {
+        let attacker = piece.piece_type() as usize;
+        for target in squares(piece_attacks(piece, sq, board.occupied()).bits() & pieces(enemy)) {
+            let Some(victim) = board[target] else { continue };
+            let victim = victim.piece_type() as usize;
+            let loose = params.piece_values[victim] > params.piece_values[attacker] && enemy_pawn_attacks & bit(target) == 0;
+            if piece.is_pawn() || loose { score = score + params.threats[attacker][victim]; }
+        }
+    }
+    score
+}
+
+// ============================================================================
+// Space
+// ============================================================================
+
+/// Squares on files c to f of `color`'s second to fourth ranks that no enemy pawn attacks and
+/// that an own pawn stands in front of or defends, worth more the more pieces there are to
+/// manoeuvre behind them. Middlegame only, and nothing once a side is down to a few pieces.
+fn space(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let (own, enemy) = (pawns(board, color), pawns(board, color.opponent()));
+    let area = CENTER_FILES & match color { Color::White => 0x0000_0000_FFFF_FF00, Color::Black => 0x00FF_FFFF_0000_0000 };
+    let safe = area & !own & !pawn_attacks(color.opponent(), enemy);
+    let squares = (safe & (front_span(color.opponent(), own) | pawn_attacks(color, own))).count_ones() as i32;
+
+    let pieces = [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen].into_iter()
+        .map(|piece_type| board.pieces_of(color, piece_type).len() as i32)
+        .sum::<i32>();
+    Tapered::new(params.space * squares * (pieces - SPACE_FREE_PIECES).max(0) / 16, 0)
+}
+
+// ============================================================================
+// Scaling
+// ============================================================================
+
+/// How much of `eg_score`, White's endgame total, to believe, out of `SCALE_NORMAL`. Bishops of
+/// opposite colors with nothing else beside them draw a pawn or two up, and still hold back the
+/// extra material with other pieces on; a rook ending a single pawn up is often held; and once
+/// every pawn is locked head-on in a wall no king can cross, neither side can make progress.
+pub fn scale_factor(state: &State, eg_score: i32) -> u8 {
+    let board = &state.board;
+    let strong = if eg_score >= 0 { Color::White } else { Color::Black };
+    let weak = strong.opponent();
+    let count = |piece_type, color| board.count(piece_type, color) as i32;
+    let non_pawn = |color| [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen].into_iter()
+        .map(|piece_type| count(piece_type, color) * piece_value(piece_type))
+        .sum::<i32>();
+
+    if locked(board) { return SCALE_LOCKED; }
+    let bishops = [Color::White, Color::Black].map(|color| board.pieces_of(color, PieceType::Bishop).bits());
+    let opposite = bishops.iter().all(|bishops| bishops.count_ones() == 1)
+        && (bishops[0] & LIGHT_SQUARES == 0) != (bishops[1] & LIGHT_SQUARES == 0);
+    if opposite && non_pawn(strong) == non_pawn(weak) {
+        return if non_pawn(strong) == piece_value(PieceType::Bishop) { SCALE_OPPOSITE_BISHOPS } else { SCALE_OPPOSITE_BISHOPS_PIECES };
+    }
+    let rook = piece_value(PieceType::Rook);
+    if non_pawn(strong) == rook && non_pawn(weak) == rook && count(PieceType::Rook, strong) == 1
+        && count(PieceType::Pawn, strong) - count(PieceType::Pawn, weak) == 1
+    {
+        return SCALE_ROOK_PAWN_UP;
+    }
+    SCALE_NORMAL
+}
+
+/// Every pawn stands head-on against an enemy one, and on every file a pawn or a square a pawn
+/// attacks bars the way, so no king finds a file to cross by.
+fn locked(board: &Board) -> bool {
+    let (white, black) = (pawns(board, Color::White), pawns(board, Color::Black));
+    if white == 0 || push(Color::White, white) != black { return false; }
+    let barrier = white | black | pawn_attacks(Color::White, white) | pawn_attacks(Color::Black, black);
+    file_fill(barrier) == u64::MAX
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamsError::UnknownName(name) => write!(f, "unknown parameter '{}'", name),
+            ParamsError::BadValue(token)   => write!(f, "invalid value '{}'", token),
+            ParamsError::WrongCount { name, expected, found } =>
+                write!(f, "parameter '{}' takes {} values, found {}", name, expected, found),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+// ============================================================================
+// Weights
+// ============================================================================
+
+/// The built-in weights. Per-type arrays are indexed by `PieceType` discriminant: knight,
+/// bishop, rook, queen, pawn, king.
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    piece_values: [320, 330, 500, 900, 100, 0],
+    // A second rook or queen adds less than the first, and minors gain against heavy pieces
+    imbalance_ours: [
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, -4, -3, 0],
+        [0, 0, -3, -8, 0],
+        [0, 0, 0, 0, 0],
+    ],
+    imbalance_theirs: [
+        [0, 0, 4, 6, 0],
+        [0, 0, 4, 6, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0],
+    ],
+    knight_pawns: 4,
+    mg_tables: [MG_KNIGHT, MG_BISHOP, MG_ROOK, MG_QUEEN, MG_PAWN, MG_KING],
+    eg_tables: [EG_KNIGHT, EG_BISHOP, EG_ROOK, EG_QUEEN, EG_PAWN, EG_KING],
+
+    doubled: Tapered::new(-10, -20),
+    isolated: Tapered::new(-10, -15),
+    backward: Tapered::new(-8, -10),
+    protected_passer: Tapered::new(10, 20),
+    passed: [
+        Tapered::new(0, 0), Tapered::new(5, 10), Tapered::new(5, 15), Tapered::new(10, 25),
+        Tapered::new(20, 45), Tapered::new(35, 75), Tapered::new(55, 110), Tapered::new(0, 0),
+    ],
+    connected: [
+        Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(5, 3), Tapered::new(8, 5),
+        Tapered::new(12, 10), Tapered::new(20, 18), Tapered::new(35, 30), Tapered::new(0, 0),
+    ],
+    passer_king_distance: [Tapered::new(0, -3), Tapered::new(0, 6)],
+    passer_free_path: Tapered::new(4, 10),
+    passer_blockaded: Tapered::new(-2, -5),
+    passer_knight_blockade: Tapered::new(-2, -4),
+    passer_connected: Tapered::new(2, 6),
+
+    shield: [
+        Tapered::new(-20, 0), Tapered::new(15, 0), Tapered::new(5, 0), Tapered::new(-5, 0),
+        Tapered::new(-10, 0), Tapered::new(-15, 0), Tapered::new(-15, 0), Tapered::new(0, 0),
+    ],
+    storm: [
+        Tapered::new(0, 0), Tapered::new(-10, 0), Tapered::new(-30, 0), Tapered::new(-20, 0),
+        Tapered::new(-10, 0), Tapered::new(-5, 0), Tapered::new(0, 0), Tapered::new(0, 0),
+    ],
+    open_file: Tapered::new(-25, 0),
+    semi_open_file: Tapered::new(-12, 0),
+    center_king: Tapered::new(-6, 0),
+    attack_weights: [2, 2, 3, 5, 0, 0],
+    tropism: [3, 1, 2, 4, 0, 0],
+
+    outpost: [Tapered::new(25, 15), Tapered::new(12, 8)],
+    central_outpost: Tapered::new(10, 5),
+    bad_bishop: Tapered::new(-6, -10),
+    threats: [
+        [Tapered::new(0, 0), Tapered::new(5, 5), Tapered::new(30, 40), Tapered::new(40, 40)],
+        [Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(30, 40), Tapered::new(40, 40)],
+        [Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(40, 40)],
+        [Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(0, 0), Tapered::new(0, 0)],
+        [Tapered::new(50, 40), Tapered::new(50, 40), Tapered::new(70, 50), Tapered::new(70, 50)],
+    ],
+    pawn_attacked: Tapered::new(-10, -10),
+
+    space: 3,
+};
+
+const PHASE_WEIGHTS: [i32; 6] = [1, 1, 2, 4, 0, 0];
+
+/// Endgame scales, out of `SCALE_NORMAL`: bishops of opposite colors alone and with other
+/// pieces, a rook ending a pawn up, and a locked pawn wall.
+pub const SCALE_NORMAL: u8 = 64;
+const SCALE_OPPOSITE_BISHOPS: u8 = 24;
+const SCALE_OPPOSITE_BISHOPS_PIECES: u8 = 48;
+const SCALE_ROOK_PAWN_UP: u8 = 48;
+const SCALE_LOCKED: u8 = 8;
+
+/// Halfmove clock from which the evaluation fades toward a draw.
+const DAMPING_START: u8 = 30;
+
+/// Every piece type but the king, in discriminant order, as the imbalance tables index them.
+const MATERIAL_TYPES: [PieceType; 5] = [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::Pawn];
+
+/// Knights, bishops, rooks, and queens a side keeps without space counting for anything.
+const SPACE_FREE_PIECES: i32 = 3;
+
+/// How much the passed pawn modifiers count by the pawn's rank from its own side: nothing on
+/// the first three, then more with each step nearer promotion.
+const PASSER_RANK_SCALE: [i32; 8] = [0, 0, 0, 1, 2, 4, 7, 0];
+
+/// Phase of the starting material; 0 is a bare endgame.
+const MAX_PHASE: i32 = 24;
+
+/// Middlegame penalty by attack units: slow at first, steep once several pieces join in.
+const SAFETY_TABLE: [i32; 64] = safety_table();
+
+const fn safety_table() -> [i32; 64] {
+    let mut table = [0; 64];
+    let mut units = 0;
+    while units < 64 {
+        let penalty = (units * units / 4) as i32;
+        table[units] = if penalty < 500 { penalty } else { 500 };
+        units += 1;
+    }
+    table
+}
+
+/// Tables are laid out as the board reads from White's side, a8 first. Black uses the same
+/// tables mirrored vertically.
+const fn table_index(sq: Square, color: Color) -> usize {
+    match color {
+        Color::White => sq.index() ^ 56,
+        Color::Black => sq.index(),
+    }
+}
+
+// ============================================================================
+// Piece-Square Tables
+// ============================================================================
+
+// --- Middlegame --- //
+const MG_PAWN: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const MG_KNIGHT: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+const MG_BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const MG_ROOK: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+const MG_QUEEN: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+const MG_KING: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+// --- Endgame --- //
+const EG_PAWN: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     80,  80,  80,  80,  80,  80,  80,  80,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     30,  30,  30,  30,  30,  30,  30,  30,
+     15,  15,  15,  15,  15,  15,  15,  15,
+      5,   5,   5,   5,   5,   5,   5,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const EG_KNIGHT: [i32; 64] = [
+    -40, -30, -20, -20, -20, -20, -30, -40,
+    -30, -10,   0,   5,   5,   0, -10, -30,
+    -20,   0,  10,  15,  15,  10,   0, -20,
+    -20,   5,  15,  20,  20,  15,   5, -20,
+    -20,   5,  15,  20,  20,  15,   5, -20,
+    -20,   0,  10,  15,  15,  10,   0, -20,
+    -30, -10,   0,   5,   5,   0, -10, -30,
+    -40, -30, -20, -20, -20, -20, -30, -40,
+];
+
+const EG_BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,  10,  15,  15,  10,   0, -10,
+    -10,   0,  10,  15,  15,  10,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const EG_ROOK: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     10,  10,  10,  10,  10,  10,  10,  10,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const EG_QUEEN: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   5,  10,  10,  10,  10,   5, -10,
+     -5,   5,  10,  15,  15,  10,   5,  -5,
+     -5,   5,  10,  15,  15,  10,   5,  -5,
+    -10,   5,  10,  10,  10,  10,   5, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+const EG_KING: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Openings, middlegames with both kings castled and not, and endgames of every phase.
+    const CORPUS: [&str; 16] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "r1bq1rk1/pp2ppbp/2np1np1/8/3NP3/2N1BP2/PPPQ2PP/R3KB1R w KQ - 3 9",
+        "2kr3r/ppp2ppp/2n5/2b1p3/4P1b1/2NP1N2/PPP2PPP/R1B2RK1 b - - 3 10",
+        "r2q1rk1/pb1nbppp/1p2pn2/2pp4/2PP4/1PNBPN2/PB3PPP/R2Q1RK1 w - - 0 10",
+        "6k1/5ppp/8/8/8/8/qq3PPP/3R2K1 w - - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "8/8/4k3/3p4/3P4/4K3/8/8 b - - 0 1",
+        "8/5pk1/6p1/7p/7P/6P1/5PK1/8 w - - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "8/8/8/4k3/8/8/8/R3K3 b - - 0 1",
+        "6k1/pp3ppp/8/3P4/8/8/PP3PPP/6K1 w - - 0 1",
+        "r1b1k2r/ppppqppp/2n2n2/2b5/2B1P3/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 6 6",
+    ];
+
+    fn corpus() -> impl Iterator<Item = State> {
+        CORPUS.into_iter().map(|fen| State::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn swapping_colors_leaves_the_evaluation_unchanged() {
+        for state in corpus() {
+            let swapped = state.color_swapped();
+            assert_eq!(evaluate(&state), evaluate(&swapped), "{} against {}", state.to_fen(), swapped.to_fen());
+            assert_eq!(explain(&state).white_score(), -explain(&swapped).white_score());
+        }
+        assert_eq!(evaluate(&State::start()), evaluate(&State::start().color_swapped()));
+    }
+
+    #[test]
+    fn breakdown_adds_up_to_the_evaluation() {
+        for state in corpus() {
+            let breakdown = explain(&state);
+            assert_eq!(breakdown.terms.iter().map(|term| term.term).collect::<Vec<_>>(), Term::ALL);
+            assert_eq!(breakdown.score(), evaluate(&state), "{}", state.to_fen());
+            let white = breakdown.white_score();
+            assert_eq!(breakdown.score(), if state.to_move == Color::White { white } else { -white });
+
+            let material: i32 = state.board.pieces()
+                .map(|(_, piece)| {
+                    let value = piece_value(piece.piece_type());
+                    if piece.color() == Color::White { value } else { -value }
+                })
+                .sum();
+            assert_eq!(breakdown.terms[Term::Material as usize].total(), Tapered::new(material, material));
+        }
+    }
+
+    // --- Imbalance --- //
+
+    fn imbalance_term(fen: &str) -> i32 {
+        let state = State::from_fen(fen).unwrap();
+        let [white, black] = imbalance(&state.board, &DEFAULT_PARAMS);
+        assert_eq!(imbalance(&state.color_swapped().board, &DEFAULT_PARAMS), [black, white]);
+        assert_eq!(explain(&state).terms[Term::Imbalance as usize].total(), Tapered::new(white - black, white - black));
+        white - black
+    }
+
+    #[test]
+    fn imbalances_lean_the_published_way() {
+        // Two minors outweigh a rook and pawn, and three a queen, by more than their values say
+        assert!(imbalance_term("2rnb1k1/5ppp/8/8/8/8/P4PPP/1RR3K1 w - - 0 1") < 0);
+        assert!(imbalance_term("rr2k3/ppp2ppp/2nbn3/8/8/8/PPP2PPP/RR1QK3 w - - 0 1") < 0);
+
+        // Each rook and queen adds less than the one before
+        let rooks = ["4k3/8/8/8/8/8/8/4K3 w - - 0 1", "4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "4k3/8/8/8/8/8/8/R3K2R w - - 0 1"].map(imbalance_term);
+        assert!(rooks[2] - rooks[1] < rooks[1] - rooks[0], "{:?}", rooks);
+        let queens = ["4k3/8/8/8/8/8/8/4K3 w - - 0 1", "4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", "4k3/8/8/8/8/8/8/Q3K2Q w - - 0 1"].map(imbalance_term);
+        assert!(queens[2] - queens[1] < queens[1] - queens[0], "{:?}", queens);
+
+        // A knight gains with the pawns on its side
+        let many = imbalance_term("4k3/8/8/8/8/8/PPPPPPPP/1N2K3 w - - 0 1");
+        let few = imbalance_term("4k3/8/8/8/8/8/PP6/1N2K3 w - - 0 1");
+        assert_eq!(many - few, DEFAULT_PARAMS.knight_pawns * 6);
+    }
+
+    #[test]
+    fn cached_imbalance_matches_a_fresh_one() {
+        let mut material = MaterialTable::new();
+        for _ in 0..2 {
+            for state in corpus() {
+                assert_eq!(material.probe(&state.board), imbalance(&state.board, &DEFAULT_PARAMS), "{}", state.to_fen());
+            }
+        }
+        // Bare kings share their key with an empty entry
+        assert_eq!(material.probe(&State::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().board), [0, 0]);
+    }
+
+    // --- Scaling --- //
+
+    #[test]
+    fn opposite_bishops_hold_back_a_clean_extra_pawn() {
+        let eval = |fen| evaluate(&State::from_fen(fen).unwrap());
+        let opposite = eval("6k1/5ppp/4b3/8/8/4B3/P4PPP/6K1 w - - 0 1");
+        let same = eval("6k1/5ppp/3b4/8/8/4B3/P4PPP/6K1 w - - 0 1");
+        assert!((0..70).contains(&opposite), "{}", opposite);
+        assert!((70..=150).contains(&same), "{}", same);
+    }
+
+    #[test]
+    fn endings_that_tend_to_draw_scale_down() {
+        let scale = |fen| {
+            let state = State::from_fen(fen).unwrap();
+            scale_factor(&state, explain(&state).total().eg)
+        };
+        assert_eq!(scale("6k1/5ppp/8/8/8/8/P4PPP/3R2K1 w - - 0 1"), SCALE_NORMAL);
+        assert_eq!(scale("3r2k1/5ppp/8/8/8/8/P4PPP/3R2K1 w - - 0 1"), SCALE_ROOK_PAWN_UP);
+        assert_eq!(scale("6k1/5ppp/4b3/8/8/2N1B3/P4PPP/6K1 w - - 0 1"), SCALE_NORMAL);
+        assert_eq!(scale("6k1/5ppp/2n1b3/8/8/2N1B3/P4PPP/6K1 w - - 0 1"), SCALE_OPPOSITE_BISHOPS_PIECES);
+        // Every file barred, even a knight up; with the g-pawns gone the king walks round by the h-file
+        assert_eq!(scale("8/8/3k4/p1p1p1p1/P1P1P1P1/3K4/8/6N1 w - - 0 1"), SCALE_LOCKED);
+        assert_eq!(scale("8/8/3k4/p1p1p3/P1P1P3/3K4/8/6N1 w - - 0 1"), SCALE_NORMAL);
+    }
+
+    #[test]
+    fn scaling_never_flips_the_score() {
+        let fens = CORPUS.into_iter().chain([
+            "6k1/5ppp/4b3/8/8/4B3/P4PPP/6K1 w - - 0 1",
+            "3r2k1/5ppp/8/8/8/8/P4PPP/3R2K1 b - - 0 1",
+            "8/8/3k4/p1p1p1p1/P1P1P1P1/3K4/8/6N1 w - - 0 1",
+            "r5k1/5ppp/2b5/8/8/2B5/5PPP/6K1 w - - 0 1",
+        ]);
+        for fen in fens {
+            let breakdown = explain(&State::from_fen(fen).unwrap());
+            let unscaled = breakdown.total().taper(breakdown.phase);
+            assert!(breakdown.white_score() * unscaled >= 0, "{}: {} unscaled, {} scaled", fen, unscaled, breakdown.white_score());
+        }
+    }
+
+    #[test]
+    fn the_score_fades_as_the_halfmove_clock_climbs() {
+        let fresh = State::from_fen("8/8/8/3k4/8/8/P7/1Q2K3 w - - 0 80").unwrap();
+        let stale = State::from_fen("8/8/8/3k4/8/8/P7/1Q2K3 w - - 90 80").unwrap();
+        assert!(evaluate(&stale).abs() < evaluate(&fresh).abs(), "{} against {}", evaluate(&stale), evaluate(&fresh));
+        assert!(evaluate(&stale) > 0);
+        assert_eq!(explain(&stale).score(), evaluate(&stale));
+        assert_eq!(evaluate(&State::from_fen("8/8/8/3k4/8/8/P7/1Q2K3 w - - 30 80").unwrap()), evaluate(&fresh));
+    }
+
+    // --- Pawn Structure --- //
+
+    /// The squares of `set`, in algebraic notation from a1 up.
+    fn names(set: SquareSet) -> Vec<String> {
+        set.into_iter().map(|sq| sq.to_string()).collect()
+    }
+
+    #[test]
+    fn pawn_weaknesses_are_found_where_they_stand() {
+        // g2 is passed, a2 and b5 face each other across adjacent files
+        let state = State::from_fen("4k3/8/8/1p6/8/8/P5P1/4K3 w - - 0 1").unwrap();
+        assert_eq!(names(passed_pawns(&state, Color::White)), ["g2"]);
+        assert!(passed_pawns(&state, Color::Black).is_empty());
+
+        // c2 sits behind c3; neither has a neighbour
+        let state = State::from_fen("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1").unwrap();
+        assert_eq!(names(doubled_pawns(&state, Color::White)), ["c2"]);
+        assert_eq!(names(isolated_pawns(&state, Color::White)), ["c2", "c3"]);
+
+        // a2 defends b3; e4 is alone
+        let state = State::from_fen("4k3/8/8/8/4P3/1P6/P7/4K3 w - - 0 1").unwrap();
+        assert_eq!(names(connected_pawns(&state, Color::White)), ["b3"]);
+        assert_eq!(names(isolated_pawns(&state, Color::White)), ["e4"]);
+
+        // d3 has fallen behind c4 and e4, and c5 guards d4; the isolated c5 is not also backward
+        let state = State::from_fen("4k3/8/8/2p5/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(names(backward_pawns(&state, Color::White)), ["d3"]);
+        assert!(backward_pawns(&state, Color::Black).is_empty());
+        assert_eq!(names(isolated_pawns(&state, Color::Black)), ["c5"]);
+    }
+
+    #[test]
+    fn pawn_structure_sums_its_weights() {
+        // Doubled once, isolated twice, and passed on the second and third ranks
+        let state = State::from_fen("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1").unwrap();
+        let p = &DEFAULT_PARAMS;
+        let expected = p.doubled + p.isolated * 2 + p.passed[1] + p.passed[2];
+        assert_eq!(pawn_structure(&state.board, p), [expected, Tapered::default()]);
+        assert_eq!(explain(&state).terms[Term::PawnStructure as usize].white, expected);
+    }
+
+    #[test]
+    fn cached_pawn_structure_matches_a_fresh_one() {
+        let (mut pawns, mut material) = (PawnTable::new(), MaterialTable::new());
+        for _ in 0..2 {
+            for state in corpus() {
+                assert_eq!(pawns.probe(&state.board), pawn_scores(&state.board, &DEFAULT_PARAMS), "{}", state.to_fen());
+                assert_eq!(evaluate_cached(&state, &mut pawns, &mut material), evaluate(&state));
+            }
+        }
+    }
+
+    // --- Passed Pawns --- //
+
+    fn white_passers(fen: &str) -> Tapered {
+        let state = State::from_fen(fen).unwrap();
+        passed_pawn_terms(&state.board, &DEFAULT_PARAMS, Color::White)
+    }
+
+    fn white_eval(fen: &str) -> i32 {
+        explain(&State::from_fen(fen).unwrap()).white_score()
+    }
+
+    #[test]
+    fn a_blockaded_passer_is_worth_less_than_a_free_one() {
+        let free = white_passers("8/8/8/3P4/8/8/k7/4K3 w - - 0 1");
+        let bishop = white_passers("8/8/3b4/3P4/8/8/k7/4K3 w - - 0 1");
+        let knight = white_passers("8/8/3n4/3P4/8/8/k7/4K3 w - - 0 1");
+        assert!(knight.eg < bishop.eg && bishop.eg < free.eg, "{:?} {:?} {:?}", knight, bishop, free);
+        // Under attack the path is no longer free, even with nothing standing on it
+        let attacked = white_passers("8/8/8/3P4/8/8/k6b/4K3 w - - 0 1");
+        assert_eq!(free - attacked, DEFAULT_PARAMS.passer_free_path * PASSER_RANK_SCALE[4]);
+    }
+
+    #[test]
+    fn king_distance_to_a_passer_counts_in_the_endgame() {
+        // Black's king in the square of the a-pawn, and out of it
+        let caught = white_eval("8/8/2k5/P7/8/8/8/4K3 w - - 0 1");
+        let runs = white_eval("8/7k/8/P7/8/8/8/4K3 w - - 0 1");
+        assert!(runs > caught, "{} against {}", runs, caught);
+        // White's own king escorting it helps too
+        let escorted = white_eval("8/7k/8/P7/1K6/8/8/8 w - - 0 1");
+        assert!(escorted > runs, "{} against {}", escorted, runs);
+        // The middlegame weight is nil
+        assert_eq!(white_passers("8/8/2k5/P7/8/8/8/4K3 w - - 0 1").mg, white_passers("8/7k/8/P7/8/8/8/4K3 w - - 0 1").mg);
+    }
+
+    #[test]
+    fn passers_side_by_side_support_each_other() {
+        let unconnected = EvalParams { passer_connected: Tapered::default(), ..DEFAULT_PARAMS };
+        let connected_part = |fen: &str| {
+            let board = State::from_fen(fen).unwrap().board;
+            passed_pawn_terms(&board, &DEFAULT_PARAMS, Color::White) - passed_pawn_terms(&board, &unconnected, Color::White)
+        };
+        assert_eq!(connected_part("8/8/8/2P3P1/8/8/k7/4K3 w - - 0 1"), Tapered::default());
+        assert_eq!(connected_part("8/8/8/2PP4/8/8/k7/4K3 w - - 0 1"), DEFAULT_PARAMS.passer_connected * (2 * PASSER_RANK_SCALE[4]));
+    }
+
+    // --- King Safety --- //
+
+    fn white_king_safety(fen: &str) -> Tapered {
+        let state = State::from_fen(fen).unwrap();
+        shelter(&state.board, &DEFAULT_PARAMS, Color::White) + king_safety(&state.board, &DEFAULT_PARAMS, Color::White)
+    }
+
+    #[test]
+    fn king_shield_swings_with_each_pawn() {
+        let p = &DEFAULT_PARAMS;
+        let intact = white_king_safety("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+        assert_eq!(intact, p.shield[1] * 3);
+
+        // One step up the shield is worth less; gone, with the enemy pawn left, the file is half open
+        let advanced = white_king_safety("6k1/5ppp/8/8/8/6P1/5P1P/6K1 w - - 0 1");
+        assert_eq!(intact - advanced, p.shield[1] - p.shield[2]);
+        let missing = white_king_safety("6k1/5ppp/8/8/8/8/5P1P/6K1 w - - 0 1");
+        assert_eq!(intact - missing, p.shield[1] - p.shield[0] - p.semi_open_file);
+        let open = white_king_safety("6k1/5p1p/8/8/8/8/5P1P/6K1 w - - 0 1");
+        assert_eq!(intact - open, p.shield[1] - p.shield[0] - p.open_file);
+
+        // An uncastled king has no shield to lose
+        assert_eq!(white_king_safety("4k3/8/8/8/8/8/3PPP2/4K3 w - - 0 1"), Tapered::default());
+        // The shield is a middlegame term, gone once the material is off
+        assert_eq!((intact - missing).eg, 0);
+    }
+
+    #[test]
+    fn pushing_the_shield_and_meeting_a_storm_each_cost_the_castled_king() {
+        let p = &DEFAULT_PARAMS;
+        let intact = "r2q1rk1/ppp2ppp/2n2n2/8/8/2N2N2/PPP2PPP/R2Q1RK1 w - - 0 1";
+        let pushed = "r2q1rk1/ppp2ppp/2n2n2/8/6P1/2N2N2/PPP2P1P/R2Q1RK1 w - - 0 1";
+        let stormed = "r2q1rk1/ppp2pp1/2n2n2/8/6Pp/2N2N2/PPP2P1P/R2Q1RK1 w - - 0 1";
+        assert_eq!(white_king_safety(intact) - white_king_safety(pushed), p.shield[1] - p.shield[3]);
+        assert_eq!(white_king_safety(pushed) - white_king_safety(stormed), p.storm[6] - p.storm[3]);
+
+        let eval = |fen| evaluate(&State::from_fen(fen).unwrap());
+        assert!(eval(intact) > eval(pushed) && eval(pushed) > eval(stormed), "{} {} {}", eval(intact), eval(pushed), eval(stormed));
+    }
+
+    #[test]
+    fn a_king_left_on_open_central_files_suffers_until_the_queens_are_off() {
+        let p = &DEFAULT_PARAMS;
+        // The d- and e-files are open, with two rooks and a queen to use them
+        let queens = white_king_safety("r2qk2r/ppp2ppp/8/8/8/8/PPP2PPP/R2QK2R w KQkq - 0 1");
+        let no_queens = white_king_safety("r3k2r/ppp2ppp/8/8/8/8/PPP2PPP/R3K2R w KQkq - 0 1");
+        assert_eq!(queens - no_queens, p.center_king * (2 * 3));
+        assert_eq!(no_queens, p.open_file * 2);
+    }
+
+    #[test]
+    fn cached_shelter_follows_the_king_between_buckets() {
+        let mut pawns = PawnTable::new();
+        for fen in ["6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1", "6k1/5ppp/8/8/8/8/5PPP/4K3 w - - 0 1", "6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1"] {
+            let state = State::from_fen(fen).unwrap();
+            assert_eq!(pawns.probe(&state.board), pawn_scores(&state.board, &DEFAULT_PARAMS), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn a_lone_attacker_costs_nothing_and_a_second_one_does() {
+        let lone = white_king_safety("6k1/8/8/8/8/5q2/5PPP/6K1 w - - 0 1");
+        let two = white_king_safety("6k1/8/8/8/8/5q2/5PPP/3r2K1 w - - 0 1");
+        assert_eq!(lone, white_king_safety("6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1"));
+        assert!(two.mg < lone.mg, "{:?} against {:?}", two, lone);
+    }
+
+    // --- Pieces --- //
+
+    fn white_pieces(fen: &str) -> Tapered {
+        let state = State::from_fen(fen).unwrap();
+        piece_placement(&state.board, &DEFAULT_PARAMS, Color::White)
+    }
+
+    #[test]
+    fn a_knight_no_pawn_can_chase_is_on_an_outpost() {
+        let p = &DEFAULT_PARAMS;
+        // d5, defended from e4, with no black pawn on the c- or e-file
+        let outpost = white_pieces("4k3/p5p1/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        assert_eq!(outpost, p.outpost[0] + p.central_outpost);
+        // ...e6 would chase it from e7
+        assert_eq!(white_pieces("4k3/p3p1p1/8/3N4/4P3/8/8/4K3 w - - 0 1"), Tapered::default());
+        // Undefended, or still on White's side of the board
+        assert_eq!(white_pieces("4k3/p5p1/8/3N4/8/4P3/8/4K3 w - - 0 1"), Tapered::default());
+        assert_eq!(white_pieces("4k3/p5p1/8/8/3N4/4P3/8/4K3 w - - 0 1"), Tapered::default());
+        // A wing outpost earns less, and a bishop's less again
+        assert_eq!(white_pieces("4k3/6p1/8/1N6/2P5/8/8/4K3 w - - 0 1"), p.outpost[0]);
+        assert_eq!(white_pieces("4k3/6p1/8/1B6/2P5/8/8/4K3 w - - 0 1"), p.outpost[1]);
+    }
+
+    #[test]
+    fn a_bishop_behind_its_own_blocked_center_is_bad() {
+        let p = &DEFAULT_PARAMS;
+        // d4 and e3 stand on dark squares and are blocked; the light-squared bishop is free
+        let dark = white_pieces("4k3/8/8/3p4/3Pp3/2B1P3/8/4K3 w - - 0 1");
+        let light = white_pieces("4k3/8/8/3p4/3Pp3/3BP3/8/4K3 w - - 0 1");
+        assert_eq!(dark, p.bad_bishop * 2);
+        assert_eq!(light, Tapered::default());
+    }
+
+    #[test]
+    fn a_queen_near_the_king_earns_tropism() {
+        let far = explain(&State::from_fen("q5k1/5ppp/8/8/8/8/5PPP/Q5K1 w - - 0 1").unwrap());
+        let near = explain(&State::from_fen("q5k1/5ppp/8/7Q/8/8/5PPP/6K1 w - - 0 1").unwrap());
+        let tropism = |breakdown: &EvalBreakdown| breakdown.terms[Term::Tropism as usize].total();
+        // a1 is seven squares from g8 and h5 three
+        assert_eq!(tropism(&far), Tapered::default());
+        assert_eq!(tropism(&near) - tropism(&far), Tapered::new(4 * DEFAULT_PARAMS.tropism[PieceType::Queen as usize], 0));
+        assert!(near.white_score() > far.white_score());
+    }
+
+    // --- Threats --- //
+
+    fn threats_term(fen: &str) -> Tapered {
+        explain(&State::from_fen(fen).unwrap()).terms[Term::Threats as usize].total()
+    }
+
+    #[test]
+    fn a_knight_fork_on_queen_and_rook_is_a_threat() {
+        let p = &DEFAULT_PARAMS;
+        // From d5 the knight hits c7 and e7; from d4 neither
+        let fork = "4k3/2r1q3/8/3N4/8/8/8/4K3 w - - 0 1";
+        let quiet = "4k3/2r1q3/8/8/3N4/8/8/4K3 w - - 0 1";
+        assert_eq!(threats_term(fork), p.threats[0][2] + p.threats[0][3]);
+        assert_eq!(threats_term(quiet), Tapered::default());
+        assert!(evaluate(&State::from_fen(fork).unwrap()) > evaluate(&State::from_fen(quiet).unwrap()));
+    }
+
+    #[test]
+    fn a_minor_a_pawn_attacks_is_threatened() {
+        let p = &DEFAULT_PARAMS;
+        // e4 hits d5; e3 does not
+        for (attacked, safe, victim) in [
+            ("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1", "4k3/8/8/3n4/8/4P3/8/4K3 w - - 0 1", PieceType::Knight),
+            ("4k3/8/8/3b4/4P3/8/8/4K3 w - - 0 1", "4k3/8/8/3b4/8/4P3/8/4K3 w - - 0 1", PieceType::Bishop),
+        ] {
+            let expected = p.threats[PieceType::Pawn as usize][victim as usize] - p.pawn_attacked;
+            assert_eq!(threats_term(attacked) - threats_term(safe), expected, "{}", attacked);
+        }
+        // A piece worth more than its attacker is no threat while a pawn defends it
+        assert_eq!(threats_term("4k3/8/2p5/1r6/8/2N5/8/4K3 w - - 0 1"), Tapered::default());
+        assert_eq!(threats_term("4k3/8/8/1r6/8/2N5/8/4K3 w - - 0 1"), p.threats[0][2]);
+    }
+
+    // --- Space --- //
+
+    fn white_space(fen: &str) -> Tapered {
+        let state = State::from_fen(fen).unwrap();
+        space(&state.board, &DEFAULT_PARAMS, Color::White) - space(&state.board, &DEFAULT_PARAMS, Color::Black)
+    }
+
+    #[test]
+    fn a_big_pawn_center_gains_space_while_the_pieces_are_on() {
+        let center = white_space("r1bqkb1r/pp3ppp/2nppn2/8/3PP3/2N2N2/PP3PPP/R1BQKB1R w KQkq - 0 1");
+        assert!(center.mg > 0, "{:?}", center);
+        assert_eq!(center.eg, 0);
+        // The same pawns with the queens and all but a rook and a bishop each traded
+        assert_eq!(white_space("r3k3/pp2bppp/3pp3/8/3PP3/4B3/PP3PPP/R3K3 w - - 0 1"), Tapered::default());
+    }
+}
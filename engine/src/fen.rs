@@ -0,0 +1,243 @@
+//! FEN (Forsyth–Edwards Notation) parsing and serialization.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::castling::CastlingRights;
+use crate::state::{AttackCache, State};
+use crate::zobrist::hash_state;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// Fewer than the four mandatory fields (placement, side, castling, en passant).
+    MissingFields(usize),
+    /// More than the six standard fields.
+    ExtraFields(usize),
+    /// Piece placement does not describe exactly eight ranks.
+    WrongRankCount(usize),
+    /// A rank (1-based, as written in the FEN) does not cover exactly eight files.
+    WrongRankLength(u8),
+    /// A character in the placement field is neither a piece letter nor a digit 1–8.
+    BadPieceChar(char),
+    /// The side-to-move field is not `w` or `b`.
+    BadSideToMove(String),
+    /// The castling field is not `-` or a duplicate-free set of `KQkq` letters or, for Chess960,
+    /// rook files, in any order.
+    BadCastling(String),
+    /// The en passant field is not `-` or a square on the rank behind a just-pushed pawn.
+    BadEnPassant(String),
+    /// A halfmove or fullmove clock is not a number, or is too large to store: the halfmove
+    /// clock goes up to 255 and the fullmove number to 65535.
+    BadClock(String),
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a FEN string. The halfmove and fullmove clocks default to `0` and `1` when absent.
+/// Chess960 castling may be given in Shredder-FEN or X-FEN form.
+pub fn parse_fen(fen: &str) -> Result<State, FenError> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() < 4 { return Err(FenError::MissingFields(fields.len())); }
+    if fields.len() > 6 { return Err(FenError::ExtraFields(fields.len())); }
+
+    let board = parse_placement(fields[0])?;
+    let to_move = parse_side(fields[1])?;
+    let castling_rights = parse_castling(fields[2], &board)?;
+    let en_passant = parse_en_passant(fields[3], to_move)?;
+    let halfmove_clock = fields.get(4).map_or(Ok(0), |f| parse_clock(f))?;
+    let fullmove_number = fields.get(5).map_or(Ok(1), |f| parse_clock(f))?;
+
+    let mut state = State { board, to_move, castling_rights, en_passant, halfmove_clock, fullmove_number, hash: 0, attacks: AttackCache::default() };
+    state.hash = hash_state(&state);
+    Ok(state)
+}
+
+/// The placement field alone, ranks 8 down to 1.
+pub(crate) fn parse_placement(field: &str) -> Result<Board, FenError> {
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 { return Err(FenError::WrongRankCount(ranks.len())); }
+
+    let mut board = Board::new();
+    // FEN lists ranks from 8 down to 1
+    for (row, text) in ranks.iter().enumerate() {
+        let rank = 7 - row as u8;
+        let mut file = 0u8;
+        for c in text.chars() {
+            match c {
+                '1'..='8' => file += c as u8 - b'0',
+                _ => {
+                    let piece = piece_from_char(c).ok_or(FenError::BadPieceChar(c))?;
+                    if file >= 8 { return Err(FenError::WrongRankLength(rank + 1)); }
+                    board.set_piece(piece, (rank, file));
+                    file += 1;
+                }
+            }
+            if file > 8 { return Err(FenError::WrongRankLength(rank + 1)); }
+        }
+        if file != 8 { return Err(FenError::WrongRankLength(rank + 1)); }
+    }
+    Ok(board)
+}
+
+fn parse_side(field: &str) -> Result<Color, FenError> {
+    field.parse().map_err(|_| FenError::BadSideToMove(field.to_string()))
+}
+
+fn parse_castling(field: &str, board: &Board) -> Result<CastlingRights, FenError> {
+    CastlingRights::from_fen_field(field, board).map_err(|_| FenError::BadCastling(field.to_string()))
+}
+
+fn parse_en_passant(field: &str, to_move: Color) -> Result<Option<Square>, FenError> {
+    if field == "-" { return Ok(None); }
+
+    // The skipped square sits on the pushing side's third rank
+    let pusher = !to_move;
+    let expected_rank = (pusher.pawn_rank() as i8 + pusher.forward()) as u8;
+    match Square::from_algebraic(field) {
+        Some(sq) if sq.rank() == expected_rank => Ok(Some(sq)),
+        _ => Err(FenError::BadEnPassant(field.to_string())),
+    }
+}
+
+fn parse_clock<T: std::str::FromStr>(field: &str) -> Result<T, FenError> {
+    field.parse().map_err(|_| FenError::BadClock(field.to_string()))
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+/// Render the full six-field FEN for a state.
+pub fn format_fen(state: &State) -> String {
+    let en_passant = state.en_passant.map_or("-".to_string(), |sq| sq.to_string());
+    format!(
+        "{} {} {} {} {} {}",
+        format_placement(&state.board), state.to_move, state.castling_rights, en_passant,
+        state.halfmove_clock, state.fullmove_number,
+    )
+}
+
+/// The placement field alone, ranks 8 down to 1.
+pub(crate) fn format_placement(board: &Board) -> String {
+    let mut fen = String::new();
+
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            match board[(rank, file)] {
+                Some(piece) => {
+                    if empty > 0 { fen.push((b'0' + empty) as char); empty = 0; }
+                    fen.push(piece_to_char(piece));
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 { fen.push((b'0' + empty) as char); }
+        if rank > 0 { fen.push('/'); }
+    }
+    fen
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+pub(crate) fn piece_from_char(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+    PieceType::from_char(c).map(|piece_type| Piece::new(piece_type, color))
+}
+
+pub(crate) fn piece_to_char(piece: Piece) -> char {
+    piece.piece_type().to_char(piece.color())
+}
+
+// --- Traits --- //
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            FenError::MissingFields(n)    => write!(f, "expected at least 4 fields, found {}", n),
+            FenError::ExtraFields(n)      => write!(f, "expected at most 6 fields, found {}", n),
+            FenError::WrongRankCount(n)   => write!(f, "expected 8 ranks, found {}", n),
+            FenError::WrongRankLength(r)  => write!(f, "rank {} does not cover 8 files", r),
+            FenError::BadPieceChar(c)     => write!(f, "invalid piece character '{}'", c),
+            FenError::BadSideToMove(s)    => write!(f, "invalid side to move '{}'", s),
+            FenError::BadCastling(s)      => write!(f, "invalid castling rights '{}'", s),
+            FenError::BadEnPassant(s)     => write!(f, "invalid en passant square '{}'", s),
+            FenError::BadClock(s)         => write!(f, "invalid move clock '{}'", s),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mv::Move;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn start_position_round_trips() {
+        let state = parse_fen(START).unwrap();
+        assert_eq!(format_fen(&state), START);
+        assert_eq!(state.hash(), State::start().hash());
+    }
+
+    #[test]
+    fn missing_clocks_default() {
+        let state = parse_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+        assert_eq!((state.halfmove_clock, state.fullmove_number), (0, 1));
+        assert_eq!(format_fen(&state), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn each_malformed_field_has_its_own_error() {
+        let cases = [
+            ("8/8/8 w", FenError::MissingFields(2)),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra", FenError::ExtraFields(7)),
+            ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::WrongRankCount(7)),
+            ("4k4/8/8/8/8/8/8/4K3 w - - 0 1", FenError::WrongRankLength(8)),
+            ("4k3/8/8/8/8/8/8/4X3 w - - 0 1", FenError::BadPieceChar('X')),
+            ("4k3/8/8/8/8/8/8/4K3 x - - 0 1", FenError::BadSideToMove("x".to_string())),
+            ("4k3/8/8/8/8/8/8/4K3 w KK - 0 1", FenError::BadCastling("KK".to_string())),
+            ("4k3/8/8/8/8/8/8/4K3 w - e4 0 1", FenError::BadEnPassant("e4".to_string())),
+            ("4k3/8/8/8/8/8/8/4K3 w - - x 1", FenError::BadClock("x".to_string())),
+        ];
+        for (fen, error) in cases {
+            assert_eq!(parse_fen(fen).err(), Some(error), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn clocks_too_large_to_store_are_rejected() {
+        assert_eq!(parse_fen("4k3/8/8/8/8/8/8/4K2R w K - 256 1").err(), Some(FenError::BadClock("256".to_string())));
+        assert_eq!(parse_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 65536").err(), Some(FenError::BadClock("65536".to_string())));
+    }
+
+    #[test]
+    fn clocks_at_their_limits_saturate_and_unmake_exactly() {
+        let mut state = parse_fen("4k3/8/8/8/8/8/8/4K2R w K - 255 200").unwrap();
+        let undo = state.make_move(Move::from_uci("e1f1", &state).unwrap());
+        assert_eq!(state.halfmove_clock, 255);
+        state.unmake_move(undo);
+        assert_eq!(format_fen(&state), "4k3/8/8/8/8/8/8/4K2R w K - 255 200");
+
+        let mut state = parse_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 65535").unwrap();
+        let undo = state.make_move(Move::from_uci("e8d8", &state).unwrap());
+        assert_eq!(state.fullmove_number, 65535);
+        state.unmake_move(undo);
+        assert_eq!(format_fen(&state), "4k3/8/8/8/8/8/8/4K3 b - - 0 65535");
+    }
+}
@@ -0,0 +1,176 @@
+//! C ABI, behind the `ffi` feature. A handle owns a game and a search; positions and moves
+//! cross the boundary as FEN and UCI strings. Functions return a negative `BC_ERR_*` code on
+//! failure, and a panic inside the engine is caught here rather than unwinding into C.
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::game::Game;
+use crate::mv::Move;
+use crate::search::{Search, SearchLimits};
+
+// --- Return Codes --- //
+pub const BC_OK: i32 = 0;
+/// A handle or string argument is null.
+pub const BC_ERR_NULL: i32 = -1;
+/// A string argument is not valid UTF-8.
+pub const BC_ERR_UTF8: i32 = -2;
+/// The FEN or UCI string does not parse.
+pub const BC_ERR_PARSE: i32 = -3;
+/// The move is not legal in the current position.
+pub const BC_ERR_ILLEGAL: i32 = -4;
+/// The output buffer cannot hold the string and its terminating NUL.
+pub const BC_ERR_BUFFER: i32 = -5;
+/// The engine panicked; the handle should be freed.
+pub const BC_ERR_PANIC: i32 = -6;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Opaque to C: a game in progress and the search that plays in it.
+pub struct BcGame {
+    game: Game,
+    search: Search,
+}
+
+// ============================================================================
+// Lifecycle
+// ============================================================================
+
+/// A new game at the standard initial position, or null if allocation panicked. Release it
+/// with `bc_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn bc_new_game() -> *mut BcGame {
+    catch_unwind(|| Box::into_raw(Box::new(BcGame { game: Game::new(), search: Search::new() })))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Free a handle from `bc_new_game`. Null is ignored.
+///
+/// # Safety
+/// `handle` must be null or a live handle from `bc_new_game`, not used again afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_free(handle: *mut BcGame) {
+    if handle.is_null() { return; }
+    // SAFETY: the caller passes a pointer from Box::into_raw and never uses it again
+    let game = unsafe { Box::from_raw(handle) };
+    let _ = catch_unwind(AssertUnwindSafe(move || drop(game)));
+}
+
+// ============================================================================
+// Position
+// ============================================================================
+
+/// Start a new game from a FEN string. On error the current game is kept.
+///
+/// # Safety
+/// `handle` must be a live handle and `fen` a NUL-terminated string, or either may be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_set_fen(handle: *mut BcGame, fen: *const c_char) -> i32 {
+    // SAFETY: forwarded from the caller's contract
+    guard(|| unsafe {
+        let handle = handle.as_mut().ok_or(BC_ERR_NULL)?;
+        handle.game = Game::from_fen(read_str(fen)?).map_err(|_| BC_ERR_PARSE)?;
+        Ok(BC_OK)
+    })
+}
+
+/// Write the current position as FEN into `buf`, returning its length without the NUL.
+///
+/// # Safety
+/// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_fen(handle: *const BcGame, buf: *mut c_char, len: usize) -> i32 {
+    // SAFETY: forwarded from the caller's contract
+    guard(|| unsafe {
+        let handle = handle.as_ref().ok_or(BC_ERR_NULL)?;
+        write_str(&handle.game.current().to_fen(), buf, len)
+    })
+}
+
+// ============================================================================
+// Moves
+// ============================================================================
+
+/// Write the legal moves as space-separated UCI into `buf`, returning its length without the
+/// NUL. A position without legal moves writes the empty string.
+///
+/// # Safety
+/// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_legal_moves(handle: *const BcGame, buf: *mut c_char, len: usize) -> i32 {
+    // SAFETY: forwarded from the caller's contract
+    guard(|| unsafe {
+        let handle = handle.as_ref().ok_or(BC_ERR_NULL)?;
+        let moves: Vec<String> = handle.game.current().moves().all().map(|mv| mv.to_uci(false)).collect();
+        write_str(&moves.join(" "), buf, len)
+    })
+}
+
+/// Play a legal move given in UCI notation.
+///
+/// # Safety
+/// `handle` must be a live handle and `uci_move` a NUL-terminated string, or either may be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_make_move(handle: *mut BcGame, uci_move: *const c_char) -> i32 {
+    // SAFETY: forwarded from the caller's contract
+    guard(|| unsafe {
+        let handle = handle.as_mut().ok_or(BC_ERR_NULL)?;
+        let mv = Move::from_uci(read_str(uci_move)?, handle.game.current()).map_err(|_| BC_ERR_PARSE)?;
+        handle.game.play(mv).map_err(|_| BC_ERR_ILLEGAL)?;
+        Ok(BC_OK)
+    })
+}
+
+/// Search to `depth` and write the best move in UCI notation into `buf`, `0000` when there is
+/// none, returning its length without the NUL. The move is not played.
+///
+/// # Safety
+/// `handle` must be a live handle and `buf` writable for `len` bytes, or either may be null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bc_best_move(handle: *mut BcGame, depth: u32, buf: *mut c_char, len: usize) -> i32 {
+    // SAFETY: forwarded from the caller's contract
+    guard(|| unsafe {
+        let handle = handle.as_mut().ok_or(BC_ERR_NULL)?;
+        let result = handle.search.best_move_in(&handle.game, SearchLimits::depth(depth));
+        let best = result.best_move.map_or("0000".to_string(), |mv| mv.to_uci(false));
+        write_str(&best, buf, len)
+    })
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+/// Run `body`, turning its error code or a panic into the return value.
+fn guard(body: impl FnOnce() -> Result<i32, i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(code) | Err(code)) => code,
+        Err(_) => BC_ERR_PANIC,
+    }
+}
+
+/// # Safety
+/// `ptr` must be null or a NUL-terminated string that outlives the returned borrow.
+unsafe fn read_str<'a>(ptr: *const c_char) -> Result<&'a str, i32> {
+    if ptr.is_null() { return Err(BC_ERR_NULL); }
+    // SAFETY: non-null and NUL-terminated per the caller
+    unsafe { CStr::from_ptr(ptr) }.to_str().map_err(|_| BC_ERR_UTF8)
+}
+
+/// Copy `text` and a NUL into `buf`, returning the length of `text`.
+///
+/// # Safety
+/// `buf` must be null or writable for `len` bytes.
+unsafe fn write_str(text: &str, buf: *mut c_char, len: usize) -> Result<i32, i32> {
+    if buf.is_null() { return Err(BC_ERR_NULL); }
+    if text.len() >= len { return Err(BC_ERR_BUFFER); }
+    // SAFETY: text.len() + 1 <= len bytes are writable per the caller
+    unsafe {
+        ptr::copy_nonoverlapping(text.as_ptr().cast::<c_char>(), buf, text.len());
+        *buf.add(text.len()) = 0;
+    }
+    i32::try_from(text.len()).map_err(|_| BC_ERR_BUFFER)
+}
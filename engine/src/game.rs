@@ -0,0 +1,200 @@
+//! A game record: a starting position and the moves played from it.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::fen::FenError;
+use crate::history::History;
+use crate::mv::Move;
+use crate::pgn::{canonical_result, format_pgn, parse_pgn, PgnError, PgnTags};
+use crate::state::{GameStatus, State, Undo};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A position plus everything needed to take moves back and to detect repetitions.
+#[derive(Clone, Debug)]
+pub struct Game {
+    initial: State,
+    current: State,
+    moves: Vec<Move>,
+    san: Vec<String>,
+    undos: Vec<Undo>,
+    history: History,
+    /// The tags the game was imported with, or the defaults.
+    tags: PgnTags,
+    /// A result recorded without the position showing it, as for a resignation or a draw by
+    /// agreement.
+    result: Option<&'static str>,
+}
+
+/// The move is not legal in the current position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IllegalMove(pub Move);
+
+// ============================================================================
+// Game — Construction
+// ============================================================================
+
+impl Game {
+    /// A game from the standard initial position.
+    pub fn new() -> Self {
+        Self::from_state(State::start())
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        State::from_fen(fen).map(Self::from_state)
+    }
+
+    pub fn from_state(state: State) -> Self {
+        let mut history = History::new();
+        history.push(state.hash());
+        Game {
+            initial: state.clone(),
+            current: state,
+            moves: Vec::new(),
+            san: Vec::new(),
+            undos: Vec::new(),
+            history,
+            tags: PgnTags::default(),
+            result: None,
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self { Self::new() }
+}
+
+// ============================================================================
+// Game — Playing
+// ============================================================================
+
+impl Game {
+    /// Play a move after checking it against the legal moves of the current position.
+    pub fn play(&mut self, mv: Move) -> Result<(), IllegalMove> {
+        if !self.current.is_legal_move(mv) { return Err(IllegalMove(mv)); }
+
+        self.san.push(mv.to_san(&self.current));
+        self.undos.push(self.current.make_move(mv));
+        self.moves.push(mv);
+        self.result = None;
+        // Every position is kept, not just those since the last irreversible move, so that
+        // `undo` can always pop; positions across an irreversible move never hash alike anyway
+        self.history.push(self.current.hash());
+        Ok(())
+    }
+
+    /// Take back the last move, returning it, or None at the starting position.
+    pub fn undo(&mut self) -> Option<Move> {
+        let undo = self.undos.pop()?;
+        self.current.unmake_move(undo);
+        self.history.pop();
+        self.result = None;
+        self.san.pop();
+        self.moves.pop()
+    }
+}
+
+// ============================================================================
+// Game — Queries
+// ============================================================================
+
+impl Game {
+    pub fn initial(&self) -> &State { &self.initial }
+    pub fn current(&self) -> &State { &self.current }
+    pub fn moves_played(&self) -> &[Move] { &self.moves }
+
+    /// The moves played, in SAN as written before each was made.
+    pub fn san_moves(&self) -> &[String] { &self.san }
+
+    /// Hashes of every position in the game, the initial one first.
+    pub fn history(&self) -> &History { &self.history }
+
+    /// Mate, stalemate, and the fifty-move, material, and repetition draws.
+    pub fn status(&self) -> GameStatus {
+        self.current.status_with(&self.history)
+    }
+}
+
+// ============================================================================
+// Game — PGN
+// ============================================================================
+
+impl Game {
+    /// Replay the mainline of the first game in a PGN text, keeping its tags and result.
+    pub fn from_pgn(pgn: &str) -> Result<Self, PgnError> {
+        parse_pgn(pgn)
+    }
+
+    /// Render the game as PGN with the given tags; `to_pgn(game.tags())` exports an imported
+    /// game as it came in. The result is taken from `status`, or while the game goes on from
+    /// the recorded result.
+    pub fn to_pgn(&self, tags: &PgnTags) -> String {
+        format_pgn(self, tags)
+    }
+
+    pub fn tags(&self) -> &PgnTags { &self.tags }
+
+    pub fn set_tags(&mut self, tags: PgnTags) { self.tags = tags; }
+
+    /// The result token recorded for a game its position does not show to be over. Playing or
+    /// taking back a move clears it.
+    pub fn recorded_result(&self) -> Option<&'static str> { self.result }
+
+    /// Record `1-0`, `0-1`, or `1/2-1/2`, as for a resignation; anything else clears the result.
+    pub fn set_result(&mut self, token: &str) { self.result = canonical_result(token).filter(|&r| r != "*"); }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "illegal move '{}'", self.0)
+    }
+}
+
+impl Error for IllegalMove {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Color;
+
+    const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn play_all(game: &mut Game, line: &[&str]) {
+        for text in line {
+            let mv = Move::from_uci(text, game.current()).unwrap();
+            game.play(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn scholars_mate_is_mate_and_undoes_back_to_the_start() {
+        let mut game = Game::new();
+        play_all(&mut game, &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"]);
+        assert_eq!(game.status(), GameStatus::Checkmate(Color::White));
+        assert_eq!(game.san_moves(), ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+        assert_eq!(game.current().to_fen(), "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4");
+
+        let mut taken_back = Vec::new();
+        while let Some(mv) = game.undo() {
+            taken_back.push(mv.to_uci(false));
+        }
+        assert_eq!(taken_back, ["h5f7", "g8f6", "d1h5", "b8c6", "f1c4", "e7e5", "e2e4"]);
+        assert_eq!(game.current().to_fen(), START_FEN);
+        assert_eq!(game.current().hash(), State::start().hash());
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        assert!(game.moves_played().is_empty() && game.san_moves().is_empty());
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.undo(), None);
+    }
+}
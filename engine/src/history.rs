@@ -0,0 +1,77 @@
+//! Repetition tracking over Zobrist hashes.
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Hashes of the positions reached since the last irreversible move, oldest first. The most
+/// recent entry is the current position.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    hashes: Vec<u64>,
+}
+
+// ============================================================================
+// History — Recording
+// ============================================================================
+
+impl History {
+    pub fn new() -> Self {
+        History { hashes: Vec::new() }
+    }
+
+    /// Record the position reached by a reversible move.
+    pub fn push(&mut self, hash: u64) {
+        self.hashes.push(hash);
+    }
+
+    /// Record the position reached by an irreversible move. Nothing before it can recur, so the
+    /// earlier entries are dropped.
+    pub fn reset(&mut self, hash: u64) {
+        self.hashes.clear();
+        self.hashes.push(hash);
+    }
+
+    /// Forget the most recent position, as when a search takes back a move. Only entries
+    /// recorded with `push` can be taken back this way.
+    pub fn pop(&mut self) -> Option<u64> {
+        self.hashes.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// The most recent `count` positions, the current one included.
+    pub fn tail(&self, count: usize) -> History {
+        History { hashes: self.hashes[self.hashes.len().saturating_sub(count)..].to_vec() }
+    }
+}
+
+// ============================================================================
+// History — Queries
+// ============================================================================
+
+impl History {
+    /// How many times the position has occurred, the current one included.
+    pub fn count(&self, hash: u64) -> usize {
+        self.hashes.iter().filter(|&&h| h == hash).count()
+    }
+
+    /// Whether the position is among the `count` most recent, the current one included.
+    pub fn occurs_within(&self, hash: u64, count: usize) -> bool {
+        self.hashes[self.hashes.len().saturating_sub(count)..].contains(&hash)
+    }
+
+    /// Whether the current position has occurred before. Search treats this as a draw.
+    pub fn is_repetition(&self) -> bool {
+        match self.hashes.split_last() {
+            Some((&current, earlier)) => earlier.contains(&current),
+            None => false,
+        }
+    }
+}
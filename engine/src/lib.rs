@@ -1,8 +1,47 @@
 #![feature(gen_blocks)]
 
+pub mod attacks;
+pub mod bits;
+pub mod book;
 pub mod board;
+pub mod builder;
 pub mod castling;
+pub mod epd;
+pub mod eval;
 pub mod mv;
 pub mod state;
+pub mod time;
+pub mod fen;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+pub mod history;
 pub mod mobility;
+pub mod notation;
+pub mod options;
+pub mod perft;
+pub mod play;
+pub mod pgn;
+pub mod review;
+pub mod score;
+pub mod search;
+pub mod see;
+pub mod skill;
+#[cfg(feature = "syzygy")]
+pub mod tablebase;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod training;
+pub mod tt;
+pub mod tuner;
+pub mod uci;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod xboard;
+pub mod zobrist;
+mod controller;
 mod display;
+#[cfg(feature = "magic")]
+mod magic;
+#[cfg(feature = "serde")]
+mod serialization;
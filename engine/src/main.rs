@@ -1,67 +1,140 @@
-use engine::board::{Board, Color, Piece, PieceType, Square, SlotExt};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-fn main() {
-    println!("=== Board API Test ===\n");
+use engine::book::build_from_pgn;
+use engine::epd::run_epd_suite_with;
+use engine::board::Color;
+use engine::eval::DEFAULT_PARAMS;
+use engine::play::PlayOptions;
+use engine::search::{bench, SearchLimits, SearchOptions, BENCH_DEPTH};
+use engine::training::{filter, self_play, SelfPlayOptions, TrainingReader, TrainingWriter};
+use engine::tuner::{load_dataset, tune};
 
-    // 1. Create a new empty board
-    let mut board = Board::new();
-    println!("1. Created empty board:");
-    println!("{}\n", board);
+/// Passes `tune` runs when none are given.
+const DEFAULT_TUNE_PASSES: u32 = 100;
 
-    // 2. Set pieces using IndexMut + Option::replace
-    let e1 = Square::from_coords(0, 4);  // e1
-    let e8 = Square::from_coords(7, 4);  // e8
-    let b1 = Square::from_coords(0, 1);  // b1
-    let c3 = Square::from_coords(2, 2);  // c3
+/// Plies of each game `book` counts when no limit is given.
+const DEFAULT_BOOK_PLIES: usize = 20;
 
-    board[e1].place(Piece::new(PieceType::King, Color::White));
-    board[e8].place(Piece::new(PieceType::King, Color::Black));
-    board[b1].place(Piece::new(PieceType::Knight, Color::White));
+/// Games a move needs to enter the book when no minimum is given.
+const DEFAULT_BOOK_GAMES: u32 = 3;
 
-    println!("2. After placing kings on e1/e8 and knight on b1:");
-    println!("{}\n", board);
+/// How `selfplay` plays when not told otherwise.
+const DEFAULT_SELFPLAY_GAMES: u32 = 100;
+const DEFAULT_SELFPLAY_DEPTH: u32 = 6;
+const SELFPLAY_RANDOM_PLIES: u32 = 8;
+const SELFPLAY_MAX_PLIES: u32 = 400;
 
-    // 3. Check a square using Index
-    println!("3. Checking squares:");
-    println!("   e1 has piece: {}", board[e1].is_some());
-    println!("   c3 is empty: {}", board[c3].is_none());
-    if let Some(piece) = board[e1] {
-        println!("   e1 piece: {} {:?}", piece, piece.piece_type());
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        // `bench [depth]`: fixed-depth search over the bench positions, ending with the node
+        // signature that must stay identical across refactors
+        Some("bench") => {
+            let depth = args.get(1).and_then(|d| d.parse().ok()).unwrap_or(BENCH_DEPTH);
+            let report = bench(depth);
+            println!("time  : {} ms", report.elapsed.as_millis());
+            println!("nps   : {}", report.nodes_per_second());
+            println!("{} nodes", report.nodes);
+            Ok(())
+        }
+        // `epd <file> [depth]`: run a test suite at fixed depth with and without the
+        // shallow-depth pruning, to show what the pruning misses
+        Some("epd") if args.len() >= 2 => {
+            let depth = args.get(2).and_then(|d| d.parse().ok()).unwrap_or(BENCH_DEPTH);
+            for (label, options) in [("pruned  ", SearchOptions::default()), ("unpruned", SearchOptions::NONE)] {
+                let report = run_epd_suite_with(&args[1], SearchLimits::depth(depth), options)?;
+                for (line, err) in &report.errors { eprintln!("line {}: {}", line, err); }
+                let missed: Vec<&str> = report.entries.iter()
+                    .filter(|entry| !entry.solved)
+                    .map(|entry| entry.id.as_deref().unwrap_or("?"))
+                    .collect();
+                println!("{}: {}/{} solved, missed {}", label, report.solved(), report.total(), missed.join(" "));
+            }
+            Ok(())
+        }
+        // `tune <data> <params-out> [passes]`: fit the evaluation weights to FEN + result
+        // lines or a `selfplay` training file, starting from the built-in ones
+        Some("tune") if args.len() >= 3 => {
+            let dataset = load_dataset(&args[1])?;
+            for (line, err) in &dataset.errors { eprintln!("line {}: {}", line, err); }
+            let passes = args.get(3).and_then(|p| p.parse().ok()).unwrap_or(DEFAULT_TUNE_PASSES);
+            let report = tune(&dataset.samples, DEFAULT_PARAMS, passes, |pass, error| {
+                println!("pass {:>3}: error {:.6}", pass, error);
+            });
+            fs::write(&args[2], report.params.to_text())?;
+            println!("samples: {}", dataset.samples.len());
+            println!("k      : {:.4}", report.k);
+            println!("before : {:.6}", report.initial_error);
+            println!("after  : {:.6}", report.final_error);
+            Ok(())
+        }
+        // `book <pgn> <bin-out> [max-ply] [min-games]`: build a Polyglot book from a PGN
+        // collection, streamed rather than loaded whole
+        Some("book") if args.len() >= 3 => {
+            let max_ply = args.get(3).and_then(|p| p.parse().ok()).unwrap_or(DEFAULT_BOOK_PLIES);
+            let min_games = args.get(4).and_then(|g| g.parse().ok()).unwrap_or(DEFAULT_BOOK_GAMES);
+            let book = build_from_pgn(BufReader::new(File::open(&args[1])?), max_ply, min_games)?;
+            book.write(File::create(&args[2])?)?;
+            println!("entries: {}", book.len());
+            Ok(())
+        }
+        // `selfplay --filter <in> <out>`: copy a training file without repeated positions,
+        // positions in check, or scores near mate
+        Some("selfplay") if args.len() >= 4 && args[1] == "--filter" => {
+            let reader = TrainingReader::new(BufReader::new(File::open(&args[2])?))?;
+            let mut writer = TrainingWriter::new(BufWriter::new(File::create(&args[3])?))?;
+            let report = filter(reader, &mut writer)?;
+            writer.finish()?;
+            println!("kept      : {}", report.kept);
+            println!("duplicates: {}", report.duplicates);
+            println!("in check  : {}", report.in_check);
+            println!("near mate : {}", report.near_mate);
+            Ok(())
+        }
+        // `selfplay <out> [games] [depth]`: play the engine against itself from randomized
+        // openings, writing a training record for every searched position
+        Some("selfplay") if args.len() >= 2 => {
+            let options = SelfPlayOptions {
+                games: args.get(2).and_then(|g| g.parse().ok()).unwrap_or(DEFAULT_SELFPLAY_GAMES),
+                depth: args.get(3).and_then(|d| d.parse().ok()).unwrap_or(DEFAULT_SELFPLAY_DEPTH),
+                random_plies: SELFPLAY_RANDOM_PLIES,
+                max_plies: SELFPLAY_MAX_PLIES,
+                seed: SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64),
+            };
+            let mut writer = TrainingWriter::new(BufWriter::new(File::create(&args[1])?))?;
+            let records = self_play(&options, &mut writer)?;
+            writer.finish()?;
+            println!("records: {}", records);
+            Ok(())
+        }
+        // `play [white|black] [depth | <ms>ms] [ascii]`: play against the engine at the terminal,
+        // moves in SAN or UCI; a bare number is a search depth, one ending in `ms` a move time
+        Some("play") => {
+            let mut options = PlayOptions::default();
+            for arg in &args[1..] {
+                match arg.as_str() {
+                    "white" => options.human = Color::White,
+                    "black" => options.human = Color::Black,
+                    "ascii" => options.ascii = true,
+                    limit => match limit.strip_suffix("ms") {
+                        Some(ms) => if let Ok(ms) = ms.parse() { options.limits = SearchLimits::movetime(Duration::from_millis(ms)); },
+                        None => if let Ok(depth) = limit.parse() { options.limits = SearchLimits::depth(depth); },
+                    },
+                }
+            }
+            engine::play::run(io::stdin().lock(), io::stdout(), options)
+        }
+        // Otherwise the first line picks the protocol: `xboard` starts CECP, anything else UCI
+        _ => {
+            let mut stdin = io::stdin().lock();
+            let mut first = String::new();
+            stdin.read_line(&mut first)?;
+            let xboard = first.trim() == "xboard";
+            let input = Cursor::new(first).chain(stdin);
+            if xboard { engine::xboard::run(input, io::stdout()) } else { engine::uci::run(input, io::stdout()) }
+        }
     }
-    println!();
-
-    // 4. Set with replace (returns old value)
-    println!("4. Replace knight on b1 with a queen:");
-    let old = board[b1].place(Piece::new(PieceType::Queen, Color::White));
-    println!("   Old piece was: {:?}", old.map(|p| p.piece_type()));
-    println!("{}\n", board);
-
-    // 5. Remove a piece using take
-    println!("5. Remove the queen from b1:");
-    let removed = board[b1].lift();
-    println!("   Removed: {:?}", removed.map(|p| p.piece_type()));
-    println!("{}\n", board);
-
-    // 6. Move a piece (atomic operation)
-    println!("6. Move white king from e1 to e2:");
-    let e2 = Square::from_coords(1, 4);
-    let captured = board.move_piece(e1, e2);
-    println!("   Captured: {:?}", captured);
-    println!("{}\n", board);
-
-    // 7. Iterate over all pieces
-    println!("7. All pieces on board:");
-    for (sq, piece) in board.pieces() {
-        println!("   {} at {}", piece, sq);
-    }
-    println!();
-
-    // 8. Test different square representations
-    println!("8. Indexing with different types:");
-    let _ = board[0usize];           // usize index
-    let _ = board[(0u8, 4u8)];       // (rank, file) tuple
-    let _ = board[e2];               // Square directly
-    println!("   All indexing methods work!\n");
-
-    println!("=== All tests passed! ===");
 }
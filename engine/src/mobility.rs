@@ -1,7 +1,14 @@
 //! Move generation.
 
-use crate::board::{Board, Color, PieceType, Square};
-use crate::mv::Move;
+use std::sync::OnceLock;
+
+use crate::attacks::{
+    between, bishop_attacks, king_attacks, knight_attacks, line, pawn_attacks, piece_attacks, queen_attacks, rook_attacks,
+};
+use crate::bits::SquareSet;
+use crate::board::{Board, Color, Lateral, Piece, PieceType, Square};
+use crate::castling::CastlingSide;
+use crate::mv::{IllegalMoveError, Move, MoveType};
 use crate::state::State;
 
 // ============================================================================
@@ -12,6 +19,28 @@ pub struct MoveGenerator<'a> {
     state: &'a State,
 }
 
+/// What the side to move's king faces in one position, computed the first time
+/// `State::attack_info` asks and shared by generation, legality, and check detection until
+/// the position changes. The full attack maps, which only some callers want, are filled in on
+/// their own first use.
+#[derive(Clone, Debug)]
+pub struct AttackInfo {
+    pub king_sq: Square,
+    /// Enemy pieces giving check to the side to move.
+    pub checkers: SquareSet,
+    /// Pieces of the side to move pinned to their king by an enemy slider.
+    pub pinned: SquareSet,
+    /// Squares next to the king that it cannot step onto: those the enemy attacks with the
+    /// king lifted off the board, so that it cannot hide behind itself from a slider. Castling
+    /// is judged separately.
+    pub king_danger: SquareSet,
+    attacked: [OnceLock<SquareSet>; 2],
+}
+
+/// Which partition of the move list a generator pass produces.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MoveFilter { All, Captures, Quiets }
+
 // ============================================================================
 // MoveGenerator — Public Interface
 // ============================================================================
@@ -25,24 +54,45 @@ impl<'a> MoveGenerator<'a> {
 
     /// Generate all legal moves for the current side to move.
     pub fn all(self) -> impl Iterator<Item = Move> + 'a {
-        gen move {
-            for mv in pseudo_legal_moves(self.state) {
-                if is_legal(self.state, mv) {
-                    yield mv;
-                }
-            }
-        }
+        legal_moves(self.state, MoveFilter::All)
+    }
+
+    /// Generate legal moves that capture material, including en passant and capturing promotions.
+    pub fn captures(self) -> impl Iterator<Item = Move> + 'a {
+        legal_moves(self.state, MoveFilter::Captures)
+    }
+
+    /// Generate legal non-captures: pushes, quiet promotions, and castling.
+    pub fn quiets(self) -> impl Iterator<Item = Move> + 'a {
+        legal_moves(self.state, MoveFilter::Quiets)
     }
 
     /// Generate legal moves from a specific square.
     pub fn from(self, sq: Square) -> impl Iterator<Item = Move> + 'a {
-        gen move {
-            for mv in pseudo_legal_moves(self.state) {
-                if mv.source() == sq && is_legal(self.state, mv) {
-                    yield mv;
-                }
-            }
+        legal_moves(self.state, MoveFilter::All).filter(move |mv| mv.source() == sq)
+    }
+
+    /// Generate legal moves landing on a specific square, one per promotion piece. Castling
+    /// lands where the king ends up.
+    pub fn to(self, sq: Square) -> impl Iterator<Item = Move> + 'a {
+        legal_moves(self.state, MoveFilter::All).filter(move |mv| mv.destination() == sq)
+    }
+
+    /// Whether there is any legal move, stopping at the first. In check the king's steps are
+    /// tried before blocks and captures, being the likeliest way out.
+    pub fn any(self) -> bool {
+        let state = self.state;
+        let info = state.attack_info();
+        if !state.in_check() {
+            return pseudo_legal_moves(state, MoveFilter::All).any(|mv| info.is_legal(state, mv));
         }
+        king_moves(state, info.king_sq, MoveFilter::All).any(|mv| info.is_legal(state, mv))
+            || evasions(state, info, MoveFilter::All).next().is_some()
+    }
+
+    /// Number of legal moves.
+    pub fn count(self) -> usize {
+        self.all().count()
     }
 }
 
@@ -50,21 +100,120 @@ impl<'a> MoveGenerator<'a> {
 // Pseudo-Legal Move Generation
 // ============================================================================
 
-fn pseudo_legal_moves(state: &State) -> impl Iterator<Item = Move> + '_ {
+impl MoveFilter {
+    const fn quiets(self) -> bool { !matches!(self, MoveFilter::Captures) }
+    const fn captures(self) -> bool { !matches!(self, MoveFilter::Quiets) }
+
+    /// Whether a move onto `to` belongs to this partition for the side to move.
+    fn admits(self, state: &State, to: Square) -> bool {
+        match state.board[to] {
+            None => self.quiets(),
+            Some(target) => target.color() != state.to_move && self.captures(),
+        }
+    }
+}
+
+fn legal_moves(state: &State, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
     gen move {
-        for (sq, piece) in state.board.pieces() {
-            if piece.color() != state.to_move {
-                continue;
+        let info = state.attack_info();
+        if state.in_check() {
+            for mv in evasions(state, info, filter) { yield mv; }
+            return;
+        }
+
+        for mv in pseudo_legal_moves(state, filter) {
+            if info.is_legal(state, mv) {
+                yield mv;
             }
+        }
+    }
+}
 
-            match piece.piece_type() {
-                PieceType::Pawn   => { for mv in pawn_moves(state, sq)   { yield mv; } }
-                PieceType::Knight => { for mv in knight_moves(state, sq) { yield mv; } }
-                PieceType::Bishop => { for mv in bishop_moves(state, sq) { yield mv; } }
-                PieceType::Rook   => { for mv in rook_moves(state, sq)   { yield mv; } }
-                PieceType::Queen  => { for mv in queen_moves(state, sq)  { yield mv; } }
-                PieceType::King   => { for mv in king_moves(state, sq)   { yield mv; } }
+/// Legal moves when in check: king moves, captures of a single checker, and interpositions.
+fn evasions<'a>(state: &'a State, info: &'a AttackInfo, filter: MoveFilter) -> impl Iterator<Item = Move> + 'a {
+    gen move {
+        let king_sq = info.king_sq;
+        let mut checkers = info.checkers.into_iter();
+        let checker = checkers.next().expect("evasions require a checker");
+
+        // Double check: only the king can move
+        if checkers.next().is_some() {
+            for mv in king_moves(state, king_sq, filter) {
+                if info.is_legal(state, mv) { yield mv; }
             }
+            return;
+        }
+
+        for mv in pseudo_legal_moves(state, filter) {
+            // Pinned blockers and king steps along the checking ray still need verification
+            if resolves_check(king_sq, checker, mv) && info.is_legal(state, mv) { yield mv; }
+        }
+    }
+}
+
+/// Whether a move could answer a single check: a king move, a capture of the checker, or an
+/// interposition. Leaping and adjacent checkers leave nothing between to interpose on.
+fn resolves_check(king_sq: Square, checker: Square, mv: Move) -> bool {
+    mv.source() == king_sq
+        || mv.target() == checker
+        || between(king_sq, checker).contains(mv.target())
+        || (mv.move_type() == MoveType::EnPassant && mv.en_passant_capture() == checker)
+}
+
+/// Why `mv` is not legal for the side to move, if it is not. Only the moving piece's own moves
+/// are generated, matching the full encoding so that no stray flag or promotion bits pass.
+pub(crate) fn check_move(state: &State, mv: Move) -> Result<(), IllegalMoveError> {
+    let (source, target) = (mv.source(), mv.target());
+    let piece = state.board[source].ok_or(IllegalMoveError::NoPieceOnSource)?;
+    if piece.color() != state.to_move { return Err(IllegalMoveError::WrongColor); }
+
+    let pawn = piece.piece_type() == PieceType::Pawn;
+    let promotes = pawn && target.rank() == state.to_move.promotion_rank();
+    let malformed = match mv.move_type() {
+        MoveType::Normal      if promotes => Some(IllegalMoveError::BadPromotion),
+        MoveType::Promotion   if !promotes => Some(IllegalMoveError::BadPromotion),
+        MoveType::EnPassant   if !pawn || state.en_passant != Some(target) => Some(IllegalMoveError::BadEnPassant),
+        MoveType::Castling    if piece.piece_type() != PieceType::King => Some(IllegalMoveError::BadCastling),
+        _ => None,
+    };
+    if let Some(err) = malformed { return Err(err); }
+    if !piece_moves(state, source, piece.piece_type(), MoveFilter::All).any(|candidate| candidate == mv) {
+        return Err(match mv.move_type() {
+            MoveType::Castling => IllegalMoveError::BadCastling,
+            MoveType::EnPassant => IllegalMoveError::BadEnPassant,
+            _ => IllegalMoveError::CannotReach,
+        });
+    }
+
+    let info = state.attack_info();
+    let mut checkers = info.checkers.into_iter();
+    let answers_check = match (checkers.next(), checkers.next()) {
+        (None, _) => true,
+        (Some(checker), None) => resolves_check(info.king_sq, checker, mv),
+        (Some(_), Some(_)) => source == info.king_sq,
+    };
+    if !answers_check || !info.is_legal(state, mv) { return Err(IllegalMoveError::LeavesKingInCheck); }
+    Ok(())
+}
+
+fn pseudo_legal_moves(state: &State, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    gen move {
+        for (sq, piece) in state.board.pieces_of_color(state.to_move) {
+            for mv in piece_moves(state, sq, piece.piece_type(), filter) { yield mv; }
+        }
+    }
+}
+
+/// Pseudo-legal moves of the side to move's piece of `piece_type` on `from`.
+fn piece_moves(state: &State, from: Square, piece_type: PieceType, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    gen move {
+        match piece_type {
+            PieceType::Pawn   => { for mv in pawn_moves(state, from, filter)   { yield mv; } }
+            PieceType::Knight => { for mv in knight_moves(state, from, filter) { yield mv; } }
+            PieceType::Bishop => { for mv in bishop_moves(state, from, filter) { yield mv; } }
+            PieceType::Rook   => { for mv in rook_moves(state, from, filter)   { yield mv; } }
+            PieceType::Queen  => { for mv in queen_moves(state, from, filter)  { yield mv; } }
+            PieceType::King   => { for mv in king_moves(state, from, filter)   { yield mv; } }
         }
     }
 }
@@ -75,83 +224,196 @@ fn pseudo_legal_moves(state: &State) -> impl Iterator<Item = Move> + '_ {
 
 // --- Knight --- //
 
-const KNIGHT_OFFSETS: [(i8, i8); 8] = [
-    (-2, -1), (-2, 1), (-1, -2), (-1, 2),
-    ( 1, -2), ( 1, 2), ( 2, -1), ( 2, 1),
-];
+fn knight_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    targeted_moves(state, from, knight_attacks(from), filter)
+}
 
-fn knight_moves(state: &State, from: Square) -> impl Iterator<Item = Move> + '_ {
+// --- Pawn --- //
+
+fn pawn_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
     gen move {
         let color = state.to_move;
-        for (dr, df) in KNIGHT_OFFSETS {
-            if let Some(to) = from.offset(dr, df) {
+
+        // Pushes: single, then double from the start rank through an empty square
+        if filter.quiets()
+            && let Some(one) = from.forward(color, 1, Lateral::Straight)
+            && state.board[one].is_none()
+        {
+            for mv in pawn_advance(from, one, color) { yield mv; }
+
+            if from.rank() == color.pawn_rank()
+                && let Some(two) = from.forward(color, 2, Lateral::Straight)
+                && state.board[two].is_none()
+            {
+                yield Move::new(from, two);
+            }
+        }
+
+        // Captures, including en passant onto the skipped square
+        for lateral in [Lateral::Left, Lateral::Right] {
+            if filter.captures() && let Some(to) = from.forward(color, 1, lateral) {
                 match state.board[to] {
-                    None => yield Move::new(from, to),
-                    Some(target) if target.color() != color => yield Move::new(from, to),
-                    Some(_) => {}  // blocked by own piece
+                    Some(target) if target.color() != color => {
+                        for mv in pawn_advance(from, to, color) { yield mv; }
+                    }
+                    None if state.en_passant == Some(to) => yield Move::en_passant(from, to),
+                    _ => {}
                 }
             }
         }
     }
 }
 
-// --- Pawn --- //
-
-fn pawn_moves(_state: &State, _from: Square) -> impl Iterator<Item = Move> + '_ {
+/// A pawn step onto `to`, expanded into one move per promotable piece on the last rank.
+fn pawn_advance(from: Square, to: Square, color: Color) -> impl Iterator<Item = Move> {
     gen move {
-        // TODO: implement pawn moves
+        if to.rank() == color.promotion_rank() {
+            for piece_type in PieceType::PROMOTABLE { yield Move::promotion(from, to, piece_type); }
+        } else {
+            yield Move::new(from, to);
+        }
     }
 }
 
-// --- Bishop --- //
+// --- Sliders --- //
 
-fn bishop_moves(_state: &State, _from: Square) -> impl Iterator<Item = Move> + '_ {
-    gen move {
-        // TODO: implement bishop moves (diagonal sliding)
-    }
+fn bishop_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    targeted_moves(state, from, bishop_attacks(from, state.board.occupied()), filter)
 }
 
-// --- Rook --- //
-
-fn rook_moves(_state: &State, _from: Square) -> impl Iterator<Item = Move> + '_ {
-    gen move {
-        // TODO: implement rook moves (orthogonal sliding)
-    }
+fn rook_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    targeted_moves(state, from, rook_attacks(from, state.board.occupied()), filter)
 }
 
-// --- Queen --- //
+fn queen_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    targeted_moves(state, from, queen_attacks(from, state.board.occupied()), filter)
+}
 
-fn queen_moves(_state: &State, _from: Square) -> impl Iterator<Item = Move> + '_ {
-    gen move {
-        // TODO: implement queen moves (bishop + rook)
-    }
+/// One move onto each attacked square that the filter admits; own pieces are never admitted.
+fn targeted_moves(state: &State, from: Square, attacks: SquareSet, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
+    attacks.into_iter()
+        .filter(move |&to| filter.admits(state, to))
+        .map(move |to| Move::new(from, to))
 }
 
 // --- King --- //
 
-fn king_moves(_state: &State, _from: Square) -> impl Iterator<Item = Move> + '_ {
+fn king_moves(state: &State, from: Square, filter: MoveFilter) -> impl Iterator<Item = Move> + '_ {
     gen move {
-        // TODO: implement king moves (1-square in any direction + castling)
+        for mv in targeted_moves(state, from, king_attacks(from), filter) { yield mv; }
+
+        for side in [CastlingSide::Kingside, CastlingSide::Queenside] {
+            if filter.quiets() && can_castle(state, from, side) {
+                yield Move::castling(from, state.castling_rights.rook_square(state.to_move, side));
+            }
+        }
     }
 }
 
+/// Rights held, the rook in place, every square either piece crosses empty apart from the two
+/// of them, and the king neither starts in, passes through, nor lands in check. Attacks are
+/// judged with both pieces lifted, since in Chess960 the rook may be shielding the king.
+fn can_castle(state: &State, from: Square, side: CastlingSide) -> bool {
+    let (board, color) = (&state.board, state.to_move);
+    let rights = state.castling_rights;
+
+    if !rights.has(color, side) || from.rank() != color.home_rank() { return false; }
+    let rook_sq = rights.rook_square(color, side);
+    if board[rook_sq] != Some(Piece::new(PieceType::Rook, color)) { return false; }
+
+    let king_to = Square::from_coords(from.rank(), side.king_target_file());
+    let rook_to = Square::from_coords(from.rank(), side.rook_target_file());
+    let lifted = SquareSet::from_squares(&[from, rook_sq]);
+    let crossed = (rank_span(from, king_to) | rank_span(rook_sq, rook_to)) - lifted;
+    if board.occupied().intersects(crossed) { return false; }
+
+    let occupied = board.occupied() - lifted;
+    rank_span(from, king_to).into_iter().all(|sq| attackers_mask_through(board, sq, !color, occupied).is_empty())
+}
+
+/// Squares from `a` to `b` inclusive, both on the same rank.
+fn rank_span(a: Square, b: Square) -> SquareSet {
+    let (low, high) = (a.index().min(b.index()), a.index().max(b.index()));
+    (low..=high).map(Square::from_index).collect()
+}
+
 // ============================================================================
 // Legality Checking
 // ============================================================================
 
-/// Check if a pseudo-legal move is actually legal (doesn't leave king in check).
-fn is_legal(state: &State, mv: Move) -> bool {
-    let new_state = state.clone().apply_move(mv);
-    let king_sq = find_king(&new_state.board, state.to_move);
-    !is_square_attacked(&new_state.board, king_sq, !state.to_move)
+// Scans of the king's surroundings on this thread, so tests can see the cache hold
+#[cfg(test)]
+thread_local! {
+    pub(crate) static KING_SCANS: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+impl AttackInfo {
+    pub(crate) fn new(state: &State) -> Self {
+        #[cfg(test)]
+        KING_SCANS.with(|scans| scans.set(scans.get() + 1));
+        let (board, us) = (&state.board, state.to_move);
+        let king_sq = find_king(board, us);
+        let lifted = board.occupied() - SquareSet::from_square(king_sq);
+        let king_danger = (king_attacks(king_sq) - board.occupancy(us)).into_iter()
+            .filter(|&sq| !attackers_mask_through(board, sq, !us, lifted).is_empty())
+            .collect();
+        AttackInfo {
+            king_sq,
+            checkers: attackers_mask(board, king_sq, !us),
+            pinned: pinned_pieces(board, us),
+            king_danger,
+            attacked: [OnceLock::new(), OnceLock::new()],
+        }
+    }
+
+    /// Every square `color` attacks on `board`, the one this was built from, whatever stands
+    /// on it. Computed on first use.
+    pub(crate) fn attacked(&self, board: &Board, color: Color) -> SquareSet {
+        *self.attacked[color as usize].get_or_init(|| attacked_by(board, color))
+    }
+
+    /// Whether a pseudo-legal move leaves the king safe. Only king steps, en passant, and moves
+    /// of pinned pieces can fail; castling was fully checked when generated.
+    fn is_legal(&self, state: &State, mv: Move) -> bool {
+        let (source, target) = (mv.source(), mv.target());
+        match mv.move_type() {
+            MoveType::Castling => true,
+            // Both pawns leave the capturing rank at once, which can uncover a slider on the king
+            MoveType::EnPassant => {
+                let new_state = state.clone().apply_move(mv);
+                !is_square_attacked(&new_state.board, self.king_sq, !state.to_move)
+            }
+            _ if source == self.king_sq => !self.king_danger.contains(target),
+            _ if self.pinned.contains(source) => line(self.king_sq, source).contains(target),
+            _ => true,
+        }
+    }
+}
+
+/// Pieces of `color` that are the only piece between their king and an enemy slider.
+pub fn pinned_pieces(board: &Board, color: Color) -> SquareSet {
+    let Some(king_sq) = board.king_square(color) else { return SquareSet::EMPTY };
+    let enemy = !color;
+    let queens = board.pieces_of(enemy, PieceType::Queen);
+    let snipers = (rook_attacks(king_sq, SquareSet::EMPTY) & (board.pieces_of(enemy, PieceType::Rook) | queens))
+        | (bishop_attacks(king_sq, SquareSet::EMPTY) & (board.pieces_of(enemy, PieceType::Bishop) | queens));
+
+    let mut pinned = SquareSet::EMPTY;
+    for sniper in snipers {
+        let blockers = between(king_sq, sniper) & board.occupied();
+        if blockers.len() == 1 && blockers.intersects(board.occupancy(color)) { pinned |= blockers; }
+    }
+    pinned
+}
+
+/// Enemy pieces giving check to the side to move; see `State::checkers`.
+pub fn checkers(state: &State) -> SquareSet {
+    state.checkers()
 }
 
 /// Find the king of a given color on the board.
 fn find_king(board: &Board, color: Color) -> Square {
-    board.pieces()
-        .find(|(_, p)| p.piece_type() == PieceType::King && p.color() == color)
-        .map(|(sq, _)| sq)
-        .expect("king must exist")
+    board.king_square(color).expect("king must exist")
 }
 
 // ============================================================================
@@ -160,26 +422,258 @@ fn find_king(board: &Board, color: Color) -> Square {
 
 /// Check if a square is attacked by pieces of a given color.
 pub fn is_square_attacked(board: &Board, square: Square, by: Color) -> bool {
-    // Check knight attacks
-    for (dr, df) in KNIGHT_OFFSETS {
-        if let Some(sq) = square.offset(dr, df) {
-            if let Some(piece) = board[sq] {
-                if piece.color() == by && piece.piece_type() == PieceType::Knight {
-                    return true;
-                }
-            }
+    !attackers_mask(board, square, by).is_empty()
+}
+
+/// Pieces of a given color attacking a square, including sliders lined up behind another of
+/// their attackers, as in a battery. Direct attackers come first, then each x-ray layer.
+pub fn attackers_of(board: &Board, square: Square, by: Color) -> impl Iterator<Item = Square> + '_ {
+    gen move {
+        let mut occupied = board.occupied();
+        let mut found = SquareSet::EMPTY;
+        loop {
+            let layer = attackers_mask_through(board, square, by, occupied) - found;
+            if layer.is_empty() { break; }
+            for sq in layer { yield sq; }
+            found |= layer;
+            occupied -= layer;
         }
     }
+}
+
+/// Every square `color` attacks.
+fn attacked_by(board: &Board, color: Color) -> SquareSet {
+    board.pieces_of_color(color).fold(SquareSet::EMPTY, |attacked, (sq, piece)| attacked | piece_attacks(piece, sq, board.occupied()))
+}
+
+/// Every attacker at once: each piece type's attacks from the target square, intersected with
+/// where the attacking side keeps that piece type. Pawns look backwards, so they use the
+/// defender's capture pattern.
+fn attackers_mask(board: &Board, square: Square, by: Color) -> SquareSet {
+    attackers_mask_through(board, square, by, board.occupied())
+}
 
-    // TODO: Check sliding piece attacks (bishop, rook, queen)
-    // TODO: Check pawn attacks
-    // TODO: Check king attacks
+/// `attackers_mask` with sliders blocked only by `occupied`, as if other squares were empty.
+/// Pieces are not required to be in `occupied` themselves.
+pub(crate) fn attackers_mask_through(board: &Board, square: Square, by: Color, occupied: SquareSet) -> SquareSet {
+    let queens = board.pieces_of(by, PieceType::Queen);
 
-    false
+    (knight_attacks(square) & board.pieces_of(by, PieceType::Knight))
+        | (king_attacks(square) & board.pieces_of(by, PieceType::King))
+        | (pawn_attacks(!by, square) & board.pieces_of(by, PieceType::Pawn))
+        | (bishop_attacks(square, occupied) & (board.pieces_of(by, PieceType::Bishop) | queens))
+        | (rook_attacks(square, occupied) & (board.pieces_of(by, PieceType::Rook) | queens))
 }
 
-/// Check if the current side to move is in check.
+/// Whether `mv`, legal for the side to move, checks the enemy king, found from attack masks
+/// without playing it: directly from the moved (or promoted, or castled) piece, or by a slider
+/// uncovered by the vacated squares, an en passant victim's included.
+pub fn gives_check(state: &State, mv: Move) -> bool {
+    let board = &state.board;
+    let us = state.to_move;
+    let Some(enemy_king) = board.king_square(!us) else { return false };
+    let Some(piece) = board[mv.source()] else { return false };
+
+    let (checker, landing, vacated) = match mv.move_type() {
+        MoveType::Castling => {
+            let (rook_source, rook_target) = mv.castling_rook_squares();
+            (Piece::new(PieceType::Rook, us), rook_target, SquareSet::from_squares(&[mv.source(), rook_source]))
+        }
+        MoveType::EnPassant => (piece, mv.target(), SquareSet::from_squares(&[mv.source(), mv.en_passant_capture()])),
+        MoveType::Promotion => (mv.promoted_piece(us), mv.target(), SquareSet::from_square(mv.source())),
+        MoveType::Normal => (piece, mv.target(), SquareSet::from_square(mv.source())),
+    };
+    let occupied = (board.occupied() - vacated) | SquareSet::from_squares(&[landing, mv.destination()]);
+    if piece_attacks(checker, landing, occupied).contains(enemy_king) { return true; }
+
+    let queens = board.pieces_of(us, PieceType::Queen);
+    let bishops = (board.pieces_of(us, PieceType::Bishop) | queens) - vacated;
+    let rooks = (board.pieces_of(us, PieceType::Rook) | queens) - vacated;
+    bishop_attacks(enemy_king, occupied).intersects(bishops) || rook_attacks(enemy_king, occupied).intersects(rooks)
+}
+
+/// Check if the current side to move is in check; see `State::in_check`.
 pub fn is_in_check(state: &State) -> bool {
-    let king_sq = find_king(&state.board, state.to_move);
-    is_square_attacked(&state.board, king_sq, !state.to_move)
+    state.in_check()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::StateBuilder;
+
+    /// The legal moves from `from` in a FEN position, in sorted coordinate notation.
+    fn moves_from(fen: &str, from: Square) -> Vec<String> {
+        let state = State::from_fen(fen).unwrap();
+        let mut moves: Vec<String> = state.moves().from(from).map(|mv| mv.to_uci(false)).collect();
+        moves.sort();
+        moves
+    }
+
+    // --- Pawns --- //
+
+    #[test]
+    fn pawns_push_toward_their_own_promotion_rank() {
+        let fen = "4k3/3p4/8/8/8/8/4P3/4K3 w - - 0 1";
+        assert_eq!(moves_from(fen, Square::E2), ["e2e3", "e2e4"]);
+        let fen = "4k3/3p4/8/8/8/8/4P3/4K3 b - - 0 1";
+        assert_eq!(moves_from(fen, Square::D7), ["d7d5", "d7d6"]);
+    }
+
+    #[test]
+    fn pawns_double_push_only_from_their_start_rank_through_empty_squares() {
+        // Off the start rank, and on it behind a blocker two squares ahead and one ahead
+        assert_eq!(moves_from("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1", Square::E3), ["e3e4"]);
+        assert_eq!(moves_from("4k3/8/8/8/4n3/8/4P3/4K3 w - - 0 1", Square::E2), ["e2e3"]);
+        assert!(moves_from("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1", Square::E2).is_empty());
+        // A black pawn standing on White's start rank is a step from promoting, not starting
+        assert_eq!(moves_from("4k3/8/8/8/8/8/p7/4K3 b - - 0 1", Square::A2), ["a2a1b", "a2a1n", "a2a1q", "a2a1r"]);
+    }
+
+    #[test]
+    fn pawns_capture_only_enemy_pieces_diagonally() {
+        let fen = "4k3/8/8/8/8/3b1N2/4P3/4K3 w - - 0 1";
+        assert_eq!(moves_from(fen, Square::E2), ["e2d3", "e2e3", "e2e4"]);
+    }
+
+    #[test]
+    fn en_passant_needs_a_pawn_beside_the_one_that_pushed() {
+        assert_eq!(moves_from("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", Square::E5), ["e5d6", "e5e6"]);
+        let state = State::from_fen("4k3/8/8/3p4/8/8/4P3/4K3 w - d6 0 1").unwrap();
+        assert!(state.moves().all().all(|mv| mv.move_type() != MoveType::EnPassant));
+    }
+
+    #[test]
+    fn promotions_come_one_per_piece_for_pushes_and_captures() {
+        let fen = "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+        let moves = moves_from(fen, Square::A7);
+        assert_eq!(moves, ["a7a8b", "a7a8n", "a7a8q", "a7a8r", "a7b8b", "a7b8n", "a7b8q", "a7b8r"]);
+    }
+
+    // --- Sliders --- //
+
+    #[test]
+    fn rook_rays_stop_before_friends_and_on_enemies() {
+        let fen = "4k3/8/8/8/P7/8/8/R2nK3 w - - 0 1";
+        assert_eq!(moves_from(fen, Square::A1), ["a1a2", "a1a3", "a1b1", "a1c1", "a1d1"]);
+    }
+
+    #[test]
+    fn queens_move_as_a_rook_and_a_bishop_together() {
+        let queen = State::from_fen("4k3/8/8/2p5/8/2Q1P3/8/4K3 w - - 0 1").unwrap();
+        let rook = State::from_fen("4k3/8/8/2p5/8/2R1P3/8/4K3 w - - 0 1").unwrap();
+        let bishop = State::from_fen("4k3/8/8/2p5/8/2B1P3/8/4K3 w - - 0 1").unwrap();
+        let targets = |state: &State| state.moves().from(Square::C3).map(|mv| mv.target()).collect::<SquareSet>();
+        assert_eq!(targets(&queen), targets(&rook) | targets(&bishop));
+        assert_eq!(targets(&queen).len(), 7 + 10);
+    }
+
+    // --- King --- //
+
+    #[test]
+    fn castling_is_refused_through_an_attacked_square() {
+        let castles = |fen: &str| -> Vec<String> {
+            let state = State::from_fen(fen).unwrap();
+            let mut moves: Vec<String> = state.moves().all()
+                .filter(|mv| mv.move_type() == MoveType::Castling)
+                .map(|mv| mv.to_uci(false))
+                .collect();
+            moves.sort();
+            moves
+        };
+        assert_eq!(castles("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"), ["e1c1", "e1g1"]);
+        // The rook on f8 sweeps f1, which the king crosses; b1 is crossed only by the rook
+        assert_eq!(castles("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1"), ["e1c1"]);
+        assert_eq!(castles("1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"), ["e1c1", "e1g1"]);
+        // Not out of check, and not without the right
+        assert!(castles("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").is_empty());
+        assert_eq!(castles("4k3/8/8/8/8/8/8/R3K2R w K - 0 1"), ["e1g1"]);
+    }
+
+    #[test]
+    fn kings_do_not_step_onto_attacked_or_friendly_squares() {
+        let fen = "3rk3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        assert_eq!(moves_from(fen, Square::E1), ["e1f1", "e1f2"]);
+    }
+
+    // --- Attack Detection --- //
+
+    /// White's king on h1 and Black's on a8, with White to move, plus the given pieces.
+    fn kings_and(pieces: &[(PieceType, Color, &str)]) -> State {
+        let builder = StateBuilder::new().piece(PieceType::King, Color::White, "h1").piece(PieceType::King, Color::Black, "a8");
+        let builder = pieces.iter().fold(builder, |builder, &(piece_type, color, square)| builder.piece(piece_type, color, square));
+        builder.build().unwrap()
+    }
+
+    fn black_attacks(pieces: &[(PieceType, Color, &str)], square: Square) -> bool {
+        is_square_attacked(&kings_and(pieces).board, square, Color::Black)
+    }
+
+    #[test]
+    fn each_piece_type_attacks_its_own_pattern() {
+        use PieceType::*;
+        let on_d5 = |piece_type| [(piece_type, Color::Black, "d5")];
+        assert!(black_attacks(&on_d5(Knight), Square::C3) && !black_attacks(&on_d5(Knight), Square::D3));
+        // Bishop and rook each along their own lines only, the queen along both
+        assert!(black_attacks(&on_d5(Bishop), Square::G2) && !black_attacks(&on_d5(Bishop), Square::D2));
+        assert!(black_attacks(&on_d5(Rook), Square::D2) && !black_attacks(&on_d5(Rook), Square::G2));
+        assert!(black_attacks(&on_d5(Queen), Square::G2) && black_attacks(&on_d5(Queen), Square::D2));
+        // A black pawn attacks downward only, and never straight ahead
+        assert!(black_attacks(&on_d5(Pawn), Square::E4) && black_attacks(&on_d5(Pawn), Square::C4));
+        assert!(!black_attacks(&on_d5(Pawn), Square::E6) && !black_attacks(&on_d5(Pawn), Square::D4));
+        // The king reaches one square
+        assert!(black_attacks(&[], Square::B7) && !black_attacks(&[], Square::C6));
+    }
+
+    #[test]
+    fn sliders_are_stopped_by_the_first_blocker_of_either_color() {
+        let rook = (PieceType::Rook, Color::Black, "d5");
+        assert!(!black_attacks(&[rook, (PieceType::Pawn, Color::White, "d3")], Square::D2));
+        assert!(!black_attacks(&[rook, (PieceType::Knight, Color::Black, "d3")], Square::D2));
+        // The blocker itself is attacked
+        assert!(black_attacks(&[rook, (PieceType::Pawn, Color::White, "d3")], Square::D3));
+    }
+
+    #[test]
+    fn pinned_pieces_stay_on_the_pinning_line() {
+        // The h-file rook pins the knight outright and the rook along the file
+        let rook = (PieceType::Rook, Color::Black, "h8");
+        let state = kings_and(&[rook, (PieceType::Knight, Color::White, "h3")]);
+        assert_eq!(pinned_pieces(&state.board, Color::White), SquareSet::from_square(Square::H3));
+        assert!(state.moves().from(Square::H3).next().is_none());
+        let state = kings_and(&[rook, (PieceType::Rook, Color::White, "h3")]);
+        let targets: SquareSet = state.moves().from(Square::H3).map(|mv| mv.target()).collect();
+        assert_eq!(targets, SquareSet::from_squares(&[Square::H2, Square::H4, Square::H5, Square::H6, Square::H7, Square::H8]));
+        // With nothing between, the rook attacks the king: check, not a pin
+        let state = kings_and(&[rook]);
+        assert!(state.in_check() && pinned_pieces(&state.board, Color::White).is_empty());
+    }
+
+    // --- Partitions --- //
+
+    #[test]
+    fn captures_and_quiets_partition_all_moves() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            // In check, where evasions are generated instead
+            "4k3/8/8/8/1b6/8/N7/R3K3 w Q - 0 1",
+        ];
+        for fen in fens {
+            let state = State::from_fen(fen).unwrap();
+            let sorted = |mut moves: Vec<Move>| { moves.sort_by_key(|mv| mv.to_uci(false)); moves };
+            let captures: Vec<Move> = state.moves().captures().collect();
+            let quiets: Vec<Move> = state.moves().quiets().collect();
+            assert!(captures.iter().all(|&mv| mv.move_type() == MoveType::EnPassant || state.board[mv.target()].is_some()), "{}", fen);
+            assert!(quiets.iter().all(|&mv| mv.move_type() == MoveType::Castling || state.board[mv.target()].is_none()), "{}", fen);
+            assert_eq!(sorted([captures, quiets].concat()), sorted(state.moves().all().collect()), "{}", fen);
+        }
+        assert!(State::from_fen(fens[4]).unwrap().in_check());
+    }
 }
@@ -1,14 +1,20 @@
 //! Chess move representation. "move" is a reserved keyword in Rust, so we use "mv".
 
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
 use crate::board::{Color, Piece, PieceType, Square};
 use crate::castling::CastlingSide;
+use crate::display::{debug_move, render_move};
+use crate::notation::{format_san, parse_san, MoveFormat, SanError};
+use crate::state::State;
 
 // ============================================================================
 // Type Definitions
 // ============================================================================
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum MoveType {
     Normal = 0,
     Promotion = 1,
@@ -20,6 +26,37 @@ pub enum MoveType {
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Move(u16);
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveParseError {
+    /// Not four coordinate characters plus an optional promotion letter.
+    Syntax(String),
+    /// The promotion letter is not one of `n`, `b`, `r`, `q`.
+    BadPromotion(char),
+    /// A pawn reaches the last rank without a promotion letter, or a non-pawn carries one.
+    PromotionMismatch(String),
+    /// There is no piece of the side to move on the source square.
+    NoPieceOnSource(String),
+}
+
+/// Why a move is not legal in a position, most basic reason first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IllegalMoveError {
+    /// The source square is empty.
+    NoPieceOnSource,
+    /// The piece on the source square belongs to the side not to move.
+    WrongColor,
+    /// The piece cannot move that way, or the move's encoding does not match the move.
+    CannotReach,
+    /// A promotion by a non-pawn or short of the last rank, or a pawn reaching it without one.
+    BadPromotion,
+    /// En passant by a non-pawn, or onto a square other than the en passant square.
+    BadEnPassant,
+    /// Castling without the right, with the way blocked, or out of, through, or into check.
+    BadCastling,
+    /// The move would leave the mover's own king in check.
+    LeavesKingInCheck,
+}
+
 // ============================================================================
 // MoveType
 // ============================================================================
@@ -69,6 +106,8 @@ impl Move {
         )
     }
 
+    /// Castling is encoded as the king capturing its own rook, so `target` is the rook's home
+    /// square. This keeps Chess960 castling unambiguous.
     pub const fn castling(source: Square, target: Square) -> Self {
         Self(
             (source.value() as u16)
@@ -83,38 +122,159 @@ impl Move {
     }
 
     pub const fn target(self) -> Square {
-        Square::from_index(((self.0 >> 6) & 0b111111) as usize)
+        Square::from_index(((self.0 & Self::TARGET_MASK) >> 6) as usize)
     }
 
     pub const fn move_type(self) -> MoveType {
-        MoveType::from_u8(((self.0 >> 14) & 0b11) as u8)
+        MoveType::from_u8(((self.0 & Self::TYPE_MASK) >> 14) as u8)
     }
 
-    pub const fn promoted_piece(self, color: Color) -> Piece {
+    pub const fn promoted_type(self) -> PieceType {
         match self.move_type() {
-            MoveType::Promotion => {
-                let piece_type = PieceType::from_u8(((self.0 >> 12) & 0b11) as u8);
-                Piece::new(piece_type, color)
-            }
+            MoveType::Promotion => PieceType::from_u8(((self.0 & Self::PROMO_MASK) >> 12) as u8),
             _ => unreachable!()
         }
     }
 
+    pub const fn promoted_piece(self, color: Color) -> Piece {
+        Piece::new(self.promoted_type(), color)
+    }
+
     // --- Derived (for special move types) --- //
     pub const fn castling_side(self) -> CastlingSide {
         if self.target().file() > self.source().file() { CastlingSide::Kingside } else { CastlingSide::Queenside }
     }
 
     pub const fn castling_rook_squares(self) -> (Square, Square) {
-        let side = self.castling_side();
         let rank = self.source().rank();
-        (
-            Square::from_coords(rank, side.rook_source_file()),
-            Square::from_coords(rank, side.rook_target_file()),
-        )
+        (self.target(), Square::from_coords(rank, self.castling_side().rook_target_file()))
+    }
+
+    /// Where the king ends up after castling.
+    pub const fn castling_king_target(self) -> Square {
+        Square::from_coords(self.source().rank(), self.castling_side().king_target_file())
+    }
+
+    /// The square the moving piece lands on: the target, or the king's castled square.
+    pub const fn destination(self) -> Square {
+        match self.move_type() {
+            MoveType::Castling => self.castling_king_target(),
+            _ => self.target(),
+        }
     }
 
     pub const fn en_passant_capture(self) -> Square {
         Square::from_coords(self.source().rank(), self.target().file())
     }
+
+    // --- Raw Encoding --- //
+    pub const fn bits(self) -> u16 { self.0 }
+
+    /// Any sixteen bits are a `Move`, though not necessarily a legal one anywhere; check with
+    /// `State::is_legal_move` before playing it.
+    pub const fn from_bits(bits: u16) -> Self { Self(bits) }
+}
+
+// --- Parsing --- //
+impl Move {
+    /// Parse long algebraic notation (`e2e4`, `e7e8q`, `e1g1`), resolving the move type from
+    /// the position. Castling may also be given as the king taking its own rook, as UCI does
+    /// for Chess960. Legality is not checked.
+    pub fn from_uci(text: &str, state: &State) -> Result<Self, MoveParseError> {
+        let syntax = || MoveParseError::Syntax(text.to_string());
+        if !text.is_ascii() || !(4..=5).contains(&text.len()) { return Err(syntax()); }
+
+        let source = Square::from_algebraic(&text[0..2]).ok_or_else(syntax)?;
+        let target = Square::from_algebraic(&text[2..4]).ok_or_else(syntax)?;
+        let promotion = match text[4..].chars().next() {
+            None => None,
+            Some(c) => Some(PieceType::from_promotion_char(c).ok_or(MoveParseError::BadPromotion(c))?),
+        };
+
+        let piece = match state.board[source] {
+            Some(piece) if piece.color() == state.to_move => piece,
+            _ => return Err(MoveParseError::NoPieceOnSource(text.to_string())),
+        };
+
+        let reaches_last_rank = piece.is_pawn() && target.rank() == (!state.to_move).home_rank();
+        match promotion {
+            Some(_) if !reaches_last_rank => Err(MoveParseError::PromotionMismatch(text.to_string())),
+            None if reaches_last_rank => Err(MoveParseError::PromotionMismatch(text.to_string())),
+            Some(piece_type) => Ok(Move::promotion(source, target, piece_type)),
+            None if piece.is_pawn() && state.en_passant == Some(target) => Ok(Move::en_passant(source, target)),
+            None if piece.is_king() && state.board[target] == Some(Piece::new(PieceType::Rook, state.to_move)) => {
+                Ok(Move::castling(source, target))
+            }
+            None if piece.is_king() && source.file() == CastlingSide::KING_FILE && (target - source).1.abs() == 2 => {
+                let side = CastlingSide::of_rook(target.file(), source.file());
+                Ok(Move::castling(source, state.castling_rights.rook_square(state.to_move, side)))
+            }
+            None => Ok(Move::new(source, target)),
+        }
+    }
+}
+
+// --- Notation --- //
+impl Move {
+    /// Long algebraic notation as UCI sends it. With `chess960` castling is written as the king
+    /// taking its own rook, otherwise as the king's move.
+    pub fn to_uci(self, chess960: bool) -> String {
+        match self.move_type() {
+            MoveType::Castling if chess960 => format!("{}{}", self.source(), self.target()),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Standard Algebraic Notation for this move, which must be legal in `state`.
+    pub fn to_san(self, state: &State) -> String {
+        format_san(self, state)
+    }
+
+    /// This move, legal in `state`, ready to display in SAN, long algebraic, or figurine style.
+    pub fn format(self, state: &State) -> MoveFormat<'_> {
+        MoveFormat::new(self, state)
+    }
+
+    /// Resolve a SAN token such as `Nbd2`, `exd5`, `e8=Q+` or `O-O` in `state`.
+    pub fn from_san(text: &str, state: &State) -> Result<Self, SanError> {
+        parse_san(text, state)
+    }
 }
+
+// --- Traits --- //
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { render_move(self, f) }
+}
+
+impl Debug for Move {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult { debug_move(self, f) }
+}
+
+impl Display for MoveParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            MoveParseError::Syntax(s)            => write!(f, "malformed move '{}'", s),
+            MoveParseError::BadPromotion(c)      => write!(f, "invalid promotion piece '{}'", c),
+            MoveParseError::PromotionMismatch(s) => write!(f, "promotion suffix does not fit move '{}'", s),
+            MoveParseError::NoPieceOnSource(s)   => write!(f, "no piece of the side to move on the source of '{}'", s),
+        }
+    }
+}
+
+impl Error for MoveParseError {}
+
+impl Display for IllegalMoveError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            IllegalMoveError::NoPieceOnSource   => write!(f, "no piece on the source square"),
+            IllegalMoveError::WrongColor        => write!(f, "the piece on the source square is not the side to move's"),
+            IllegalMoveError::CannotReach       => write!(f, "the piece cannot move that way"),
+            IllegalMoveError::BadPromotion      => write!(f, "invalid promotion"),
+            IllegalMoveError::BadEnPassant      => write!(f, "en passant is not possible"),
+            IllegalMoveError::BadCastling       => write!(f, "castling is not possible"),
+            IllegalMoveError::LeavesKingInCheck => write!(f, "the move leaves the king in check"),
+        }
+    }
+}
+
+impl Error for IllegalMoveError {}
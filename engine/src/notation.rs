@@ -0,0 +1,314 @@
+//! Standard Algebraic Notation (SAN), and the long algebraic and figurine styles built on it.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::board::{Color, Piece, PieceType, Square};
+use crate::castling::CastlingSide;
+use crate::mv::{Move, MoveType};
+use crate::state::State;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SanError {
+    /// The text is not shaped like a SAN move.
+    Syntax(String),
+    /// No legal move in the position matches.
+    Illegal(String),
+    /// More than one legal move matches; more disambiguation is needed.
+    Ambiguous(String),
+}
+
+/// The notations a move can be written in for people; UCI keeps to coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MoveStyle {
+    /// Standard Algebraic Notation: `Nf3`, `exd6`, `e8=Q+`.
+    #[default]
+    San,
+    /// Long algebraic notation, always naming the source square: `Ng1-f3`, `e5xd6`, `e7-e8=Q+`.
+    Lan,
+    /// SAN with the mover's piece figurines in place of letters: `♘f3`, `e8=♕+`.
+    Figurine,
+}
+
+/// A legal move displayed in a chosen style against the position it is played in. SAN unless
+/// told otherwise; made by `Move::format`.
+#[derive(Copy, Clone, Debug)]
+pub struct MoveFormat<'a> {
+    mv: Move,
+    state: &'a State,
+    style: MoveStyle,
+}
+
+/// Moves played in turn from `start`, displayed as numbered movetext: `1. e4 e5 2. Nf3`,
+/// opening with `N...` when Black moves first.
+#[derive(Copy, Clone, Debug)]
+pub struct MoveList<'a> {
+    start: &'a State,
+    moves: &'a [Move],
+    style: MoveStyle,
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Render a legal move in SAN, including disambiguation and check/mate suffixes.
+pub fn format_san(mv: Move, state: &State) -> String {
+    format_move(mv, state, MoveStyle::San)
+}
+
+/// Render a legal move in any style, with check and mate suffixes. Castling is `O-O` or
+/// `O-O-O` in all of them.
+pub fn format_move(mv: Move, state: &State, style: MoveStyle) -> String {
+    let mut san = match mv.move_type() {
+        MoveType::Castling => match mv.castling_side() {
+            CastlingSide::Kingside => "O-O".to_string(),
+            CastlingSide::Queenside => "O-O-O".to_string(),
+        },
+        _ => format_ordinary(mv, state, style),
+    };
+
+    let next = state.clone().apply_move(mv);
+    if next.in_check() {
+        san.push(if !next.moves().any() { '#' } else { '+' });
+    }
+    san
+}
+
+fn format_ordinary(mv: Move, state: &State, style: MoveStyle) -> String {
+    let piece_type = state.board[mv.source()].expect("move source must be occupied").piece_type();
+    let is_capture = state.board[mv.target()].is_some() || mv.move_type() == MoveType::EnPassant;
+    let mut san = String::new();
+
+    if piece_type != PieceType::Pawn { san.push(styled_letter(piece_type, state.to_move, style)); }
+    if style == MoveStyle::Lan {
+        san.push_str(&mv.source().to_string());
+    } else if piece_type != PieceType::Pawn {
+        san.push_str(&disambiguation(mv, piece_type, state));
+    } else if is_capture {
+        san.push(file_char(mv.source().file()));
+    }
+
+    if is_capture { san.push('x'); } else if style == MoveStyle::Lan { san.push('-'); }
+    san.push_str(&mv.target().to_string());
+
+    if mv.move_type() == MoveType::Promotion {
+        san.push('=');
+        san.push(styled_letter(mv.promoted_type(), state.to_move, style));
+    }
+    san
+}
+
+/// The letter for a piece, or its figurine for the side that moves it.
+fn styled_letter(piece_type: PieceType, color: Color, style: MoveStyle) -> char {
+    match style {
+        MoveStyle::Figurine => Piece::new(piece_type, color).to_string().chars().next().expect("one figurine"),
+        _ => piece_letter(piece_type),
+    }
+}
+
+/// File, rank, or both of the source square, as needed to tell identical pieces apart.
+fn disambiguation(mv: Move, piece_type: PieceType, state: &State) -> String {
+    let (source, target) = (mv.source(), mv.target());
+    let rivals: Vec<_> = state.moves().all()
+        .filter(|other| other.target() == target && other.source() != source)
+        .filter(|other| state.board[other.source()].is_some_and(|p| p.piece_type() == piece_type))
+        .map(|other| other.source())
+        .collect();
+
+    if rivals.is_empty() { return String::new(); }
+    if rivals.iter().all(|sq| sq.file() != source.file()) { return file_char(source.file()).to_string(); }
+    if rivals.iter().all(|sq| sq.rank() != source.rank()) { return rank_char(source.rank()).to_string(); }
+    source.to_string()
+}
+
+// ============================================================================
+// Styled Display
+// ============================================================================
+
+impl<'a> MoveFormat<'a> {
+    pub(crate) fn new(mv: Move, state: &'a State) -> Self {
+        MoveFormat { mv, state, style: MoveStyle::San }
+    }
+
+    pub fn style(mut self, style: MoveStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn san(self) -> Self { self.style(MoveStyle::San) }
+    pub fn lan(self) -> Self { self.style(MoveStyle::Lan) }
+    pub fn figurine(self) -> Self { self.style(MoveStyle::Figurine) }
+}
+
+impl<'a> MoveList<'a> {
+    /// The moves must be legal one after another from `start`. Written in SAN unless styled.
+    pub fn new(start: &'a State, moves: &'a [Move]) -> Self {
+        MoveList { start, moves, style: MoveStyle::San }
+    }
+
+    pub fn style(mut self, style: MoveStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Display for MoveFormat<'_> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", format_move(self.mv, self.state, self.style))
+    }
+}
+
+impl Display for MoveList<'_> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let mut state = self.start.clone();
+        for (i, &mv) in self.moves.iter().enumerate() {
+            if i > 0 { write!(f, " ")?; }
+            match state.to_move {
+                Color::White => write!(f, "{}. ", state.fullmove_number)?,
+                Color::Black if i == 0 => write!(f, "{}... ", state.fullmove_number)?,
+                Color::Black => {}
+            }
+            write!(f, "{}", format_move(mv, &state, self.style))?;
+            state = state.apply_move(mv);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Resolve a SAN token against the legal moves of the position. Trailing `+`, `#`, `!` and `?`
+/// are ignored, and castling may be written with zeros.
+pub fn parse_san(text: &str, state: &State) -> Result<Move, SanError> {
+    let san = text.trim_end_matches(['+', '#', '!', '?']);
+
+    let castling = match san {
+        "O-O" | "0-0" => Some(CastlingSide::Kingside),
+        "O-O-O" | "0-0-0" => Some(CastlingSide::Queenside),
+        _ => None,
+    };
+    if let Some(side) = castling {
+        return unique(text, state.moves().all()
+            .filter(|mv| mv.move_type() == MoveType::Castling && mv.castling_side() == side));
+    }
+
+    let pattern = SanPattern::parse(san).ok_or_else(|| SanError::Syntax(text.to_string()))?;
+    unique(text, state.moves().all().filter(|&mv| pattern.matches(mv, state)))
+}
+
+/// The pieces of an ordinary SAN move: `[piece][file][rank][x]<target>[=promotion]`.
+struct SanPattern {
+    piece_type: PieceType,
+    from_file: Option<u8>,
+    from_rank: Option<u8>,
+    target: Square,
+    promotion: Option<PieceType>,
+}
+
+impl SanPattern {
+    fn parse(san: &str) -> Option<Self> {
+        let mut chars: Vec<char> = san.chars().collect();
+
+        let promotion = match chars.as_slice() {
+            [.., '=', c] | [.., '1'..='8', c @ ('N' | 'B' | 'R' | 'Q')] => {
+                let piece_type = piece_from_letter(*c).filter(|&p| p != PieceType::King)?;
+                chars.truncate(chars.len() - if chars[chars.len() - 2] == '=' { 2 } else { 1 });
+                Some(piece_type)
+            }
+            _ => None,
+        };
+
+        let piece_type = match chars.first() {
+            Some(&c) if c.is_ascii_uppercase() => { chars.remove(0); piece_from_letter(c)? }
+            _ => PieceType::Pawn,
+        };
+
+        if chars.len() < 2 { return None; }
+        let target_text: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let target = Square::from_algebraic(&target_text)?;
+
+        if chars.last() == Some(&'x') { chars.pop(); }
+        let (mut from_file, mut from_rank) = (None, None);
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => from_file = Some(c as u8 - b'a'),
+                '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+                _ => return None,
+            }
+        }
+
+        Some(SanPattern { piece_type, from_file, from_rank, target, promotion })
+    }
+
+    fn matches(&self, mv: Move, state: &State) -> bool {
+        let source = mv.source();
+        let promotion = match mv.move_type() {
+            MoveType::Promotion => Some(mv.promoted_type()),
+            _ => None,
+        };
+
+        mv.move_type() != MoveType::Castling
+            && mv.target() == self.target
+            && state.board[source].is_some_and(|p| p.piece_type() == self.piece_type)
+            && self.from_file.is_none_or(|file| file == source.file())
+            && self.from_rank.is_none_or(|rank| rank == source.rank())
+            && promotion == self.promotion
+    }
+}
+
+fn unique(text: &str, mut candidates: impl Iterator<Item = Move>) -> Result<Move, SanError> {
+    let first = candidates.next().ok_or_else(|| SanError::Illegal(text.to_string()))?;
+    match candidates.next() {
+        None => Ok(first),
+        Some(_) => Err(SanError::Ambiguous(text.to_string())),
+    }
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Pawn   => 'P',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Rook   => 'R',
+        PieceType::Queen  => 'Q',
+        PieceType::King   => 'K',
+    }
+}
+
+fn piece_from_letter(letter: char) -> Option<PieceType> {
+    match letter {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+fn file_char(file: u8) -> char { (b'a' + file) as char }
+fn rank_char(rank: u8) -> char { (b'1' + rank) as char }
+
+// --- Traits --- //
+impl Display for SanError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            SanError::Syntax(s)    => write!(f, "malformed SAN move '{}'", s),
+            SanError::Illegal(s)   => write!(f, "no legal move matches '{}'", s),
+            SanError::Ambiguous(s) => write!(f, "ambiguous SAN move '{}'", s),
+        }
+    }
+}
+
+impl Error for SanError {}
@@ -0,0 +1,331 @@
+//! Engine options as UCI `setoption` configures them: a registry of named, typed options, each
+//! with an optional callback that applies a change to the search.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+#[cfg(feature = "syzygy")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::book::{MAX_BOOK_DEPTH, MAX_BOOK_VARIETY};
+use crate::search::Search;
+use crate::skill::{Strength, MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+#[cfg(feature = "syzygy")]
+use crate::tablebase::Tablebase;
+use crate::time::DEFAULT_MOVE_OVERHEAD;
+use crate::tt::DEFAULT_MEGABYTES;
+
+/// Largest transposition table the `Hash` option accepts, in megabytes.
+pub const MAX_HASH_MEGABYTES: i64 = 4096;
+
+/// Most search threads the `Threads` option accepts.
+pub const MAX_THREADS: i64 = 256;
+
+/// Longest `Move Overhead`, in milliseconds.
+const MAX_MOVE_OVERHEAD: i64 = 5_000;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// The kinds of option UCI knows, with their defaults and limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    /// An integer; values outside the range are clamped into it.
+    Spin { min: i64, max: i64, default: i64 },
+    Check { default: bool },
+    /// Free text; UCI writes the empty string as `<empty>`.
+    String { default: String },
+    /// One of a fixed set of words, matched without regard to case.
+    Combo { default: String, choices: Vec<String> },
+    /// An action with no value, such as clearing the hash table.
+    Button,
+}
+
+/// The current value of an option. Combo values are held as strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionValue {
+    Spin(i64),
+    Check(bool),
+    String(String),
+    /// What a button's callback receives when it is pressed.
+    Button,
+}
+
+/// Applies a changed value to the search, as resizing the hash table does for `Hash`.
+pub type OnChange = fn(&mut Search, &OptionValue);
+
+/// Types an option's value can be read back as through `Options::get`.
+pub trait OptionType: Sized {
+    fn from_value(value: &OptionValue) -> Option<Self>;
+}
+
+#[derive(Clone, Debug)]
+struct EngineOption {
+    name: String,
+    kind: OptionKind,
+    value: OptionValue,
+    on_change: Option<OnChange>,
+}
+
+/// The options an engine advertises, in the order they were registered.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    entries: Vec<EngineOption>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionError {
+    /// No option has this name.
+    Unknown(String),
+    /// The value does not fit the option: not a number for a spin, not `true` or `false` for
+    /// a check, or not one of a combo's choices.
+    BadValue { name: String, value: String },
+}
+
+// ============================================================================
+// Options — Registration
+// ============================================================================
+
+impl Options {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The engine's own options, each at its default: `Hash`, `Threads`, `MultiPV`, `Ponder`,
+    /// `OwnBook`, `BookFile`, `Book Variety`, `Book Depth`, `UCI_Chess960`, `Skill Level`, `UCI_LimitStrength`, `UCI_Elo`,
+    /// `SyzygyPath` with the `syzygy` feature, `Move Overhead`, and the `Clear Hash` button. `MultiPV` goes no higher than one, as the
+    /// search reports a single line.
+    pub fn builtin() -> Self {
+        let mut options = Options::new();
+        options
+            .register("Hash", OptionKind::Spin { min: 1, max: MAX_HASH_MEGABYTES, default: DEFAULT_MEGABYTES as i64 }, Some(set_hash))
+            .register("Threads", OptionKind::Spin { min: 1, max: MAX_THREADS, default: 1 }, Some(set_threads))
+            .register("MultiPV", OptionKind::Spin { min: 1, max: 1, default: 1 }, None)
+            .register("Ponder", OptionKind::Check { default: false }, None)
+            .register("OwnBook", OptionKind::Check { default: false }, None)
+            .register("BookFile", OptionKind::String { default: String::new() }, None)
+            .register("Book Variety", OptionKind::Spin { min: 0, max: MAX_BOOK_VARIETY as i64, default: 0 }, None)
+            .register("Book Depth", OptionKind::Spin { min: 0, max: MAX_BOOK_DEPTH as i64, default: MAX_BOOK_DEPTH as i64 }, None)
+            .register("UCI_Chess960", OptionKind::Check { default: false }, None)
+            .register("Skill Level", OptionKind::Spin { min: 0, max: MAX_SKILL_LEVEL as i64, default: MAX_SKILL_LEVEL as i64 }, Some(set_skill_level))
+            .register("UCI_LimitStrength", OptionKind::Check { default: false }, Some(set_limit_strength))
+            .register("UCI_Elo", OptionKind::Spin { min: MIN_ELO as i64, max: MAX_ELO as i64, default: MAX_ELO as i64 }, Some(set_elo));
+        #[cfg(feature = "syzygy")]
+        options.register("SyzygyPath", OptionKind::String { default: String::new() }, Some(set_syzygy_path));
+        let overhead = DEFAULT_MOVE_OVERHEAD.as_millis() as i64;
+        options
+            .register("Move Overhead", OptionKind::Spin { min: 0, max: MAX_MOVE_OVERHEAD, default: overhead }, Some(set_move_overhead))
+            .register("Clear Hash", OptionKind::Button, Some(clear_hash));
+        options
+    }
+
+    /// Add an option at its default, replacing any of the same name.
+    pub fn register(&mut self, name: &str, kind: OptionKind, on_change: Option<OnChange>) -> &mut Self {
+        let value = match &kind {
+            OptionKind::Spin { default, .. } => OptionValue::Spin(*default),
+            OptionKind::Check { default } => OptionValue::Check(*default),
+            OptionKind::String { default } | OptionKind::Combo { default, .. } => OptionValue::String(default.clone()),
+            OptionKind::Button => OptionValue::Button,
+        };
+        let option = EngineOption { name: name.to_string(), kind, value, on_change };
+        match self.entries.iter_mut().find(|entry| entry.name.eq_ignore_ascii_case(name)) {
+            Some(entry) => *entry = option,
+            None => self.entries.push(option),
+        }
+        self
+    }
+}
+
+// ============================================================================
+// Options — Access
+// ============================================================================
+
+impl Options {
+    /// The value of an option, if it exists and holds a `T`. Names match without regard to
+    /// case, as UCI asks.
+    pub fn get<T: OptionType>(&self, name: &str) -> Option<T> {
+        T::from_value(&self.entry(name)?.value)
+    }
+
+    /// Set an option from the text of a `setoption` value, then hand the new value to its
+    /// callback. Spins are clamped into range; a button ignores the text and fires.
+    pub fn set(&mut self, name: &str, text: &str, search: &mut Search) -> Result<(), OptionError> {
+        let entry = self.entries.iter_mut()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| OptionError::Unknown(name.to_string()))?;
+        let text = text.trim();
+        let bad_value = || OptionError::BadValue { name: entry.name.clone(), value: text.to_string() };
+
+        let value = match &entry.kind {
+            OptionKind::Spin { min, max, .. } => OptionValue::Spin(text.parse::<i64>().map_err(|_| bad_value())?.clamp(*min, *max)),
+            OptionKind::Check { .. } if text.eq_ignore_ascii_case("true") => OptionValue::Check(true),
+            OptionKind::Check { .. } if text.eq_ignore_ascii_case("false") => OptionValue::Check(false),
+            OptionKind::Check { .. } => return Err(bad_value()),
+            OptionKind::String { .. } if text == "<empty>" => OptionValue::String(String::new()),
+            OptionKind::String { .. } => OptionValue::String(text.to_string()),
+            OptionKind::Combo { choices, .. } => match choices.iter().find(|choice| choice.eq_ignore_ascii_case(text)) {
+                Some(choice) => OptionValue::String(choice.clone()),
+                None => return Err(bad_value()),
+            },
+            OptionKind::Button => OptionValue::Button,
+        };
+        entry.value = value;
+        if let Some(on_change) = entry.on_change { on_change(search, &entry.value); }
+        Ok(())
+    }
+
+    /// One `option name <name> type <kind> ...` line per option, for the reply to `uci`.
+    pub fn uci_lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.entries.iter().map(|entry| {
+            let name = &entry.name;
+            match &entry.kind {
+                OptionKind::Spin { min, max, default } => {
+                    format!("option name {} type spin default {} min {} max {}", name, default, min, max)
+                }
+                OptionKind::Check { default } => format!("option name {} type check default {}", name, default),
+                OptionKind::String { default } => {
+                    let default = if default.is_empty() { "<empty>" } else { default };
+                    format!("option name {} type string default {}", name, default)
+                }
+                OptionKind::Combo { default, choices } => {
+                    let choices: Vec<String> = choices.iter().map(|choice| format!(" var {}", choice)).collect();
+                    format!("option name {} type combo default {}{}", name, default, choices.concat())
+                }
+                OptionKind::Button => format!("option name {} type button", name),
+            }
+        })
+    }
+
+    fn entry(&self, name: &str) -> Option<&EngineOption> {
+        self.entries.iter().find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl OptionType for i64 {
+    fn from_value(value: &OptionValue) -> Option<Self> {
+        match value { OptionValue::Spin(n) => Some(*n), _ => None }
+    }
+}
+
+impl OptionType for bool {
+    fn from_value(value: &OptionValue) -> Option<Self> {
+        match value { OptionValue::Check(b) => Some(*b), _ => None }
+    }
+}
+
+impl OptionType for String {
+    fn from_value(value: &OptionValue) -> Option<Self> {
+        match value { OptionValue::String(s) => Some(s.clone()), _ => None }
+    }
+}
+
+// ============================================================================
+// Built-in Callbacks
+// ============================================================================
+
+fn set_hash(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Spin(megabytes) = value { search.set_hash_size(*megabytes as usize); }
+}
+
+fn set_threads(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Spin(threads) = value { search.set_threads(*threads as usize); }
+}
+
+fn set_move_overhead(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Spin(ms) = value { search.set_move_overhead(Duration::from_millis(*ms as u64)); }
+}
+
+fn set_skill_level(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Spin(level) = value { search.set_strength(Strength { skill_level: *level as u32, ..search.strength() }); }
+}
+
+fn set_limit_strength(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Check(on) = value { search.set_strength(Strength { limit_strength: *on, ..search.strength() }); }
+}
+
+fn set_elo(search: &mut Search, value: &OptionValue) {
+    if let OptionValue::Spin(elo) = value { search.set_strength(Strength { elo: *elo as u32, ..search.strength() }); }
+}
+
+fn clear_hash(search: &mut Search, _value: &OptionValue) {
+    search.clear_hash();
+}
+
+/// Directories separated as in `PATH`; none, or a path that cannot be read, disables probing.
+#[cfg(feature = "syzygy")]
+fn set_syzygy_path(search: &mut Search, value: &OptionValue) {
+    let OptionValue::String(paths) = value else { return };
+    let tablebase = if paths.is_empty() { None } else {
+        Tablebase::new(std::env::split_paths(paths)).ok().filter(|tablebase| tablebase.max_pieces() > 0)
+    };
+    search.set_tablebase(tablebase.map(Arc::new));
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for OptionError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            OptionError::Unknown(name)             => write!(f, "unknown option '{}'", name),
+            OptionError::BadValue { name, value }  => write!(f, "invalid value '{}' for option {}", value, name),
+        }
+    }
+}
+
+impl Error for OptionError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spins_are_clamped_into_range() {
+        let (mut options, mut search) = (Options::builtin(), Search::new());
+        options.set("Threads", "1000", &mut search).unwrap();
+        assert_eq!(options.get::<i64>("Threads"), Some(MAX_THREADS));
+        options.set("Threads", "-3", &mut search).unwrap();
+        assert_eq!(options.get::<i64>("Threads"), Some(1));
+        options.set("Hash", "0", &mut search).unwrap();
+        assert_eq!(options.get::<i64>("Hash"), Some(1));
+        options.set("Move Overhead", "75", &mut search).unwrap();
+        assert_eq!(options.get::<i64>("Move Overhead"), Some(75));
+    }
+
+    #[test]
+    fn unknown_options_and_bad_values_are_errors() {
+        let (mut options, mut search) = (Options::builtin(), Search::new());
+        assert_eq!(options.set("No Such Option", "1", &mut search), Err(OptionError::Unknown("No Such Option".to_string())));
+        assert!(matches!(options.set("Threads", "many", &mut search), Err(OptionError::BadValue { .. })));
+        assert!(matches!(options.set("Ponder", "yes", &mut search), Err(OptionError::BadValue { .. })));
+        assert_eq!(options.get::<i64>("Threads"), Some(1));
+        assert_eq!(options.get::<bool>("Ponder"), Some(false));
+    }
+
+    #[test]
+    fn names_match_without_case_and_values_read_back_typed() {
+        let (mut options, mut search) = (Options::builtin(), Search::new());
+        options.set("uci_chess960", "TRUE", &mut search).unwrap();
+        options.set("bookfile", "<empty>", &mut search).unwrap();
+        assert_eq!(options.get::<bool>("UCI_Chess960"), Some(true));
+        assert_eq!(options.get::<String>("BookFile"), Some(String::new()));
+        assert_eq!(options.get::<i64>("UCI_Chess960"), None);
+    }
+
+    #[test]
+    fn combos_accept_only_their_choices() {
+        let (mut options, mut search) = (Options::new(), Search::new());
+        let choices = vec!["Solid".to_string(), "Risky".to_string()];
+        options.register("Style", OptionKind::Combo { default: "Solid".to_string(), choices }, None);
+        options.set("Style", "risky", &mut search).unwrap();
+        assert_eq!(options.get::<String>("Style"), Some("Risky".to_string()));
+        assert!(options.set("Style", "Wild", &mut search).is_err());
+        assert_eq!(options.uci_lines().next().unwrap(), "option name Style type combo default Solid var Solid var Risky");
+    }
+}
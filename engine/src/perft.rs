@@ -0,0 +1,98 @@
+//! Perft: exhaustive move-path counting for validating move generation.
+
+use crate::mv::Move;
+use crate::state::State;
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Count the leaf nodes of the legal move tree to the given depth.
+pub fn perft(state: &State, depth: u32) -> u64 {
+    walk(&mut state.clone(), depth)
+}
+
+/// Per-root-move leaf counts, for diffing against a reference engine's `divide` output.
+pub fn perft_divide(state: &State, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 { return Vec::new(); }
+    let mut state = state.clone();
+    let moves: Vec<Move> = state.moves().all().collect();
+    moves.into_iter()
+        .map(|mv| {
+            let undo = state.make_move(mv);
+            let nodes = walk(&mut state, depth - 1);
+            state.unmake_move(undo);
+            (mv, nodes)
+        })
+        .collect()
+}
+
+/// The tree below `state`, walked by making and unmaking moves on the one position.
+fn walk(state: &mut State, depth: u32) -> u64 {
+    match depth {
+        0 => 1,
+        // Bulk-count at the frontier instead of making each leaf move
+        1 => state.moves().count() as u64,
+        _ => {
+            let moves: Vec<Move> = state.moves().all().collect();
+            let mut nodes = 0;
+            for mv in moves {
+                let undo = state.make_move(mv);
+                nodes += walk(state, depth - 1);
+                state.unmake_move(undo);
+            }
+            nodes
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn start_position_counts() {
+        let state = State::start();
+        for (depth, expected) in [(1, 20), (2, 400), (3, 8_902), (4, 197_281)] {
+            assert_eq!(perft(&state, depth), expected, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn kiwipete_counts() {
+        let state = State::from_fen(KIWIPETE).unwrap();
+        for (depth, expected) in [(1, 48), (2, 2_039), (3, 97_862), (4, 4_085_603)] {
+            assert_eq!(perft(&state, depth), expected, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft_and_leaves_the_position_alone() {
+        let state = State::from_fen(KIWIPETE).unwrap();
+        let divided = perft_divide(&state, 3);
+        assert_eq!(divided.len(), 48);
+        assert_eq!(divided.iter().map(|&(_, nodes)| nodes).sum::<u64>(), 97_862);
+        assert_eq!(perft(&state, 0), 1);
+        assert!(perft_divide(&state, 0).is_empty());
+    }
+
+    #[test]
+    fn chess960_counts() {
+        let state = State::from_fen("qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9").unwrap();
+        for (depth, expected) in [(1, 22), (2, 593), (3, 13_440), (4, 382_958)] {
+            assert_eq!(perft(&state, depth), expected, "depth {}", depth);
+        }
+        // Castling with the king and rook crossing each other
+        let state = State::from_fen("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9").unwrap();
+        for (depth, expected) in [(1, 29), (2, 502), (3, 14_569), (4, 287_739)] {
+            assert_eq!(perft(&state, depth), expected, "depth {}", depth);
+        }
+        assert_eq!(perft(&State::start_960(518).unwrap(), 4), 197_281);
+    }
+}
@@ -0,0 +1,470 @@
+//! PGN (Portable Game Notation) import and export.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{self, BufRead};
+use std::mem;
+
+use crate::board::Color;
+use crate::fen::FenError;
+use crate::game::Game;
+use crate::notation::parse_san;
+use crate::state::{GameStatus, State};
+
+/// Longest line of exported movetext, per the PGN export format.
+const LINE_WIDTH: usize = 80;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// The Seven Tag Roster, less `Result`, which is derived from the game, and any further tags.
+/// Unknown values are written `?`, as the standard asks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnTags {
+    pub event: String,
+    pub site: String,
+    /// `YYYY.MM.DD`, with `??` for unknown parts.
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    /// Tags beyond the roster, in order, such as `ECO` or `TimeControl`. `SetUp` and `FEN` are
+    /// not kept here; they follow from the game's initial position.
+    pub extra: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnError {
+    /// A tag pair is not `[Name "value"]`.
+    BadTag(String),
+    /// A `{` comment is never closed.
+    UnterminatedComment,
+    /// A `)` without a matching `(`, or a variation still open at the end.
+    UnbalancedVariation,
+    /// The `FEN` tag does not hold a valid position.
+    BadFen(FenError),
+    /// A mainline move is illegal, ambiguous, or unreadable.
+    BadMove { number: u16, color: Color, san: String },
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        PgnTags {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+            extra: Vec::new(),
+        }
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse the first game in a PGN text and replay its mainline. Comments, NAGs, `%` escape
+/// lines, and variations are skipped; move numbers are optional; `0-0` castling, en-dash
+/// results, and CRLF line endings are accepted. A `FEN` tag sets the starting position; the
+/// other tags and the result, from the `Result` tag or the termination marker, are kept on
+/// the game.
+pub fn parse_pgn(text: &str) -> Result<Game, PgnError> {
+    let mut fen = None;
+    let mut game: Option<Game> = None;
+    let mut tags = PgnTags::default();
+    let mut result = None;
+
+    for token in tokenize(text)? {
+        match token {
+            Token::Tag(name, value) => match name.as_str() {
+                _ if game.is_some() => {}
+                "FEN" => fen = Some(value),
+                "SetUp" => {}
+                "Result" => result = Some(value),
+                "Event" => tags.event = value,
+                "Site" => tags.site = value,
+                "Date" => tags.date = value,
+                "Round" => tags.round = value,
+                "White" => tags.white = value,
+                "Black" => tags.black = value,
+                _ => tags.extra.push((name, value)),
+            },
+            Token::Word(word) => {
+                if is_result(&word) {
+                    result.get_or_insert(word);
+                    break;
+                }
+                let san = strip_move_number(&word);
+                if san.is_empty() { continue; }
+
+                let game = match &mut game {
+                    Some(game) => game,
+                    None => game.insert(match &fen {
+                        Some(fen) => Game::from_fen(fen).map_err(PgnError::BadFen)?,
+                        None => Game::new(),
+                    }),
+                };
+                let state = game.current();
+                let mv = parse_san(san, state).map_err(|_| PgnError::BadMove {
+                    number: state.fullmove_number,
+                    color: state.to_move,
+                    san: san.to_string(),
+                })?;
+                game.play(mv).expect("SAN resolves only to legal moves");
+            }
+        }
+    }
+
+    let mut game = match (game, fen) {
+        (Some(game), _) => game,
+        (None, Some(fen)) => Game::from_fen(&fen).map_err(PgnError::BadFen)?,
+        (None, None) => Game::new(),
+    };
+    game.set_tags(tags);
+    if let Some(result) = result { game.set_result(&result); }
+    Ok(game)
+}
+
+/// White's score in a game's text: 1 for a win, 0.5 for a draw, 0 for a loss, as the `Result`
+/// tag gives it or, failing that, the termination marker. None for unfinished games and text
+/// that does not tokenize.
+pub fn parse_result(text: &str) -> Option<f64> {
+    let tokens = tokenize(text).ok()?;
+    let tag = tokens.iter().find_map(|token| match token {
+        Token::Tag(name, value) if name == "Result" => Some(value.as_str()),
+        _ => None,
+    });
+    let marker = || tokens.iter().rev().find_map(|token| match token {
+        Token::Word(word) if is_result(word) => Some(word.as_str()),
+        _ => None,
+    });
+    match canonical_result(tag.or_else(marker)?)? {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        _ => None,
+    }
+}
+
+/// A termination marker in its standard spelling, accepting en dashes and `½-½`.
+pub(crate) fn canonical_result(word: &str) -> Option<&'static str> {
+    match word.replace('–', "-").as_str() {
+        "1-0" => Some("1-0"),
+        "0-1" => Some("0-1"),
+        "1/2-1/2" | "½-½" => Some("1/2-1/2"),
+        "*" => Some("*"),
+        _ => None,
+    }
+}
+
+enum Token {
+    Tag(String, String),
+    Word(String),
+}
+
+/// Split PGN text into tag pairs and mainline words, dropping everything else.
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let at_line_start = line_start;
+        line_start = c == '\n';
+        match c {
+            c if c.is_whitespace() => {}
+            '%' if at_line_start => { chars.by_ref().find(|&c| c == '\n'); line_start = true; }
+            ';' => { chars.by_ref().find(|&c| c == '\n'); line_start = true; }
+            '{' => { chars.by_ref().find(|&c| c == '}').ok_or(PgnError::UnterminatedComment)?; }
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(PgnError::UnbalancedVariation)?,
+            '$' => while chars.next_if(char::is_ascii_digit).is_some() {},
+            '[' => {
+                let mut tag = String::new();
+                let mut quoted = false;
+                loop {
+                    match chars.next() {
+                        Some('\\') if quoted => tag.extend(chars.next()),
+                        Some('"') => { quoted = !quoted; tag.push('"'); }
+                        Some(']') if !quoted => break,
+                        Some(c) => tag.push(c),
+                        None => return Err(PgnError::BadTag(tag)),
+                    }
+                }
+                if depth == 0 { tokens.push(parse_tag(&tag)?); }
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !"{}()[];$".contains(c)) {
+                    word.push(c);
+                }
+                if depth == 0 { tokens.push(Token::Word(word)); }
+            }
+        }
+    }
+
+    if depth != 0 { return Err(PgnError::UnbalancedVariation); }
+    Ok(tokens)
+}
+
+/// `Name "value"`, the text between the brackets with escapes already resolved.
+fn parse_tag(tag: &str) -> Result<Token, PgnError> {
+    let bad = || PgnError::BadTag(tag.to_string());
+    let (name, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(bad)?;
+    let value = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or_else(bad)?;
+    Ok(Token::Tag(name.to_string(), value.to_string()))
+}
+
+/// Game termination markers, including en-dash and `½` spellings.
+fn is_result(word: &str) -> bool {
+    canonical_result(word).is_some()
+}
+
+/// Drop a leading `12.` or `12...`, which may be glued to the move that follows.
+fn strip_move_number(word: &str) -> &str {
+    let digits = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    if digits.len() < word.len() && digits.starts_with('.') { digits.trim_start_matches('.') } else { word }
+}
+
+// ============================================================================
+// Streaming
+// ============================================================================
+
+/// Split a PGN collection into the text of each game, a line at a time, so that databases
+/// far larger than memory stream through. A game ends where a tag pair follows its movetext,
+/// or at the end of input. A read error ends the stream after it is yielded.
+pub fn games(reader: impl BufRead) -> impl Iterator<Item = io::Result<String>> {
+    gen move {
+        let mut game = String::new();
+        let mut in_movetext = false;
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => { yield Err(err); return; }
+            };
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') {
+                if in_movetext { yield Ok(mem::take(&mut game)); }
+                in_movetext = false;
+            } else if !trimmed.is_empty() {
+                in_movetext = true;
+            }
+            game.push_str(&line);
+            game.push('\n');
+        }
+        if !game.trim().is_empty() { yield Ok(game); }
+    }
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+/// Render a game as PGN: the tag pairs, a blank line, then wrapped movetext ending with the
+/// result. Games that do not start from the initial position also get `SetUp` and `FEN` tags.
+/// The result is the recorded one while the position does not decide the game.
+pub fn format_pgn(game: &Game, tags: &PgnTags) -> String {
+    let result = match game.status() {
+        GameStatus::Ongoing => game.recorded_result().unwrap_or("*"),
+        status => result_token(status),
+    };
+    let mut pgn = String::new();
+
+    for (name, value) in [
+        ("Event", &tags.event),
+        ("Site", &tags.site),
+        ("Date", &tags.date),
+        ("Round", &tags.round),
+        ("White", &tags.white),
+        ("Black", &tags.black),
+    ] {
+        pgn.push_str(&tag_pair(name, value));
+    }
+    pgn.push_str(&tag_pair("Result", result));
+
+    let initial = game.initial().to_fen();
+    if initial != State::start().to_fen() {
+        pgn.push_str(&tag_pair("SetUp", "1"));
+        pgn.push_str(&tag_pair("FEN", &initial));
+    }
+    for (name, value) in &tags.extra {
+        pgn.push_str(&tag_pair(name, value));
+    }
+
+    pgn.push('\n');
+    pgn.push_str(&wrap(&movetext_tokens(game, result), LINE_WIDTH));
+    pgn.push('\n');
+    pgn
+}
+
+/// The token closing the movetext, which also fills the `Result` tag.
+pub fn result_token(status: GameStatus) -> &'static str {
+    match status {
+        GameStatus::Checkmate(Color::White) => "1-0",
+        GameStatus::Checkmate(Color::Black) => "0-1",
+        GameStatus::Ongoing => "*",
+        _ => "1/2-1/2",
+    }
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+fn tag_pair(name: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, escaped)
+}
+
+/// Move numbers, SAN moves, and the result, each a separate token. A game starting with Black
+/// to move opens with `N...`.
+fn movetext_tokens(game: &Game, result: &str) -> Vec<String> {
+    let initial = game.initial();
+    let mut number = initial.fullmove_number;
+    let mut to_move = initial.to_move;
+    let mut tokens = Vec::new();
+
+    for (i, san) in game.san_moves().iter().enumerate() {
+        match to_move {
+            Color::White => tokens.push(format!("{}.", number)),
+            Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+            Color::Black => {}
+        }
+        tokens.push(san.clone());
+        if to_move == Color::Black { number += 1; }
+        to_move = !to_move;
+    }
+    tokens.push(result.to_string());
+    tokens
+}
+
+/// Join tokens with single spaces, breaking lines before they would exceed `width`.
+fn wrap(tokens: &[String], width: usize) -> String {
+    let mut text = String::new();
+    let mut line_len = 0;
+    for token in tokens {
+        if line_len > 0 && line_len + 1 + token.len() > width {
+            text.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            text.push(' ');
+            line_len += 1;
+        }
+        text.push_str(token);
+        line_len += token.len();
+    }
+    text
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PgnError::BadTag(s)              => write!(f, "malformed tag pair '[{}]'", s),
+            PgnError::UnterminatedComment    => write!(f, "unterminated comment"),
+            PgnError::UnbalancedVariation    => write!(f, "unbalanced variation parentheses"),
+            PgnError::BadFen(e)              => write!(f, "invalid FEN tag: {}", e),
+            PgnError::BadMove { number, color, san } => {
+                let dots = if *color == Color::White { "." } else { "..." };
+                write!(f, "illegal move {}{} {}", number, dots, san)
+            }
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESIGNED: &str = r#"[Event "Club Championship"]
+[Site "Leipzig"]
+[Date "2024.03.09"]
+[Round "4"]
+[White "Anderssen, A."]
+[Black "Kieseritzky, L."]
+[Result "0-1"]
+[ECO "C33"]
+[TimeControl "40/7200"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 0-1
+"#;
+
+    #[test]
+    fn import_then_export_keeps_tags_and_result() {
+        let game = parse_pgn(RESIGNED).unwrap();
+        assert_eq!(game.tags().white, "Anderssen, A.");
+        assert_eq!(game.tags().extra, [("ECO".to_string(), "C33".to_string()), ("TimeControl".to_string(), "40/7200".to_string())]);
+        assert_eq!(game.recorded_result(), Some("0-1"));
+        assert_eq!(game.to_pgn(game.tags()), RESIGNED);
+    }
+
+    #[test]
+    fn export_then_import_gives_the_same_moves() {
+        // Castling both ways, promotions by capture, and checks
+        let fen = "r3k2r/1P6/8/8/8/8/6p1/R3K2R w KQkq - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        for san in ["O-O-O", "O-O", "bxa8=Q", "Kg7", "Qxf8+", "Kxf8", "Rd8+", "Kg7", "Kb1", "gxh1=N", "Rd7+"] {
+            game.play(parse_san(san, game.current()).unwrap()).unwrap();
+        }
+        let pgn = game.to_pgn(&PgnTags::default());
+        assert!(pgn.contains("[FEN \"r3k2r/1P6/8/8/8/8/6p1/R3K2R w KQkq - 0 1\"]"));
+        assert!(pgn.contains("1. O-O-O O-O 2. bxa8=Q Kg7 3. Qxf8+ Kxf8 4. Rd8+ Kg7 5. Kb1 gxh1=N 6. Rd7+ *"));
+
+        let reimported = parse_pgn(&pgn).unwrap();
+        assert_eq!(reimported.moves_played(), game.moves_played());
+        assert_eq!(reimported.san_moves(), game.san_moves());
+    }
+
+    #[test]
+    fn nested_variations_comments_and_nags_are_skipped() {
+        let pgn = r#"[Event "Annotated"]
+[Result "1-0"]
+
+1. e4 {King's pawn} e5 (1... c5 2. Nf3 (2. c3 d5 (2... Nf6 3. e5) 3. exd5) 2... d6) 2. Nf3 $1
+Nc6 (2... d6 3. d4 (3. Bc4 {Philidor} Be7) 3... exd4) 3. Bb5 ; the Spanish
+a6 $2 (3... Nf6 (3... f5!? 4. Nc3) 4. O-O) 4. Ba4 1-0
+"#;
+        let game = parse_pgn(pgn).unwrap();
+        assert_eq!(game.san_moves(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4"]);
+        assert_eq!(game.recorded_result(), Some("1-0"));
+        assert_eq!(game.tags().event, "Annotated");
+
+        assert_eq!(parse_pgn("1. e4 (1. d4 (1. c4) e5").unwrap_err(), PgnError::UnbalancedVariation);
+        assert_eq!(parse_pgn("1. e4 (1. d4)) e5").unwrap_err(), PgnError::UnbalancedVariation);
+        // Variation moves are never checked, not even against the position they branch from
+        assert!(parse_pgn("1. e4 (1. Qh5 (1. Ke3)) e5").is_ok());
+    }
+
+    #[test]
+    fn the_position_decides_the_result_over_the_record() {
+        let mut game = parse_pgn("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 *").unwrap();
+        assert!(game.to_pgn(&PgnTags::default()).ends_with("4. Ng1 Ng8 1/2-1/2\n"));
+        game.set_result("1-0");
+        assert!(game.to_pgn(&PgnTags::default()).ends_with("1/2-1/2\n"));
+
+        let fools_mate = parse_pgn("1. f3 e5 2. g4 Qh4# *").unwrap();
+        assert!(fools_mate.to_pgn(&PgnTags::default()).ends_with("2. g4 Qh4# 0-1\n"));
+    }
+
+    #[test]
+    fn movetext_wraps_at_80_columns() {
+        let tokens: Vec<String> = (1..=40).flat_map(|n| [format!("{}.", n), "Nf3".to_string(), "Nf6".to_string()]).collect();
+        let text = wrap(&tokens, LINE_WIDTH);
+        assert!(text.lines().count() > 1);
+        assert!(text.lines().all(|line| line.len() <= LINE_WIDTH && !line.starts_with(' ')));
+        assert_eq!(text.split_whitespace().count(), tokens.len());
+    }
+}
@@ -0,0 +1,233 @@
+//! Playing against the engine at a terminal. The board is printed after every move, moves are
+//! typed in SAN or UCI, and the engine replies by searching to a fixed limit.
+
+use std::io::{self, BufRead, Write};
+
+use crate::bits::SquareSet;
+use crate::board::{Color, PieceType, Square};
+use crate::game::Game;
+use crate::mv::Move;
+use crate::search::{Search, SearchLimits};
+use crate::state::{GameStatus, State};
+
+/// Depth the engine searches when no limit is given.
+const DEFAULT_DEPTH: u32 = 5;
+
+const HELP: &str = "Enter moves in SAN (Nf3, exd5, O-O) or UCI (g1f3). \
+    Commands: undo, fen, hint, flip, resign, help, quit.";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct PlayOptions {
+    /// The side the player takes in the first game; the engine takes the other.
+    pub human: Color,
+    /// How long the engine thinks over each reply and each hint.
+    pub limits: SearchLimits,
+    /// Piece letters instead of figurines, for terminals that garble them.
+    pub ascii: bool,
+}
+
+/// How a game left the loop.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Ending {
+    /// Mate, a draw, or a resignation.
+    Finished,
+    /// The player quit or input ran out.
+    Quit,
+}
+
+/// One game in progress and the terminal it is played at.
+struct Session<'a, W: Write> {
+    output: &'a mut W,
+    search: &'a mut Search,
+    options: &'a PlayOptions,
+    game: Game,
+    human: Color,
+    /// Black at the bottom of the board.
+    flipped: bool,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        PlayOptions { human: Color::White, limits: SearchLimits::depth(DEFAULT_DEPTH), ascii: false }
+    }
+}
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Play games until the player quits or input ends. After each finished game the player is
+/// offered a rematch with the colors reversed.
+pub fn run(input: impl BufRead, mut output: impl Write, options: PlayOptions) -> io::Result<()> {
+    let mut lines = input.lines();
+    let mut search = Search::new();
+    let mut human = options.human;
+    writeln!(output, "{}", HELP)?;
+
+    loop {
+        search.clear_hash();
+        let mut session = Session {
+            output: &mut output,
+            search: &mut search,
+            options: &options,
+            game: Game::new(),
+            human,
+            flipped: human == Color::Black,
+        };
+        if session.play(&mut lines)? == Ending::Quit { return Ok(()); }
+
+        write!(output, "Play again with the colors reversed? [y/n] ")?;
+        output.flush()?;
+        match lines.next().transpose()? {
+            Some(answer) if answer.trim().to_ascii_lowercase().starts_with('y') => human = !human,
+            _ => return Ok(()),
+        }
+    }
+}
+
+// ============================================================================
+// Session — Game Loop
+// ============================================================================
+
+impl<W: Write> Session<'_, W> {
+    fn play(&mut self, lines: &mut impl Iterator<Item = io::Result<String>>) -> io::Result<Ending> {
+        self.show_board()?;
+        loop {
+            if let Some(result) = result_message(self.game.status()) {
+                writeln!(self.output, "{}", result)?;
+                return Ok(Ending::Finished);
+            }
+            if self.game.current().to_move != self.human {
+                self.engine_move()?;
+                continue;
+            }
+
+            write!(self.output, "{:?} to move> ", self.human)?;
+            self.output.flush()?;
+            let Some(line) = lines.next().transpose()? else { return Ok(Ending::Quit) };
+            match line.trim() {
+                "" => {}
+                "help" => writeln!(self.output, "{}", HELP)?,
+                "quit" | "exit" => return Ok(Ending::Quit),
+                "fen" => writeln!(self.output, "{}", self.game.current().to_fen())?,
+                "flip" => {
+                    self.flipped = !self.flipped;
+                    self.show_board()?;
+                }
+                "hint" => match self.think() {
+                    Some(mv) => writeln!(self.output, "Hint: {}", mv.to_san(self.game.current()))?,
+                    None => writeln!(self.output, "No hint: there are no legal moves.")?,
+                },
+                "undo" => self.undo()?,
+                "resign" => {
+                    writeln!(self.output, "{:?} resigns. {:?} wins.", self.human, !self.human)?;
+                    return Ok(Ending::Finished);
+                }
+                text => match parse_move(text, self.game.current()) {
+                    Ok(mv) => {
+                        self.game.play(mv).expect("parsed moves are legal");
+                        self.show_board()?;
+                    }
+                    Err(message) => writeln!(self.output, "{}", message)?,
+                },
+            }
+        }
+    }
+
+    fn engine_move(&mut self) -> io::Result<()> {
+        let Some(mv) = self.think() else { return Ok(()) };
+        writeln!(self.output, "blitz plays {}", mv.to_san(self.game.current()))?;
+        self.game.play(mv).expect("the search returns legal moves");
+        self.show_board()
+    }
+
+    /// Take back the player's last move along with the engine's reply to it.
+    fn undo(&mut self) -> io::Result<()> {
+        if self.game.moves_played().len() < 2 {
+            return writeln!(self.output, "Nothing to undo.");
+        }
+        self.game.undo();
+        self.game.undo();
+        self.show_board()
+    }
+
+    fn think(&mut self) -> Option<Move> {
+        self.search.best_move_in(&self.game, self.options.limits.clone()).best_move
+    }
+
+    /// The board from the player's side, with the last move's squares marked.
+    fn show_board(&mut self) -> io::Result<()> {
+        let board = &self.game.current().board;
+        let mut display = board.display();
+        if self.options.ascii { display = display.ascii(); }
+        if self.flipped { display = display.flipped(); }
+        if let Some(&mv) = self.game.moves_played().last() {
+            display = display.highlight(SquareSet::from(mv.source()) | SquareSet::from(mv.destination()));
+        }
+        writeln!(self.output, "\n{}", display)
+    }
+}
+
+// ============================================================================
+// Input
+// ============================================================================
+
+/// A legal move typed in UCI or SAN, or a message saying what went wrong and which moves would
+/// have done instead.
+fn parse_move(text: &str, state: &State) -> Result<Move, String> {
+    if let Ok(mv) = Move::from_uci(text, state) && state.is_legal_move(mv) { return Ok(mv); }
+    if let Ok(mv) = Move::from_san(text, state) { return Ok(mv); }
+
+    // A UCI move names its source square and a SAN move its piece; list what either can do
+    let (moves, about): (Vec<Move>, String) = match uci_source(text) {
+        Some(sq) => match state.board[sq] {
+            Some(piece) if piece.color() == state.to_move => {
+                (state.moves().from(sq).collect(), format!(" for the {} on {}", piece_name(piece.piece_type()), sq))
+            }
+            _ => return Err(format!("There is no {:?} piece on {}.", state.to_move, sq)),
+        },
+        None => match text.chars().next().filter(char::is_ascii_uppercase).and_then(PieceType::from_char) {
+            Some(piece_type) => {
+                let moves = state.moves().all()
+                    .filter(|mv| state.board[mv.source()].is_some_and(|piece| piece.piece_type() == piece_type));
+                (moves.collect(), format!(" for your {}s", piece_name(piece_type)))
+            }
+            None => (state.moves().all().collect(), String::new()),
+        },
+    };
+
+    let moves: Vec<String> = moves.iter().map(|mv| mv.to_san(state)).collect();
+    if moves.is_empty() {
+        Err(format!("'{}' is not a legal move. There are no legal moves{}.", text, about))
+    } else {
+        Err(format!("'{}' is not a legal move. Legal moves{}: {}", text, about, moves.join(", ")))
+    }
+}
+
+/// The source square of text shaped like a UCI move.
+fn uci_source(text: &str) -> Option<Square> {
+    let target = text.get(2..4).and_then(Square::from_algebraic);
+    target.and_then(|_| text.get(0..2)).and_then(Square::from_algebraic)
+}
+
+fn piece_name(piece_type: PieceType) -> String {
+    format!("{:?}", piece_type).to_lowercase()
+}
+
+/// The announcement for a finished game, or None while it goes on.
+fn result_message(status: GameStatus) -> Option<String> {
+    Some(match status {
+        GameStatus::Ongoing                  => return None,
+        GameStatus::Checkmate(winner)        => format!("Checkmate. {:?} wins.", winner),
+        GameStatus::Stalemate                => "Stalemate. The game is drawn.".to_string(),
+        GameStatus::DrawFiftyMove            => "Draw by the fifty-move rule.".to_string(),
+        GameStatus::DrawInsufficientMaterial => "Draw: neither side has mating material.".to_string(),
+        GameStatus::DrawDeadPosition         => "Draw: neither side can ever mate.".to_string(),
+        GameStatus::DrawThreefoldRepetition  => "Draw by threefold repetition.".to_string(),
+        GameStatus::DrawFivefoldRepetition   => "Draw by fivefold repetition.".to_string(),
+    })
+}
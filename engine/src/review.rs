@@ -0,0 +1,139 @@
+//! Game review: every move of a game searched and marked by how much it gave away against
+//! the best move found, as the annotations of a review tool show it.
+
+use std::io;
+
+use crate::controller::EngineController;
+use crate::game::Game;
+use crate::mv::Move;
+use crate::score::Score;
+use crate::search::SearchLimits;
+use crate::state::GameStatus;
+
+/// Loss, in centipawns, from which a move counts as an inaccuracy, a mistake, and a blunder.
+/// Anything less is good, and a move the search would have played itself is best.
+pub const INACCURACY_LOSS: i32 = 50;
+pub const MISTAKE_LOSS: i32 = 100;
+pub const BLUNDER_LOSS: i32 = 300;
+
+/// What a forced mate is worth when measuring loss. Scores beyond it count as it, so that a
+/// mate played more slowly, or a won position that stays won, loses nothing, while stepping
+/// into a mate from level ground is a blunder.
+pub const MATE_LOSS_CAP: i32 = 1000;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One move of a reviewed game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveReview {
+    /// The move played.
+    pub mv: Move,
+    /// What the search would have played instead.
+    pub best_move: Move,
+    /// The position before the move, as the search scored it, for the side that moved.
+    pub score_before: Score,
+    /// The position the move left, for the same side.
+    pub score_after: Score,
+    /// Centipawns the move gave away, never negative; see `MATE_LOSS_CAP`.
+    pub loss: i32,
+    pub class: MoveClass,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveClass {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+// ============================================================================
+// Review
+// ============================================================================
+
+/// Search every position of `game` from its initial one with `limits`, then mark each move
+/// by comparing the score before it with the score of the position it left. One search serves
+/// as both, so each position is searched once, in order, through one analysis controller
+/// whose table warms from each position to the next. A position drawn by the game's rules,
+/// as by a repetition, scores zero whatever the search makes of it.
+pub fn review_game(game: &Game, limits: SearchLimits) -> Vec<MoveReview> {
+    let mut engine = EngineController::new(io::sink());
+    let mut replay = Game::from_state(game.initial().clone());
+    let mut analyse = |replay: &Game| {
+        let mut result = engine.analyse(replay, limits.clone()).expect("no search is running");
+        // The search scores mate and stalemate at the root, but not the game's draw rules
+        if !matches!(replay.status(), GameStatus::Ongoing | GameStatus::Checkmate(_)) { result.score = Score::ZERO; }
+        result
+    };
+
+    let mut before = analyse(&replay);
+    let mut reviews = Vec::with_capacity(game.moves_played().len());
+    for &mv in game.moves_played() {
+        replay.play(mv).expect("the game's moves are legal");
+        let after = analyse(&replay);
+        let best_move = before.best_move.unwrap_or(mv);
+        let (score_before, score_after) = (before.score, -after.score);
+        let loss = if mv == best_move { 0 } else { (capped(score_before) - capped(score_after)).max(0) };
+        let class = if mv == best_move { MoveClass::Best } else { classify(loss) };
+        reviews.push(MoveReview { mv, best_move, score_before, score_after, loss, class });
+        before = after;
+    }
+    reviews
+}
+
+/// Centipawns, with mates and anything past `MATE_LOSS_CAP` counted as the cap.
+fn capped(score: Score) -> i32 {
+    score.raw().clamp(-MATE_LOSS_CAP, MATE_LOSS_CAP)
+}
+
+fn classify(loss: i32) -> MoveClass {
+    match loss {
+        _ if loss >= BLUNDER_LOSS => MoveClass::Blunder,
+        _ if loss >= MISTAKE_LOSS => MoveClass::Mistake,
+        _ if loss >= INACCURACY_LOSS => MoveClass::Inaccuracy,
+        _ => MoveClass::Good,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played(fen: Option<&str>, moves: &[&str]) -> Game {
+        let mut game = fen.map_or_else(Game::new, |fen| Game::from_fen(fen).unwrap());
+        for text in moves { game.play(Move::from_uci(text, game.current()).unwrap()).unwrap(); }
+        game
+    }
+
+    #[test]
+    fn a_hung_queen_is_the_one_blunder() {
+        // 3...Qh4?? walks into the f3 knight
+        let game = played(None, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "d8h4", "f3h4"]);
+        let reviews = review_game(&game, SearchLimits::depth(6));
+        assert_eq!(reviews.len(), 7);
+        let blunders: Vec<usize> = (0..reviews.len()).filter(|&i| reviews[i].class == MoveClass::Blunder).collect();
+        assert_eq!(blunders, [5], "{:#?}", reviews);
+        assert!(reviews[5].loss > BLUNDER_LOSS && reviews[5].best_move != reviews[5].mv);
+        assert_eq!(reviews[6].class, MoveClass::Best);
+    }
+
+    #[test]
+    fn a_perfectly_played_mate_loses_nothing() {
+        let fen = "r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1";
+        let mut game = played(Some(fen), &[]);
+        let mut engine = EngineController::new(io::sink());
+        while game.status() == GameStatus::Ongoing {
+            let mv = engine.analyse(&game, SearchLimits::depth(6)).unwrap().best_move.unwrap();
+            game.play(mv).unwrap();
+        }
+        assert!(matches!(game.status(), GameStatus::Checkmate(_)));
+        assert_eq!(game.moves_played().len(), 5);
+
+        let reviews = review_game(&game, SearchLimits::depth(6));
+        assert!(reviews.iter().all(|review| review.loss == 0), "{:#?}", reviews);
+        assert!(reviews.iter().step_by(2).all(|review| review.score_after.mate_distance().is_some_and(|plies| plies >= 0)));
+    }
+}
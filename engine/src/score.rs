@@ -0,0 +1,235 @@
+//! Search scores: centipawns from the side to move's perspective, or a mate counted in plies
+//! from the root of the search.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::{Add, Neg, Sub};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A centipawn score or a mate score, ordered so that sooner mates rank above later ones and
+/// every mate above every centipawn score. Negating turns a score for one side into the same
+/// score for the other, as negamax needs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(i32);
+
+/// Score of mating at the root; each ply further away scores one less.
+const MATE: i32 = 30_000;
+
+/// Longest mate, in plies, that is told apart from a centipawn score.
+const MAX_MATE_PLIES: i32 = 1000;
+
+/// Scores at least this far from zero are mates.
+const MATE_BOUND: i32 = MATE - MAX_MATE_PLIES;
+
+// ============================================================================
+// Construction
+// ============================================================================
+
+impl Score {
+    pub const ZERO: Score = Score(0);
+
+    /// Beyond every reachable score: the bound of the first search window. Arithmetic saturates
+    /// here, and it fits the sixteen bits a hash table entry keeps.
+    pub const INFINITY: Score = Score(32_000);
+
+    /// Largest centipawn score, short of every mate.
+    pub const MAX_CP: i32 = MATE_BOUND - 1;
+
+    /// Centipawns, clamped so that no evaluation ever reads as a mate.
+    pub const fn cp(centipawns: i32) -> Score {
+        Score(if centipawns > Self::MAX_CP { Self::MAX_CP } else if centipawns < -Self::MAX_CP { -Self::MAX_CP } else { centipawns })
+    }
+
+    /// The side to move mates `plies` from the root, counting the mating move.
+    pub const fn mate_in(plies: u32) -> Score {
+        Score(MATE - plies as i32)
+    }
+
+    /// The side to move is mated `plies` from the root.
+    pub const fn mated_in(plies: u32) -> Score {
+        Score(-MATE + plies as i32)
+    }
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+impl Score {
+    pub const fn is_mate(self) -> bool {
+        self.0.abs() >= MATE_BOUND
+    }
+
+    /// Plies from the root to mate: positive when the side to move mates, negative when it is
+    /// mated, None for centipawn scores.
+    pub const fn mate_distance(self) -> Option<i32> {
+        if !self.is_mate() { None }
+        else if self.0 > 0 { Some(MATE - self.0) }
+        else { Some(-MATE - self.0) }
+    }
+
+    /// Full moves to mate, counting the mating move, negative when the side to move is mated.
+    pub const fn mate_moves(self) -> Option<i32> {
+        match self.mate_distance() {
+            Some(plies) if plies > 0 => Some((plies + 1) / 2),
+            Some(plies) => Some(plies / 2),
+            None => None,
+        }
+    }
+
+    /// The underlying value: centipawns, or the mate score itself for mates.
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+}
+
+// ============================================================================
+// Hash Table Adjustment
+// ============================================================================
+
+impl Score {
+    /// Mate scores count plies from the root; stored ones count from the node itself, so the
+    /// same position reached at a different ply reads back the right distance to mate.
+    pub const fn to_tt(self, ply: u32) -> i16 {
+        let stored = if self.0 >= MATE_BOUND { self.0 + ply as i32 }
+            else if self.0 <= -MATE_BOUND { self.0 - ply as i32 }
+            else { self.0 };
+        stored as i16
+    }
+
+    /// A stored score, read back at `ply` from the root.
+    pub const fn from_tt(stored: i16, ply: u32) -> Score {
+        let stored = stored as i32;
+        Score(if stored >= MATE_BOUND { stored - ply as i32 }
+            else if stored <= -MATE_BOUND { stored + ply as i32 }
+            else { stored })
+    }
+}
+
+// ============================================================================
+// Arithmetic
+// ============================================================================
+
+impl Score {
+    const fn saturate(value: i32) -> Score {
+        let limit = Self::INFINITY.0;
+        Score(if value > limit { limit } else if value < -limit { -limit } else { value })
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+/// Shift by centipawns, saturating at infinity.
+impl Add<i32> for Score {
+    type Output = Score;
+
+    fn add(self, centipawns: i32) -> Score {
+        Score::saturate(self.0.saturating_add(centipawns))
+    }
+}
+
+impl Sub<i32> for Score {
+    type Output = Score;
+
+    fn sub(self, centipawns: i32) -> Score {
+        Score::saturate(self.0.saturating_sub(centipawns))
+    }
+}
+
+// ============================================================================
+// Display
+// ============================================================================
+
+/// As UCI reports it: `cp 35`, or `mate 3` with a negative count when being mated.
+impl Display for Score {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.mate_moves() {
+            Some(moves) => write!(f, "mate {}", moves),
+            None => write!(f, "cp {}", self.0),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A spread of centipawn and mate scores for both sides, the extremes included.
+    fn samples() -> Vec<Score> {
+        let mut scores = vec![Score::ZERO, Score::INFINITY, -Score::INFINITY];
+        for cp in [-Score::MAX_CP - 50, -Score::MAX_CP, -10_000, -35, -1, 1, 35, 10_000, Score::MAX_CP, Score::MAX_CP + 50] {
+            scores.push(Score::cp(cp));
+        }
+        for plies in [0, 1, 2, 5, 6, 99, 500, MAX_MATE_PLIES as u32] {
+            scores.extend([Score::mate_in(plies), Score::mated_in(plies)]);
+        }
+        scores
+    }
+
+    #[test]
+    fn negation_is_an_involution_that_reverses_order() {
+        for a in samples() {
+            assert_eq!(-(-a), a);
+            assert_eq!(a.mate_distance().map(|plies| -plies), (-a).mate_distance());
+            for b in samples() {
+                assert_eq!(a.cmp(&b), (-b).cmp(&-a), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn every_mate_outranks_every_centipawn_score_and_sooner_mates_rank_higher() {
+        assert!(Score::mate_in(5) > Score::cp(10_000));
+        assert!(Score::mate_in(MAX_MATE_PLIES as u32) > Score::cp(i32::MAX));
+        assert!(Score::mated_in(MAX_MATE_PLIES as u32) < Score::cp(i32::MIN));
+        assert!(Score::mate_in(3) > Score::mate_in(5));
+        assert!(Score::mated_in(3) < Score::mated_in(5));
+        assert!(!Score::cp(i32::MAX).is_mate() && Score::mate_in(MAX_MATE_PLIES as u32).is_mate());
+        assert!(Score::INFINITY > Score::mate_in(0) && -Score::INFINITY < Score::mated_in(0));
+    }
+
+    #[test]
+    fn mate_counts_read_in_full_moves() {
+        assert_eq!(Score::mate_in(1).mate_moves(), Some(1));
+        assert_eq!(Score::mate_in(5).mate_moves(), Some(3));
+        assert_eq!(Score::mated_in(4).mate_moves(), Some(-2));
+        assert_eq!(Score::cp(35).mate_moves(), None);
+        assert_eq!(Score::mate_in(5).to_string(), "mate 3");
+        assert_eq!(Score::mated_in(4).to_string(), "mate -2");
+        assert_eq!(Score::cp(-35).to_string(), "cp -35");
+    }
+
+    #[test]
+    fn hash_table_round_trip_keeps_the_distance_from_the_node() {
+        for score in samples().into_iter().filter(|score| score.raw().abs() < Score::INFINITY.raw()) {
+            for ply in [0, 1, 7, 40] {
+                assert_eq!(Score::from_tt(score.to_tt(ply), ply), score, "{:?} at ply {}", score, ply);
+            }
+        }
+        // Mate in 3 plies found at ply 4 is mate in 5 plies when met again at ply 2
+        let stored = Score::mate_in(4 + 3).to_tt(4);
+        assert_eq!(Score::from_tt(stored, 2), Score::mate_in(2 + 3));
+        let stored = Score::mated_in(4 + 2).to_tt(4);
+        assert_eq!(Score::from_tt(stored, 10), Score::mated_in(10 + 2));
+        assert_eq!(Score::from_tt(Score::cp(35).to_tt(9), 1), Score::cp(35));
+    }
+
+    #[test]
+    fn arithmetic_saturates_at_infinity() {
+        assert_eq!(Score::INFINITY + 1, Score::INFINITY);
+        assert_eq!(-Score::INFINITY - 1, -Score::INFINITY);
+        assert_eq!(Score::cp(10) + 5 - 20, Score::cp(-5));
+    }
+}
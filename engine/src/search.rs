@@ -0,0 +1,1690 @@
+//! Game-tree search: negamax with alpha-beta pruning under iterative deepening.
+
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::{Piece, PieceType, Square};
+use crate::eval::{evaluate_cached, piece_value, MaterialTable, PawnTable};
+use crate::game::Game;
+use crate::history::History;
+use crate::mv::{Move, MoveType};
+use crate::score::Score;
+use crate::see::see;
+use crate::skill::{pick, Strength, CANDIDATES, MATE_IN_ONE_LEVEL};
+use crate::state::{GameStatus, State, Undo};
+#[cfg(feature = "syzygy")]
+use crate::tablebase::{Tablebase, Wdl};
+#[cfg(feature = "trace")]
+use crate::trace::{SearchTrace, Tracer};
+use crate::time::{Stopwatch, TimeManager, DEFAULT_MOVE_OVERHEAD};
+use crate::tt::{Bound, TranspositionTable, TtEntry};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+const MAX_DEPTH: u32 = 64;
+
+/// Slack over the captured piece's value before quiescence gives up on a capture.
+const DELTA_MARGIN: i32 = 200;
+
+/// Depth given up by the null-move search, on top of the move itself.
+const NULL_MOVE_REDUCTION: u32 = 2;
+
+/// Quiet moves ordered at or after this index are searched reduced first.
+const LMR_MIN_INDEX: usize = 3;
+
+/// Most the two captures of a recapture may differ by in value for it to count as a trade.
+const RECAPTURE_MARGIN: i32 = 50;
+
+/// Deepest remaining depth at which futility pruning and reverse futility apply.
+const FUTILITY_MAX_DEPTH: u32 = 3;
+
+/// How far above alpha, by remaining depth, a quiet move could plausibly lift the static eval.
+const FUTILITY_MARGINS: [i32; FUTILITY_MAX_DEPTH as usize + 1] = [0, 150, 300, 450];
+
+/// How far beta, by remaining depth, the static eval must beat to cut without searching.
+const REVERSE_FUTILITY_MARGINS: [i32; FUTILITY_MAX_DEPTH as usize + 1] = [0, 120, 250, 400];
+
+/// How much the futility margins give when the static eval is improving: reverse futility
+/// cuts more readily, and futility prunes less.
+const IMPROVING_MARGIN: i32 = 40;
+
+/// How far below alpha, by remaining depth, the static eval must be for razoring to ask
+/// quiescence whether the node fails low.
+const RAZOR_MARGINS: [i32; FUTILITY_MAX_DEPTH as usize + 1] = [0, 250, 400, 550];
+
+/// Depth from which a TT move is tested for being the only good move.
+const SINGULAR_MIN_DEPTH: u32 = 8;
+
+/// How far per ply of depth every other move must score below the TT move for it to count as
+/// singular.
+const SINGULAR_MARGIN: i32 = 2;
+
+/// Depth from which ProbCut is tried at non-PV nodes.
+const PROBCUT_MIN_DEPTH: u32 = 5;
+
+/// How far above beta a capture must score in the shallow search for ProbCut to trust it.
+const PROBCUT_MARGIN: i32 = 300;
+
+/// Depth the ProbCut verification search gives up, the capture included.
+const PROBCUT_REDUCTION: u32 = 3;
+
+/// Depth every root move is scored to before the best few are scored again at full depth,
+/// when a skill level weakens the search.
+const SKILL_SHORTLIST_DEPTH: u32 = 3;
+
+/// Nodes between checks of the stop flag and the hard deadline.
+const CHECK_INTERVAL: u64 = 2048;
+
+/// Score of a tablebase win at the root: beyond any evaluation, yet short of every mate.
+#[cfg(feature = "syzygy")]
+const TB_WIN_SCORE: i32 = Score::MAX_CP - MAX_DEPTH as i32;
+
+/// When to stop searching, mirroring the parameters of UCI `go`. With no limits set the search
+/// runs to the maximum depth or until stopped.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    /// Nodes the main thread may visit. The count is exact, so with one thread a node-limited
+    /// search is deterministic.
+    pub nodes: Option<u64>,
+    /// Stop once a mate in at most this many moves is found for the side to move.
+    pub mate: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    /// Ignore the clock entirely; only the stop flag ends the search.
+    pub infinite: bool,
+    /// Search the predicted position on the opponent's time. The caller raises the ponder flag
+    /// before starting; the clock limits take effect once it is lowered, counting the time
+    /// already spent.
+    pub ponder: bool,
+    /// Only these root moves are searched, unless none of them is legal. Empty means all.
+    pub searchmoves: Vec<Move>,
+}
+
+/// Switches for the shallow-depth pruning and for move ordering, all on by default. Turning one
+/// off shows what it costs or finds, as when bisecting a tactical regression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Skip quiet moves near the leaves when the static eval plus a margin cannot reach alpha.
+    pub futility: bool,
+    /// Cut near the leaves when the static eval minus a margin still beats beta.
+    pub reverse_futility: bool,
+    /// Drop straight into quiescence one ply from the leaves when the static eval is far
+    /// below alpha.
+    pub razoring: bool,
+    /// Let the opponent move twice in a row; if that still fails high at reduced depth, cut.
+    pub null_move: bool,
+    /// Cut when a winning capture, searched shallow, beats beta by a margin.
+    pub probcut: bool,
+    /// Search late quiet moves a ply shallower first, and at full depth only if they beat alpha.
+    pub late_move_reductions: bool,
+    /// Try the moves at each node best guess first; off, they are searched as generated.
+    pub ordering: bool,
+}
+
+/// Outcome of the deepest fully completed iteration.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    /// None only when the side to move has no legal moves.
+    pub best_move: Option<Move>,
+    /// From the side to move's perspective.
+    pub score: Score,
+    pub depth: u32,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+}
+
+/// Progress reported after each completed iteration of the main thread.
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: u32,
+    /// The deepest ply reached, quiescence and extensions included.
+    pub seldepth: u32,
+    /// From the side to move's perspective.
+    pub score: Score,
+    /// Nodes searched so far by every thread.
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Move>,
+    /// Permille of the transposition table written during this search.
+    pub hashfull: u32,
+    /// Positions the main thread scored from the endgame tables.
+    pub tbhits: u64,
+}
+
+/// Receives a `SearchInfo` after each iteration; see `Search::set_info_callback`.
+pub type InfoCallback = Box<dyn FnMut(SearchInfo) + Send>;
+
+/// What outlives a single search: the hash table, the stop flag, and the thread count. Each
+/// search runs Lazy SMP, with helper threads searching the same root through the shared table.
+#[derive(Default)]
+pub struct Search {
+    helpers: usize,
+    options: SearchOptions,
+    on_info: Option<InfoCallback>,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    /// Nodes of the current search over all threads, each adding its count every
+    /// `CHECK_INTERVAL` nodes.
+    total_nodes: AtomicU64,
+    tt: TranspositionTable,
+    /// Clock kept back for lag; None keeps `DEFAULT_MOVE_OVERHEAD`.
+    move_overhead: Option<Duration>,
+    strength: Strength,
+    /// Xorshift state behind the weakened pick, zero until the first one.
+    skill_seed: u64,
+    #[cfg(feature = "syzygy")]
+    tablebase: Option<Arc<Tablebase>>,
+    #[cfg(feature = "trace")]
+    trace_plies: u32,
+    #[cfg(feature = "trace")]
+    last_trace: Option<SearchTrace>,
+}
+
+/// The state one search thread owns for one search. Only the main thread has a clock, a node
+/// limit, and the info callback; helpers run until the main thread raises the stop flag.
+struct Worker<'a> {
+    limits: SearchLimits,
+    options: SearchOptions,
+    time: Option<TimeManager>,
+    on_info: Option<&'a mut (dyn FnMut(SearchInfo) + Send)>,
+    stop: &'a AtomicBool,
+    ponder: &'a AtomicBool,
+    total_nodes: &'a AtomicU64,
+    tt: &'a TranspositionTable,
+    #[cfg(feature = "syzygy")]
+    tablebase: Option<&'a Tablebase>,
+    root_moves: &'a [Move],
+    nodes: u64,
+    /// The deepest ply the current iteration has reached.
+    seldepth: u32,
+    tb_hits: u64,
+    /// Nodes where ProbCut was tried, and of those, where it cut.
+    probcut_tries: u64,
+    probcut_cuts: u64,
+    stopped: bool,
+    /// The game's positions up to the root, which repetitions in the tree may reach back to.
+    history: History,
+    ordering: OrderingTables,
+    pawns: PawnTable,
+    material: MaterialTable,
+    /// Depth of the current iteration, which bounds how far extensions may reach.
+    root_depth: u32,
+    /// The path from the root, a ply per entry.
+    stack: [SearchStackEntry; MAX_DEPTH as usize + 1],
+    #[cfg(feature = "trace")]
+    trace: Tracer,
+    #[cfg(feature = "trace")]
+    last_trace: Option<SearchTrace>,
+}
+
+/// What the search knows about one ply of the current path: the node reached there, and the
+/// move it is searching, if any. The node fields are written on entering the node; the move
+/// fields by `make` and cleared again by `unmake`, so a pruned move leaves nothing behind.
+#[derive(Copy, Clone, Debug, Default)]
+struct SearchStackEntry {
+    key: u64,
+    /// None in check, where the static eval means nothing.
+    static_eval: Option<Score>,
+    in_check: bool,
+    mv: Option<Move>,
+    /// The piece `mv` moved, and where to.
+    moved: Option<PieceTo>,
+    /// Where `mv` captured, and what.
+    captured: Option<(Square, PieceType)>,
+    /// The move searched from here is a null move.
+    null_move: bool,
+    /// The move a singular verification search leaves out.
+    excluded: Option<Move>,
+}
+
+/// The moves the root searches, settled once before the first iteration: the legal moves,
+/// narrowed to `searchmoves` when any of those is legal, or else by the tables when they cover
+/// the root.
+struct RootMoves {
+    moves: Vec<Move>,
+    /// Root moves scored from the tables, zero unless they did the narrowing.
+    tb_hits: u64,
+}
+
+/// Move-ordering memory gathered during a search: the current node's TT move and the move
+/// that led to it, two killer moves per ply, a history score per side and from/to pair for
+/// quiet moves, a countermove reply to each piece and square moved to, and a continuation
+/// history scoring each quiet piece and square after the previous one.
+#[derive(Clone)]
+pub struct OrderingTables {
+    pub(crate) tt_move: Option<Move>,
+    pub(crate) previous: Option<PieceTo>,
+    killers: [[Option<Move>; 2]; MAX_DEPTH as usize],
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    countermoves: Box<[[Option<Move>; 64]; 12]>,
+    continuation: Box<[[[[i16; 64]; 12]; 64]; 12]>,
+}
+
+/// The piece a move moved and the square it landed on.
+pub(crate) type PieceTo = (Piece, Square);
+
+// ============================================================================
+// SearchLimits — Construction
+// ============================================================================
+
+impl SearchLimits {
+    /// Search exactly `depth` plies.
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits { depth: Some(depth), ..Self::default() }
+    }
+
+    /// Search until `nodes` positions have been visited.
+    pub fn nodes(nodes: u64) -> Self {
+        SearchLimits { nodes: Some(nodes), ..Self::default() }
+    }
+
+    /// Search for exactly this long.
+    pub fn movetime(movetime: Duration) -> Self {
+        SearchLimits { movetime: Some(movetime), ..Self::default() }
+    }
+
+    /// Search until the stop flag is raised.
+    pub fn infinite() -> Self {
+        SearchLimits { infinite: true, ..Self::default() }
+    }
+
+    /// Search until a mate in at most `moves` moves is found.
+    pub fn mate(moves: u32) -> Self {
+        SearchLimits { mate: Some(moves), ..Self::default() }
+    }
+
+    /// Search only these root moves.
+    pub fn searchmoves(moves: Vec<Move>) -> Self {
+        SearchLimits { searchmoves: moves, ..Self::default() }
+    }
+}
+
+// --- Combining --- //
+// Each adds a limit to those already set; whichever is reached first ends the search.
+impl SearchLimits {
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn with_nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_mate(mut self, moves: u32) -> Self {
+        self.mate = Some(moves);
+        self
+    }
+
+    pub fn with_movetime(mut self, movetime: Duration) -> Self {
+        self.movetime = Some(movetime);
+        self
+    }
+
+    pub fn with_searchmoves(mut self, moves: Vec<Move>) -> Self {
+        self.searchmoves = moves;
+        self
+    }
+}
+
+impl SearchOptions {
+    /// Every pruning technique off: the baseline the options are measured against. Moves are
+    /// still ordered, since none of the pruning pays without it.
+    pub const NONE: SearchOptions = SearchOptions {
+        futility: false, reverse_futility: false, razoring: false, null_move: false, probcut: false,
+        late_move_reductions: false, ordering: true,
+    };
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            futility: true, reverse_futility: true, razoring: true, null_move: true, probcut: true,
+            late_move_reductions: true, ordering: true,
+        }
+    }
+}
+
+// ============================================================================
+// SearchInfo
+// ============================================================================
+
+impl SearchInfo {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.nodes as f64 / self.elapsed.as_secs_f64().max(1e-3)) as u64
+    }
+}
+
+// ============================================================================
+// Search — Public Interface
+// ============================================================================
+
+impl Search {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the transposition table with an empty one of `megabytes`.
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt.resize(megabytes);
+    }
+
+    /// Forget everything learned in earlier searches, as for a new game.
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Search on `threads` threads in total, the calling one included. One thread keeps the
+    /// search deterministic.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.helpers = threads.max(1) - 1;
+    }
+
+    /// Keep this much of the clock back on every move for lag between engine and GUI.
+    pub fn set_move_overhead(&mut self, overhead: Duration) {
+        self.move_overhead = Some(overhead);
+    }
+
+    /// Enable or disable individual pruning techniques for later searches.
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+    }
+
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    /// Play at this strength in later searches; see `skill`.
+    pub fn set_strength(&mut self, strength: Strength) {
+        self.strength = strength;
+    }
+
+    pub fn strength(&self) -> Strength {
+        self.strength
+    }
+
+    /// Call `callback` with the depth, score, and line of every iteration the main thread
+    /// completes, as UCI `info` lines report them.
+    pub fn set_info_callback(&mut self, callback: impl FnMut(SearchInfo) + Send + 'static) {
+        self.on_info = Some(Box::new(callback));
+    }
+
+    /// Record the main thread's tree down to `plies` below the root in later searches; zero
+    /// turns recording off.
+    #[cfg(feature = "trace")]
+    pub fn set_trace_plies(&mut self, plies: u32) {
+        self.trace_plies = plies;
+    }
+
+    /// The tree of the last completed iteration of the latest traced search.
+    #[cfg(feature = "trace")]
+    pub fn last_trace(&self) -> Option<&SearchTrace> {
+        self.last_trace.as_ref()
+    }
+
+    /// Probe these tables at the root and in positions with few enough pieces, or stop probing.
+    #[cfg(feature = "syzygy")]
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<Tablebase>>) {
+        self.tablebase = tablebase;
+    }
+
+    /// Raising this flag from another thread ends a running search, which then reports the
+    /// last completed iteration. The search lowers it again before returning.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// While this flag is up the search ignores the clock. Raise it before a ponder search and
+    /// lower it from another thread on a ponder hit; the clock applies from then on.
+    pub fn ponder_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.ponder)
+    }
+
+    /// Deepen one ply at a time until a limit is hit, or until a mate within the `mate` limit is
+    /// found. An iteration cut short by the node limit, the clock, or the stop flag is
+    /// discarded in favour of the last completed one. With
+    /// helper threads, the deepest completed iteration of any thread is reported, the main
+    /// thread's on ties, and the node count covers all of them.
+    pub fn best_move(&mut self, state: &State, limits: SearchLimits) -> SearchResult {
+        let mut history = History::new();
+        history.push(state.hash());
+        self.search(state, &history, limits)
+    }
+
+    /// As `best_move` from the game's current position, with the positions played before it
+    /// counting as repetitions.
+    pub fn best_move_in(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        // Nothing before the last irreversible move can recur
+        let history = game.history().tail(game.current().halfmove_clock as usize + 1);
+        self.search(game.current(), &history, limits)
+    }
+
+    /// `history` ends with `state`, the root.
+    fn search(&mut self, state: &State, history: &History, limits: SearchLimits) -> SearchResult {
+        match self.strength.depth_limit() {
+            Some(depth) => self.search_weakened(state, history, limits, depth),
+            None => self.search_threads(state, history, limits),
+        }
+    }
+
+    /// Search no deeper than `depth`, then play the weakened pick among the root moves. Every
+    /// move is scored shallow first, and only the best few again to the depth reached. The
+    /// result keeps the search's line and score, with the picked move in front when it
+    /// differs. The scoring searches report no `info`.
+    fn search_weakened(&mut self, state: &State, history: &History, limits: SearchLimits, depth: u32) -> SearchResult {
+        let depth = limits.depth.map_or(depth, |limit| limit.min(depth));
+        let mut result = self.search_threads(state, history, SearchLimits { depth: Some(depth), ..limits.clone() });
+        let moves = RootMoves::new(state, &limits.searchmoves).moves;
+        let Some(best) = result.best_move else { return result };
+        if moves.len() < 2 { return result; }
+
+        let level = self.strength.level();
+        let mate = moves.iter().copied()
+            .find(|&mv| matches!(state.clone().apply_move(mv).status(), GameStatus::Checkmate(_)));
+        let mv = match mate {
+            Some(mate) if level >= MATE_IN_ONE_LEVEL => mate,
+            _ => {
+                let on_info = self.on_info.take();
+                let mut scored = self.score_root_moves(state, history, &moves, result.depth.min(SKILL_SHORTLIST_DEPTH));
+                if result.depth > SKILL_SHORTLIST_DEPTH {
+                    scored.sort_by_key(|&(_, score)| Reverse(score));
+                    let mut shortlist: Vec<Move> = scored.iter().take(CANDIDATES).map(|&(mv, _)| mv).collect();
+                    if !shortlist.contains(&best) { shortlist[CANDIDATES - 1] = best; }
+                    scored = self.score_root_moves(state, history, &shortlist, result.depth);
+                }
+                self.on_info = on_info;
+                if self.skill_seed == 0 { self.skill_seed = 0x9E37_79B9_7F4A_7C15; }
+                pick(&mut scored, level, &mut self.skill_seed)
+            }
+        };
+        if mv != best {
+            result.best_move = Some(mv);
+            result.pv = vec![mv];
+        }
+        result
+    }
+
+    /// Each of `moves` with its score from a search of it alone to `depth`.
+    fn score_root_moves(&mut self, state: &State, history: &History, moves: &[Move], depth: u32) -> Vec<(Move, Score)> {
+        let limits = SearchLimits::depth(depth.max(1));
+        moves.iter()
+            .map(|&mv| (mv, self.search_threads(state, history, limits.clone().with_searchmoves(vec![mv])).score))
+            .collect()
+    }
+
+    /// The full search, on every thread.
+    fn search_threads(&mut self, state: &State, history: &History, limits: SearchLimits) -> SearchResult {
+        let root_moves = RootMoves::new(state, &limits.searchmoves);
+        #[cfg(feature = "syzygy")]
+        let root_moves = match &self.tablebase {
+            Some(tablebase) if limits.searchmoves.is_empty() => root_moves.narrowed_by(tablebase, state),
+            _ => root_moves,
+        };
+
+        self.tt.new_search();
+        self.total_nodes.store(0, Ordering::Relaxed);
+        let time = TimeManager::new(&limits, state.to_move, self.move_overhead.unwrap_or(DEFAULT_MOVE_OVERHEAD));
+        let mut on_info = self.on_info.take();
+        let result = thread::scope(|scope| {
+            let helpers: Vec<_> = (1..=self.helpers)
+                .map(|id| {
+                    let mut worker = self.worker(SearchLimits { nodes: None, ..limits.clone() }, None, &root_moves.moves);
+                    scope.spawn(move || worker.iterate(state, history, id))
+                })
+                .collect();
+
+            let mut main = self.worker(limits, Some(time), &root_moves.moves);
+            main.tb_hits = root_moves.tb_hits;
+            main.on_info = on_info.as_deref_mut().map(|callback| callback as _);
+            #[cfg(feature = "trace")]
+            { main.trace = Tracer::new(self.trace_plies); }
+            let mut result = main.iterate(state, history, 0);
+            #[cfg(feature = "trace")]
+            let trace = main.last_trace.take();
+            // The main thread alone decides when the search is over
+            self.stop.store(true, Ordering::Relaxed);
+            for helper in helpers {
+                let helper = helper.join().expect("search thread panicked");
+                let nodes = result.nodes + helper.nodes;
+                if helper.depth > result.depth { result = helper; }
+                result.nodes = nodes;
+            }
+            #[cfg(feature = "trace")]
+            return (result, trace);
+            #[cfg(not(feature = "trace"))]
+            result
+        });
+        #[cfg(feature = "trace")]
+        let result = {
+            let (result, trace) = result;
+            self.last_trace = trace;
+            result
+        };
+
+        self.on_info = on_info;
+        self.stop.store(false, Ordering::Relaxed);
+        result
+    }
+
+    fn worker<'a>(&'a self, limits: SearchLimits, time: Option<TimeManager>, root_moves: &'a [Move]) -> Worker<'a> {
+        Worker {
+            limits,
+            options: self.options,
+            time,
+            on_info: None,
+            stop: &self.stop,
+            ponder: &self.ponder,
+            total_nodes: &self.total_nodes,
+            tt: &self.tt,
+            #[cfg(feature = "syzygy")]
+            tablebase: self.tablebase.as_deref(),
+            root_moves,
+            nodes: 0,
+            seldepth: 0,
+            tb_hits: 0,
+            probcut_tries: 0,
+            probcut_cuts: 0,
+            stopped: false,
+            history: History::new(),
+            ordering: OrderingTables::default(),
+            pawns: PawnTable::new(),
+            material: MaterialTable::new(),
+            root_depth: 0,
+            stack: [SearchStackEntry::default(); MAX_DEPTH as usize + 1],
+            #[cfg(feature = "trace")]
+            trace: Tracer::default(),
+            #[cfg(feature = "trace")]
+            last_trace: None,
+        }
+    }
+}
+
+// ============================================================================
+// RootMoves
+// ============================================================================
+
+impl RootMoves {
+    fn new(state: &State, searchmoves: &[Move]) -> Self {
+        let moves: Vec<Move> = state.moves().all().collect();
+        let allowed: Vec<Move> = moves.iter().copied().filter(|mv| searchmoves.contains(mv)).collect();
+        RootMoves { moves: if allowed.is_empty() { moves } else { allowed }, tb_hits: 0 }
+    }
+}
+
+// ============================================================================
+// Worker — Iterative Deepening
+// ============================================================================
+
+impl Worker<'_> {
+    /// Search `root` one depth after another until stopped. Odd-numbered helpers start a ply
+    /// deeper, so that threads spread over different depths instead of duplicating each other.
+    fn iterate(&mut self, root: &State, history: &History, id: usize) -> SearchResult {
+        self.history = history.clone();
+        let mut state = root.clone();
+        let mut result = SearchResult {
+            best_move: self.root_moves.first().copied(),
+            score: Score::ZERO,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        };
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH);
+        for depth in 1 + (id % 2) as u32..=max_depth {
+            let mut pv = Vec::new();
+            self.root_depth = depth;
+            self.seldepth = 0;
+            let score = self.negamax(&mut state, depth, 0, -Score::INFINITY, Score::INFINITY, &mut pv, false);
+            if self.stopped { break; }
+            #[cfg(feature = "trace")]
+            { self.last_trace = self.trace.take(); }
+            self.extend_pv(&mut state, &mut pv, depth);
+            if let Some(on_info) = &mut self.on_info {
+                let elapsed = self.time.map_or(Duration::ZERO, |time| time.elapsed());
+                // Helpers' counts lag by up to `CHECK_INTERVAL` nodes each; this thread's is exact
+                let nodes = self.total_nodes.load(Ordering::Relaxed) + self.nodes % CHECK_INTERVAL;
+                // A hash cutoff stands for a search at least as deep as the one it cut
+                let seldepth = self.seldepth.max(depth);
+                on_info(SearchInfo {
+                    depth, seldepth, score, nodes, elapsed, pv: pv.clone(), hashfull: self.tt.hashfull(),
+                    tbhits: self.tb_hits,
+                });
+            }
+            result = SearchResult { best_move: pv.first().copied(), score, depth, nodes: self.nodes, pv };
+            if self.clock().is_some_and(|time| time.soft_expired()) { break; }
+            let mates_within = |moves: u32| score.mate_moves().is_some_and(|found| found > 0 && found <= moves as i32);
+            if self.limits.mate.is_some_and(mates_within) { break; }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// A hash cutoff below the root returns no line, so finish a short PV by following the
+    /// table's best moves, as long as they are legal and do not repeat a position.
+    fn extend_pv(&self, root: &mut State, pv: &mut Vec<Move>, depth: u32) {
+        let mut undos = Vec::new();
+        let mut seen = vec![root.hash()];
+        for &mv in pv.iter() {
+            undos.push(root.make_move(mv));
+            seen.push(root.hash());
+        }
+        while pv.len() < depth as usize {
+            let Some(mv) = self.tt.probe(root.hash(), 0).and_then(|entry| entry.best_move) else { break };
+            if !root.is_legal_move(mv) { break; }
+            undos.push(root.make_move(mv));
+            pv.push(mv);
+            if seen.contains(&root.hash()) { break; }
+            seen.push(root.hash());
+        }
+        undos.into_iter().rev().for_each(|undo| root.unmake_move(undo));
+    }
+}
+
+// ============================================================================
+// Bench
+// ============================================================================
+
+/// Depth `bench` searches each position to when none is given.
+pub const BENCH_DEPTH: u32 = 7;
+
+/// Openings, middlegames, endgames, and positions in check, searched by `bench`.
+const BENCH_POSITIONS: [&str; 20] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "rnbqkb1r/pp1p1ppp/4pn2/2p5/2PP4/2N5/PP2PPPP/R1BQKBNR w KQkq - 0 4",
+    "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+    "rnbqkb1r/ppp1pppp/5n2/3p4/3P1B2/5N2/PPP1PPPP/RN1QKB1R b KQkq - 3 3",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP1BBPPP/R2QK2R w KQ - 3 8",
+    "2rq1rk1/pp1bppbp/3p1np1/8/2BNP3/2N1B3/PPPQ2PP/2KR3R b - - 6 12",
+    "r2q1rk1/1b1nbppp/p2ppn2/1p6/3NPP2/1BN1B3/PPP1Q1PP/2KR3R w - - 2 12",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "3r1rk1/p1q2ppp/1pn1pn2/2b5/2P5/P1N1BN2/1PQ2PPP/3R1RK1 w - - 4 16",
+    "r1b2rk1/2q1b1pp/p2ppn2/1p6/3QP3/1BN1B3/PPP3PP/R4RK1 w - - 0 14",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/8/1p1k4/p1p5/P1P2K2/1P6/8/8 w - - 0 1",
+    "8/5k2/8/2Q5/8/8/5K2/3q4 w - - 0 1",
+    "6k1/4Rppp/8/8/8/8/5PPP/6K1 b - - 0 1",
+    "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4",
+    "rnb1kbnr/pppp1ppp/8/4p3/5PPq/8/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+    "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+];
+
+/// Totals from a `bench` run. `nodes` is the signature: any change to search behaviour
+/// changes it, while pure speed-ups do not.
+#[derive(Copy, Clone, Debug)]
+pub struct BenchReport {
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchReport {
+    pub fn nodes_per_second(&self) -> u64 {
+        (self.nodes as f64 / self.elapsed.as_secs_f64().max(1e-9)) as u64
+    }
+}
+
+/// Search every bench position to `depth` from an empty hash table and total the nodes.
+pub fn bench(depth: u32) -> BenchReport {
+    let mut search = Search::new();
+    let start = Stopwatch::start();
+    let nodes = BENCH_POSITIONS.iter()
+        .map(|fen| {
+            let state = State::from_fen(fen).expect("bench positions are valid FEN");
+            search.clear_hash();
+            search.best_move(&state, SearchLimits::depth(depth)).nodes
+        })
+        .sum();
+    BenchReport { nodes, elapsed: start.elapsed() }
+}
+
+// ============================================================================
+// Worker — Alpha-Beta
+// ============================================================================
+
+impl Worker<'_> {
+    /// Fail-hard negamax. `pv` receives the best line found below this node. `allow_null` is
+    /// false directly below a null move, so two passes never follow each other.
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    fn negamax(
+        &mut self, state: &mut State, depth: u32, ply: u32, alpha: Score, beta: Score, pv: &mut Vec<Move>,
+        allow_null: bool,
+    ) -> Score {
+        #[cfg(feature = "trace")]
+        self.trace.enter(ply, depth, alpha, beta);
+        let score = self.search_node(state, depth, ply, alpha, beta, pv, allow_null);
+        #[cfg(feature = "trace")]
+        self.trace.exit(ply, score);
+        score
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_node(
+        &mut self, state: &mut State, depth: u32, ply: u32, mut alpha: Score, beta: Score, pv: &mut Vec<Move>,
+        allow_null: bool,
+    ) -> Score {
+        if self.count_node(ply) { return Score::ZERO; }
+        self.stack[ply as usize].key = state.hash();
+        if ply > 0 && self.is_draw(state, ply) { return Score::ZERO; }
+        if depth == 0 { return self.quiesce(state, ply, alpha, beta); }
+        #[cfg(feature = "syzygy")]
+        if ply > 0 && let Some(score) = self.probe_wdl(state, ply) {
+            self.tt.store(state.hash(), MAX_DEPTH, score, Bound::Exact, None, ply);
+            return score;
+        }
+
+        // A shallow entry cannot cut, but its move is still the best guess to try first. A
+        // singular verification search shares the hash of the node it verifies, so it leaves
+        // the table alone.
+        let excluded = self.stack[ply as usize].excluded;
+        let tt_entry = if excluded.is_none() { self.tt.probe(state.hash(), ply) } else { None };
+        if let Some(entry) = tt_entry {
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            // The root always searches so that it produces a move and a line
+            if ply > 0 && entry.depth >= depth && usable { return entry.score; }
+        }
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+
+        let in_check = state.in_check();
+        let static_eval = self.enter(state, ply, in_check);
+        let improving = self.improving(ply);
+
+        // Null move: if passing still fails high at reduced depth, a real move would too.
+        // Zugzwang makes this unsound without pieces, so pawn endgames are excluded.
+        if allow_null && self.options.null_move && ply > 0 && !in_check && depth > NULL_MOVE_REDUCTION
+            && !(beta.is_mate() && beta > Score::ZERO) && has_non_pawn_material(state) && excluded.is_none()
+        {
+            let undo = state.make_null_move();
+            self.stack[ply as usize].null_move = true;
+            let score = -self.negamax(
+                state, depth - 1 - NULL_MOVE_REDUCTION, ply + 1, -beta, -beta + 1, &mut Vec::new(), false,
+            );
+            self.stack[ply as usize].null_move = false;
+            state.unmake_null_move(undo);
+
+            if self.stopped { return Score::ZERO; }
+            if score >= beta { return beta; }
+        }
+
+        // Shallow pruning trusts the static eval, which means nothing in check or once a mate
+        // score is in the window
+        let prunable = ply > 0 && !in_check && excluded.is_none() && depth <= FUTILITY_MAX_DEPTH
+            && !alpha.is_mate() && !beta.is_mate();
+        let static_eval = static_eval.unwrap_or(Score::ZERO);
+        if prunable && self.options.reverse_futility && reverse_futility_cuts(state, depth, static_eval, beta, improving) {
+            return beta;
+        }
+        // Razoring: hopeless enough that quiescence decides. One ply from the leaves its score
+        // stands; deeper, only a confirmed fail-low prunes.
+        if prunable && self.options.razoring && razoring_applies(state, depth, static_eval, alpha) {
+            if depth == 1 { return self.quiesce(state, ply, alpha, beta); }
+            let score = self.quiesce(state, ply, alpha - 1, alpha);
+            if self.stopped { return Score::ZERO; }
+            if score < alpha { return alpha; }
+        }
+        // ProbCut: a winning capture that beats beta by a margin even searched shallow would
+        // almost surely fail high at full depth. A PV node is searched with an open window, so
+        // only null-window nodes qualify.
+        let pv_node = beta.raw() - alpha.raw() > 1;
+        if self.options.probcut && ply > 0 && !pv_node && !in_check && depth >= PROBCUT_MIN_DEPTH
+            && excluded.is_none() && !beta.is_mate() && self.probcut(state, depth, ply, beta)
+        {
+            return beta;
+        }
+        if self.stopped { return Score::ZERO; }
+
+        let futile = prunable && self.options.futility && futility_prunes(depth, static_eval, alpha, improving);
+
+        let mut moves: Vec<Move> = if ply == 0 { self.root_moves.to_vec() } else { state.moves().all().collect() };
+        if moves.is_empty() {
+            return if in_check { Score::mated_in(ply) } else { Score::ZERO };
+        }
+        if let Some(excluded) = excluded {
+            moves.retain(|&mv| mv != excluded);
+            // The left-out move was the only one, which makes it singular
+            if moves.is_empty() { return alpha; }
+        }
+        let previous = if ply > 0 { self.stack[ply as usize - 1].moved } else { None };
+        self.ordering.tt_move = tt_move;
+        self.ordering.previous = previous;
+        if self.options.ordering { moves.sort_by_cached_key(|&mv| -score_move(state, mv, ply, &self.ordering)); }
+
+        let singular = match tt_entry {
+            Some(entry) => ply > 0 && self.is_singular(state, entry, depth, ply),
+            None => false,
+        };
+        if self.stopped { return Score::ZERO; }
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+        let mut quiets_tried = Vec::new();
+        for (index, mv) in moves.into_iter().enumerate() {
+            let mut line = Vec::new();
+            let capture = is_capture(state, mv);
+            let quiet = !capture && mv.move_type() != MoveType::Promotion;
+            let check = state.gives_check(mv);
+
+            // Extend forcing moves a ply: checks, recaptures that even out the trade just made,
+            // and a singular TT move
+            let recapture = ply > 0 && self.is_recapture(state, mv, ply);
+            let extend = (check || recapture || (singular && tt_move == Some(mv))) && self.can_extend(ply, depth);
+            let new_depth = depth - 1 + extend as u32;
+
+            // Futility: this quiet move is not expected to lift the eval to alpha. The first move
+            // is always searched.
+            if futile && index > 0 && quiet && !check { continue; }
+
+            let undo = self.make(state, mv, ply);
+
+            // Late move reduction: a late quiet move is probably bad, so prove it cheaply with a
+            // shallower null-window search and only pay for full depth if it beats alpha
+            let reduce = self.options.late_move_reductions && index >= LMR_MIN_INDEX && depth >= 3 && quiet && !in_check && !check;
+            let mut score = alpha + 1;
+            if reduce {
+                #[cfg(feature = "trace")]
+                self.trace.next_move(Some(mv));
+                score = -self.negamax(state, depth - 2, ply + 1, -alpha - 1, -alpha, &mut line, true);
+            }
+            if score > alpha && !self.stopped {
+                line.clear();
+                #[cfg(feature = "trace")]
+                self.trace.next_move(Some(mv));
+                score = -self.negamax(state, new_depth, ply + 1, -beta, -alpha, &mut line, true);
+            }
+
+            self.unmake(state, undo, ply);
+
+            if self.stopped { return Score::ZERO; }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.extend(line);
+                if alpha >= beta {
+                    if !capture { self.ordering.record_cutoff(state, mv, &quiets_tried, previous, depth, ply); }
+                    break;
+                }
+            }
+            if quiet { quiets_tried.push(mv); }
+        }
+
+        if excluded.is_some() { return alpha; }
+        let bound = if alpha >= beta { Bound::Lower } else if alpha > original_alpha { Bound::Exact } else { Bound::Upper };
+        self.tt.store(state.hash(), depth, alpha, bound, best_move, ply);
+        alpha
+    }
+
+    /// Resolve captures and promotions past the horizon so the static eval is never taken in the
+    /// middle of an exchange. When in check every evasion is searched instead, since standing
+    /// pat is not an option.
+    fn quiesce(&mut self, state: &mut State, ply: u32, mut alpha: Score, beta: Score) -> Score {
+        if self.count_node(ply) { return Score::ZERO; }
+
+        let in_check = state.in_check();
+        let stand_pat = Score::cp(evaluate_cached(state, &mut self.pawns, &mut self.material));
+        let mut moves: Vec<Move> = if in_check {
+            state.moves().all().collect()
+        } else {
+            if stand_pat >= beta { return beta; }
+            alpha = alpha.max(stand_pat);
+            let promotions = state.moves().quiets()
+                .filter(|mv| mv.move_type() == MoveType::Promotion && mv.promoted_type() == PieceType::Queen);
+            state.moves().captures().chain(promotions).collect()
+        };
+        moves.sort_by_cached_key(|&mv| -mvv_lva(state, mv));
+        if in_check && moves.is_empty() { return Score::mated_in(ply); }
+
+        for mv in moves {
+            // Delta pruning: even winning the victim for free would not reach alpha
+            if !in_check
+                && mv.move_type() != MoveType::Promotion
+                && stand_pat + victim(state, mv).map_or(0, piece_value) + DELTA_MARGIN < alpha
+            {
+                continue;
+            }
+            // A capture that loses material once the exchange plays out cannot raise alpha
+            if !in_check && see(state, mv) < 0 { continue; }
+
+            let undo = state.make_move(mv);
+            let score = -self.quiesce(state, ply + 1, -beta, -alpha);
+            state.unmake_move(undo);
+
+            if self.stopped { return Score::ZERO; }
+            if score > alpha {
+                alpha = score;
+                if alpha >= beta { break; }
+            }
+        }
+        alpha
+    }
+
+    /// Whether some capture holds `beta + PROBCUT_MARGIN` in a null-window search
+    /// `PROBCUT_REDUCTION` plies shallower than this node's. Only captures whose SEE gain alone
+    /// could lift the static eval that far are tried, and never one of the piece that just
+    /// moved: that may be a sacrifice, and whether it works is for the full search to find out.
+    fn probcut(&mut self, state: &mut State, depth: u32, ply: u32, beta: Score) -> bool {
+        self.probcut_tries += 1;
+        let probcut_beta = beta + PROBCUT_MARGIN;
+        let needed = (probcut_beta.raw() - evaluate_cached(state, &mut self.pawns, &mut self.material)).max(1);
+        let offered = self.stack[ply as usize - 1].moved.map(|(_, square)| square);
+        let mut captures: Vec<Move> = state.moves().captures()
+            .filter(|&mv| Some(mv.target()) != offered && see(state, mv) >= needed)
+            .collect();
+        captures.sort_by_cached_key(|&mv| -mvv_lva(state, mv));
+
+        for mv in captures {
+            let undo = self.make(state, mv, ply);
+            let score = -self.negamax(
+                state, depth - PROBCUT_REDUCTION, ply + 1, -probcut_beta, -probcut_beta + 1, &mut Vec::new(), true,
+            );
+            self.unmake(state, undo, ply);
+
+            if self.stopped { return false; }
+            if score >= probcut_beta {
+                self.probcut_cuts += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether the TT move is singular: every other move, searched at about half depth, fails
+    /// low against a window set below the stored score. The entry must be deep enough and at
+    /// least a lower bound to be trusted.
+    fn is_singular(&mut self, state: &mut State, entry: TtEntry, depth: u32, ply: u32) -> bool {
+        if depth < SINGULAR_MIN_DEPTH || entry.depth + 3 < depth || entry.bound == Bound::Upper
+            || entry.score.is_mate() || !self.can_extend(ply, depth)
+        {
+            return false;
+        }
+        let Some(tt_move) = entry.best_move else { return false };
+
+        let singular_beta = entry.score - SINGULAR_MARGIN * depth as i32;
+        self.stack[ply as usize].excluded = Some(tt_move);
+        let score = self.negamax(state, (depth - 1) / 2, ply, singular_beta - 1, singular_beta, &mut Vec::new(), false);
+        self.stack[ply as usize].excluded = None;
+        score < singular_beta
+    }
+
+    /// Whether `mv` takes back on the square the last move captured on, winning about what
+    /// that capture won. Recaptures that come out ahead or behind are not forced replies.
+    fn is_recapture(&self, state: &State, mv: Move, ply: u32) -> bool {
+        let Some((square, taken)) = self.stack[ply as usize - 1].captured else { return false };
+        let Some(victim) = victim(state, mv) else { return false };
+        mv.target() == square && (piece_value(victim) - piece_value(taken)).abs() <= RECAPTURE_MARGIN
+    }
+
+    /// Whether a move at this node may be extended. Extensions may at most double the depth
+    /// of the iteration along any line, and never pass the ply limit.
+    fn can_extend(&self, ply: u32, depth: u32) -> bool {
+        ply + depth < 2 * self.root_depth.min(MAX_DEPTH / 2)
+    }
+
+    /// Count a node at `ply` and report whether the search must abort. The node limit is exact;
+    /// the stop flag and clock are polled every `CHECK_INTERVAL` nodes.
+    fn count_node(&mut self, ply: u32) -> bool {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        if self.limits.nodes.is_some_and(|limit| self.nodes >= limit) { self.stopped = true; }
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.total_nodes.fetch_add(CHECK_INTERVAL, Ordering::Relaxed);
+            if self.stop.load(Ordering::Relaxed) || self.clock().is_some_and(|time| time.hard_expired()) {
+                self.stopped = true;
+            }
+        }
+        self.stopped
+    }
+
+    /// The time manager, unless there is none or the search is still pondering.
+    fn clock(&self) -> Option<TimeManager> {
+        self.time.filter(|_| !self.ponder.load(Ordering::Relaxed))
+    }
+
+    /// Fifty-move and repetition draws. Mate on the hundredth halfmove is not distinguished.
+    fn is_draw(&self, state: &State, ply: u32) -> bool {
+        state.halfmove_clock >= 100 || self.is_repetition(state, ply)
+    }
+
+    /// Whether the node at `ply` repeats a position earlier on the path, or past the root in
+    /// the game. Only positions since the last irreversible move can match, and none before a
+    /// null move, which the clock does not see.
+    fn is_repetition(&self, state: &State, ply: u32) -> bool {
+        let (ply, reach) = (ply as usize, state.halfmove_clock as usize);
+        let key = self.stack[ply].key;
+        for back in 1..=reach.min(ply) {
+            let entry = &self.stack[ply - back];
+            if entry.null_move { return false; }
+            if back % 2 == 0 && entry.key == key { return true; }
+        }
+        reach > ply && self.history.occurs_within(key, reach - ply + 1)
+    }
+}
+
+// ============================================================================
+// Worker — Search Stack
+// ============================================================================
+
+impl Worker<'_> {
+    /// Record the node at `ply`, evaluating it unless in check, and return its static eval.
+    fn enter(&mut self, state: &State, ply: u32, in_check: bool) -> Option<Score> {
+        let static_eval = (!in_check).then(|| Score::cp(evaluate_cached(state, &mut self.pawns, &mut self.material)));
+        let entry = &mut self.stack[ply as usize];
+        entry.static_eval = static_eval;
+        entry.in_check = in_check;
+        static_eval
+    }
+
+    /// Whether the static eval at `ply` beats the one two plies earlier, or four when the side
+    /// to move was in check two plies earlier. In check it is not; with nothing to compare
+    /// against it is, which keeps the margins cautious.
+    fn improving(&self, ply: u32) -> bool {
+        let ply = ply as usize;
+        let Some(eval) = self.stack[ply].static_eval else { return false };
+        let earlier = [2, 4].into_iter().filter(|&back| back <= ply).find_map(|back| self.stack[ply - back].static_eval);
+        earlier.is_none_or(|earlier| eval > earlier)
+    }
+
+    /// Play `mv` from the node at `ply`, recording it on the stack.
+    fn make(&mut self, state: &mut State, mv: Move, ply: u32) -> Undo {
+        let entry = &mut self.stack[ply as usize];
+        entry.mv = Some(mv);
+        entry.captured = victim(state, mv).map(|victim| (mv.target(), victim));
+        entry.moved = state.board[mv.source()].map(|piece| (piece, mv.destination()));
+        state.make_move(mv)
+    }
+
+    /// Take back the move `make` played from `ply`, clearing it from the stack.
+    fn unmake(&mut self, state: &mut State, undo: Undo, ply: u32) {
+        state.unmake_move(undo);
+        let entry = &mut self.stack[ply as usize];
+        entry.mv = None;
+        entry.captured = None;
+        entry.moved = None;
+    }
+}
+
+// ============================================================================
+// Tablebases
+// ============================================================================
+
+#[cfg(feature = "syzygy")]
+impl RootMoves {
+    /// Only the moves that hold the tables' best outcome, and of winning ones only those
+    /// closest to the next capture or pawn move: searching on WDL scores alone could drift
+    /// until the fifty-move rule takes the win away. The search then chooses among the rest.
+    fn narrowed_by(self, tablebase: &Tablebase, state: &State) -> Self {
+        let Some(probed) = tablebase.probe_root_moves(state) else { return self };
+        let Some(&(_, best_wdl, best_dtz)) = probed.iter().max_by_key(|&&(_, wdl, dtz)| (wdl, -dtz.0)) else {
+            return self;
+        };
+        RootMoves {
+            tb_hits: probed.len() as u64,
+            moves: probed.into_iter()
+                .filter(|&(_, wdl, dtz)| wdl == best_wdl && (best_wdl != Wdl::Win || dtz == best_dtz))
+                .map(|(mv, _, _)| mv)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "syzygy")]
+impl Worker<'_> {
+    /// Score an interior position from the tables. Only positions right after a capture or pawn
+    /// move are probed, where the WDL tables alone are exact.
+    fn probe_wdl(&mut self, state: &State, ply: u32) -> Option<Score> {
+        if state.halfmove_clock != 0 { return None; }
+        let wdl = self.tablebase?.probe_wdl(state)?;
+        self.tb_hits += 1;
+        Some(wdl_score(wdl, ply))
+    }
+}
+
+/// Wins sooner score higher, as with mates. Cursed wins and blessed losses are draws, since
+/// the fifty-move rule will end them first.
+#[cfg(feature = "syzygy")]
+fn wdl_score(wdl: Wdl, ply: u32) -> Score {
+    match wdl {
+        Wdl::Win => Score::cp(TB_WIN_SCORE - ply as i32),
+        Wdl::Loss => Score::cp(-TB_WIN_SCORE + ply as i32),
+        Wdl::CursedWin | Wdl::BlessedLoss | Wdl::Draw => Score::ZERO,
+    }
+}
+
+// ============================================================================
+// Move Ordering
+// ============================================================================
+
+const TT_MOVE_SCORE: i32 = 1_000_000;
+const CAPTURE_SCORE: i32 = 100_000;
+const PROMOTION_SCORE: i32 = 90_000;
+const KILLER_SCORES: [i32; 2] = [80_000, 79_000];
+const COUNTERMOVE_SCORE: i32 = 78_000;
+const LOSING_CAPTURE_SCORE: i32 = 60_000;
+
+/// History scores are halved once any reaches this, keeping quiets below the killers.
+const HISTORY_LIMIT: i32 = 50_000;
+
+/// Continuation history scores stay within this of zero. With the history limit it keeps
+/// every quiet below the losing captures.
+const CONTINUATION_LIMIT: i32 = 8_192;
+
+/// Ordering key, highest first: the TT move, captures that do not lose material by MVV-LVA,
+/// quiet queen promotions, killers, the countermove, captures that lose material, then quiets
+/// by history plus continuation history.
+pub fn score_move(state: &State, mv: Move, ply: u32, tables: &OrderingTables) -> i32 {
+    if tables.tt_move == Some(mv) { return TT_MOVE_SCORE; }
+    if is_capture(state, mv) {
+        let tier = if see(state, mv) >= 0 { CAPTURE_SCORE } else { LOSING_CAPTURE_SCORE };
+        return tier + mvv_lva(state, mv);
+    }
+    if mv.move_type() == MoveType::Promotion && mv.promoted_type() == PieceType::Queen {
+        return PROMOTION_SCORE;
+    }
+
+    let killers = &tables.killers[(ply as usize).min(MAX_DEPTH as usize - 1)];
+    if let Some(slot) = killers.iter().position(|&killer| killer == Some(mv)) {
+        return KILLER_SCORES[slot];
+    }
+
+    let history = tables.history[state.to_move as usize][mv.source().index()][mv.target().index()];
+    let Some((previous_piece, previous_to)) = tables.previous else { return history };
+    if tables.countermoves[piece_index(previous_piece)][previous_to.index()] == Some(mv) {
+        return COUNTERMOVE_SCORE;
+    }
+    let continuation = state.board[mv.source()].map_or(0, |piece| {
+        tables.continuation[piece_index(previous_piece)][previous_to.index()][piece_index(piece)][mv.destination().index()]
+    });
+    history + continuation as i32
+}
+
+impl OrderingTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a quiet move that caused a beta cutoff after `previous`, and count against the
+    /// quiets tried before it.
+    fn record_cutoff(
+        &mut self, state: &State, mv: Move, quiets_tried: &[Move], previous: Option<PieceTo>, depth: u32, ply: u32,
+    ) {
+        let killers = &mut self.killers[(ply as usize).min(MAX_DEPTH as usize - 1)];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        let bonus = (depth * depth) as i32;
+        let side = &mut self.history[state.to_move as usize];
+        let entry = &mut side[mv.source().index()][mv.target().index()];
+        *entry += bonus;
+        if *entry >= HISTORY_LIMIT {
+            side.iter_mut().flatten().for_each(|score| *score /= 2);
+        }
+
+        let Some((previous_piece, previous_to)) = previous else { return };
+        self.countermoves[piece_index(previous_piece)][previous_to.index()] = Some(mv);
+        let followups = &mut self.continuation[piece_index(previous_piece)][previous_to.index()];
+        let bonus = bonus.min(CONTINUATION_LIMIT);
+        for (tried, bonus) in quiets_tried.iter().map(|&tried| (tried, -bonus)).chain([(mv, bonus)]) {
+            let Some(piece) = state.board[tried.source()] else { continue };
+            apply_gravity(&mut followups[piece_index(piece)][tried.destination().index()], bonus);
+        }
+    }
+}
+
+/// History gravity: move `entry` by `bonus`, less the more it already leans that way, so that
+/// it approaches but never passes `CONTINUATION_LIMIT` either side of zero.
+fn apply_gravity(entry: &mut i16, bonus: i32) {
+    let current = *entry as i32;
+    let updated = current + bonus - current * bonus.abs() / CONTINUATION_LIMIT;
+    *entry = updated.clamp(-CONTINUATION_LIMIT, CONTINUATION_LIMIT) as i16;
+}
+
+/// Index of a piece among the twelve, White's first.
+fn piece_index(piece: Piece) -> usize {
+    piece.color() as usize * 6 + piece.piece_type() as usize
+}
+
+impl Default for OrderingTables {
+    fn default() -> Self {
+        OrderingTables {
+            tt_move: None,
+            previous: None,
+            killers: [[None; 2]; MAX_DEPTH as usize],
+            history: Box::new([[[0; 64]; 64]; 2]),
+            countermoves: Box::new([[None; 64]; 12]),
+            continuation: vec![[[[0; 64]; 12]; 64]; 12].into_boxed_slice().try_into().expect("twelve pieces"),
+        }
+    }
+}
+
+/// Most valuable victim first, and among equal victims the least valuable attacker.
+fn mvv_lva(state: &State, mv: Move) -> i32 {
+    let attacker = state.board[mv.source()].map_or(0, |p| attacker_rank(p.piece_type()));
+    victim(state, mv).map_or(0, |v| piece_value(v) * 10 - attacker)
+}
+
+fn attacker_rank(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn   => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook   => 4,
+        PieceType::Queen  => 5,
+        PieceType::King   => 6,
+    }
+}
+
+fn victim(state: &State, mv: Move) -> Option<PieceType> {
+    match mv.move_type() {
+        MoveType::EnPassant => Some(PieceType::Pawn),
+        MoveType::Castling => None,
+        _ => state.board[mv.target()].map(|p| p.piece_type()),
+    }
+}
+
+fn is_capture(state: &State, mv: Move) -> bool {
+    victim(state, mv).is_some()
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+/// Whether the static eval is so far below alpha that no quiet move is expected to reach it. An
+/// eval on the rise may yet get there, so it is given more room.
+fn futility_prunes(depth: u32, static_eval: Score, alpha: Score, improving: bool) -> bool {
+    let margin = FUTILITY_MARGINS[depth as usize] + if improving { IMPROVING_MARGIN } else { 0 };
+    !alpha.is_mate() && static_eval + margin <= alpha
+}
+
+/// Whether the static eval beats beta by so much that even a free move for the opponent would
+/// not bring it down, the margin shrinking while the eval is improving. With only king and
+/// pawns the free move can be what loses, so zugzwang positions are left to the search, as
+/// are mate-score windows.
+fn reverse_futility_cuts(state: &State, depth: u32, static_eval: Score, beta: Score, improving: bool) -> bool {
+    let margin = REVERSE_FUTILITY_MARGINS[depth as usize] - if improving { IMPROVING_MARGIN } else { 0 };
+    !beta.is_mate() && has_non_pawn_material(state) && static_eval - margin >= beta
+}
+
+/// Whether the static eval is so far below alpha that only a capture could save the node. In
+/// pawn endings a quiet pawn push often does instead, and mate-score windows are not compared
+/// against an eval at all.
+fn razoring_applies(state: &State, depth: u32, static_eval: Score, alpha: Score) -> bool {
+    !alpha.is_mate() && has_non_pawn_material(state) && static_eval + RAZOR_MARGINS[depth as usize] < alpha
+}
+
+fn has_non_pawn_material(state: &State) -> bool {
+    let (board, color) = (&state.board, state.to_move);
+    board.occupancy(color) != board.pieces_of(color, PieceType::Pawn) | board.pieces_of(color, PieceType::King)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Win At Chess positions and their solutions, in UCI, which every pruning change must
+    /// still find at `TACTICS_DEPTH`.
+    const TACTICS: [(&str, &str); 9] = [
+        ("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1", "g3g6"),
+        ("5rk1/1ppb3p/p1pb4/6q1/3P1p1r/2P1R2P/PP1BQ1P1/5RKN w - - 0 1", "e3g3"),
+        ("r1bq2rk/pp3pbp/2p1p1pQ/7P/3P4/2PB1N2/PP3PPR/2KR4 w - - 0 1", "h6h7"),
+        ("5k2/6pp/p1qN4/1p1p4/3P4/2PKP2Q/PP3r2/3R4 b - - 0 1", "c6c4"),
+        ("7k/p7/1R5K/6r1/6p1/6P1/8/8 w - - 0 1", "b6b7"),
+        ("rnbqkb1r/pppp1ppp/8/4P3/6n1/7P/PPPNPPP1/R1BQKBNR b KQkq - 0 1", "g4e3"),
+        ("r4q1k/p2bR1rp/2p2Q1N/5p2/5p2/2P5/PP3PPP/R5K1 w - - 0 1", "e7f7"),
+        ("3q1rk1/p4pp1/2pb3p/3p4/6Pr/1PNQ4/P1PB1PP1/4RRK1 b - - 0 1", "d6h2"),
+        ("2br2k1/2q3rn/p2NppQ1/2p1P3/Pp5R/4P3/1P3PPP/3R2K1 w - - 0 1", "h4h7"),
+    ];
+
+    const TACTICS_DEPTH: u32 = 7;
+
+    /// A node budget per `TACTICS` position: a fixed movetime, but the same on every machine.
+    const TACTICS_NODES: u64 = 1_000_000;
+
+    /// The `TACTICS` positions whose solution the search finds with the given options.
+    fn solved_with(options: SearchOptions) -> Vec<&'static str> {
+        solved_within(options, SearchLimits::depth(TACTICS_DEPTH))
+    }
+
+    fn solved_within(options: SearchOptions, limits: SearchLimits) -> Vec<&'static str> {
+        let mut search = Search::new();
+        search.set_options(options);
+        TACTICS.iter()
+            .filter(|(fen, solution)| {
+                search.clear_hash();
+                let result = search.best_move(&State::from_fen(fen).unwrap(), limits.clone());
+                result.best_move.map(|mv| mv.to_uci(false)).as_deref() == Some(*solution)
+            })
+            .map(|(fen, _)| *fen)
+            .collect()
+    }
+
+    /// Nodes and best moves over the bench positions at `depth` with the given options.
+    fn bench_with(options: SearchOptions, depth: u32) -> (u64, Vec<Option<Move>>) {
+        searched_with(options, &BENCH_POSITIONS, depth)
+    }
+
+    /// Nodes and best moves over `fens` at `depth` with the given options, each from an empty
+    /// hash table.
+    fn searched_with(options: SearchOptions, fens: &[&str], depth: u32) -> (u64, Vec<Option<Move>>) {
+        let mut search = Search::new();
+        search.set_options(options);
+        let results: Vec<SearchResult> = fens.iter()
+            .map(|fen| {
+                search.clear_hash();
+                search.best_move(&State::from_fen(fen).unwrap(), SearchLimits::depth(depth))
+            })
+            .collect();
+        (results.iter().map(|result| result.nodes).sum(), results.iter().map(|result| result.best_move).collect())
+    }
+
+    // --- Search Stack --- //
+
+    /// Walk `line` from `state` as the search would, recording each node on the stack. Returns
+    /// the position reached.
+    fn walk(worker: &mut Worker, mut state: State, line: &[&str]) -> State {
+        for (ply, text) in line.iter().enumerate() {
+            worker.stack[ply].key = state.hash();
+            worker.enter(&state, ply as u32, state.in_check());
+            let mv = Move::from_uci(text, &state).unwrap();
+            let _ = worker.make(&mut state, mv, ply as u32);
+        }
+        let ply = line.len();
+        worker.stack[ply].key = state.hash();
+        worker.enter(&state, ply as u32, state.in_check());
+        state
+    }
+
+    #[test]
+    fn improving_looks_two_plies_back_or_four_past_a_check() {
+        let search = Search::new();
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        // 3. Bb5+ puts Black in check at ply 3
+        walk(&mut worker, State::start(), &["e2e4", "d7d5", "f1b5", "c7c6", "b5a4", "g8f6"]);
+        let eval = |ply: usize| worker.stack[ply].static_eval.unwrap();
+
+        assert!(worker.improving(0) && worker.improving(1), "nothing to compare against");
+        assert!(!worker.improving(3) && worker.stack[3].in_check && worker.stack[3].static_eval.is_none());
+        for ply in [2, 4, 6] { assert_eq!(worker.improving(ply as u32), eval(ply) > eval(ply - 2), "ply {}", ply); }
+        // Black was in check two plies before ply 5, so ply 5 looks back to ply 1
+        assert_eq!(worker.improving(5), eval(5) > eval(1));
+    }
+
+    #[test]
+    fn repetitions_are_found_along_the_path_and_into_the_game() {
+        let search = Search::new();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        let state = walk(&mut worker, State::start(), &shuffle[..3]);
+        assert!(!worker.is_repetition(&state, 3));
+        let state = walk(&mut worker, State::start(), &shuffle);
+        assert!(worker.is_repetition(&state, 4));
+        // A null move on the path cuts it off
+        worker.stack[1].null_move = true;
+        assert!(!worker.is_repetition(&state, 4));
+
+        // Two plies into the game already, the tree repeats the start
+        let mut game = Game::new();
+        for text in &shuffle[..2] { game.play(Move::from_uci(text, game.current()).unwrap()).unwrap(); }
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        worker.history = game.history().clone();
+        let state = walk(&mut worker, game.current().clone(), &shuffle[2..]);
+        assert!(worker.is_repetition(&state, 2));
+        let state = walk(&mut worker, game.current().clone(), &shuffle[2..3]);
+        assert!(!worker.is_repetition(&state, 1));
+    }
+
+    #[test]
+    fn every_move_leaves_the_stack_as_it_found_it() {
+        let search = Search::new();
+        for fen in &BENCH_POSITIONS[..8] {
+            let state = State::from_fen(fen).unwrap();
+            let root_moves = RootMoves::new(&state, &[]).moves;
+            let mut worker = search.worker(SearchLimits::depth(6), None, &root_moves);
+            worker.iterate(&state, &History::new(), 0);
+            let left = worker.stack.iter().position(|entry| {
+                entry.mv.is_some() || entry.moved.is_some() || entry.captured.is_some() || entry.null_move || entry.excluded.is_some()
+            });
+            assert_eq!(left, None, "{}", fen);
+        }
+    }
+
+    // --- Futility --- //
+
+    #[test]
+    fn futility_prunes_by_a_margin_that_grows_with_depth() {
+        let alpha = Score::cp(100);
+        assert!(futility_prunes(1, Score::cp(-50), alpha, false));
+        assert!(!futility_prunes(1, Score::cp(-49), alpha, false));
+        assert!(!futility_prunes(2, Score::cp(-50), alpha, false));
+        assert!(futility_prunes(3, Score::cp(-350), alpha, false));
+        // An improving eval is given more room
+        assert!(!futility_prunes(1, Score::cp(-50), alpha, true));
+        assert!(futility_prunes(1, Score::cp(-90), alpha, true));
+        assert!(!futility_prunes(1, Score::ZERO, Score::mate_in(3), false));
+    }
+
+    #[test]
+    fn futility_still_searches_checks() {
+        // A queen and rook down, so every quiet move is futile, but Nf7 gives check and mates
+        let mut state = State::from_fen("6rk/6pp/7N/8/8/q7/6PP/6K1 w - - 0 1").unwrap();
+        assert!(futility_prunes(1, Score::cp(crate::eval::evaluate(&state)), Score::ZERO, false));
+        // Razoring would drop the node into quiescence before futility is reached
+        let mut search = Search::new();
+        search.set_options(SearchOptions { razoring: false, ..SearchOptions::default() });
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        worker.root_depth = 2;
+        let mut pv = Vec::new();
+        let score = worker.negamax(&mut state, 1, 1, Score::ZERO, Score::cp(1), &mut pv, true);
+        assert!(score >= Score::cp(1), "{:?}", score);
+    }
+
+    #[test]
+    fn futility_saves_nodes_without_losing_solutions() {
+        // Self-play positions where one move is clearly best; the file says how they were chosen
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/futility.epd");
+        let without = SearchOptions { futility: false, ..SearchOptions::default() };
+        let limits = SearchLimits::depth(8);
+        let pruned = crate::epd::run_epd_suite_with(path, limits.clone(), SearchOptions::default()).unwrap();
+        let full = crate::epd::run_epd_suite_with(path, limits, without).unwrap();
+        assert!(pruned.errors.is_empty() && pruned.total() == 100, "{:?}", pruned.errors);
+        for (pruned, full) in pruned.entries.iter().zip(&full.entries) {
+            assert!(pruned.solved || !full.solved, "{:?} lost to futility", pruned.id);
+        }
+        assert!(bench_with(SearchOptions::default(), 5).0 < bench_with(without, 5).0);
+    }
+
+    // --- Reverse Futility --- //
+
+    #[test]
+    fn reverse_futility_leaves_pawn_endings_and_mate_windows_alone() {
+        // White to move is in zugzwang: any king move gives up the pawn or the opposition
+        let pawns = State::from_fen("8/8/8/3k4/3P4/3K4/8/8 w - - 0 1").unwrap();
+        let knight = State::from_fen("8/8/8/3k4/3P4/3K4/8/6N1 w - - 0 1").unwrap();
+        let (eval, beta) = (Score::cp(900), Score::cp(100));
+        assert!(!reverse_futility_cuts(&pawns, 1, eval, beta, false));
+        assert!(reverse_futility_cuts(&knight, 1, eval, beta, false));
+        assert!(!reverse_futility_cuts(&knight, 3, Score::cp(460), beta, false));
+        // An improving eval needs less to spare
+        assert!(reverse_futility_cuts(&knight, 3, Score::cp(460), beta, true));
+        assert!(!reverse_futility_cuts(&knight, 1, eval, Score::mated_in(5), true));
+    }
+
+    #[test]
+    fn reverse_futility_saves_nodes_without_losing_moves() {
+        let without = SearchOptions { reverse_futility: false, ..SearchOptions::default() };
+        let (pruned, moves) = bench_with(SearchOptions::default(), 5);
+        let (full, unpruned) = bench_with(without, 5);
+        assert_eq!(moves, unpruned);
+        assert!(pruned < full, "{} nodes with reverse futility, {} without", pruned, full);
+    }
+
+    #[test]
+    fn reverse_futility_does_not_pass_for_a_side_in_zugzwang() {
+        // A pawn on the seventh is worth a lot statically, but with White to move every king
+        // move stalemates Black or gives the pawn up
+        let mut state = State::from_fen("4k3/4P3/3K4/8/8/8/8/8 w - - 0 1").unwrap();
+        let search = Search::new();
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        worker.root_depth = 2;
+        // The static eval clears beta by the margin, so a cut would return before searching
+        // a single move
+        let beta = Score::cp(crate::eval::evaluate(&state)) - REVERSE_FUTILITY_MARGINS[2];
+        worker.negamax(&mut state, 2, 1, beta - 1, beta, &mut Vec::new(), true);
+        assert!(worker.nodes > 1, "cut without searching");
+
+        // So Black, a move earlier, steps into it and holds the draw
+        let state = State::from_fen("8/4Pk2/3K4/8/8/8/8/8 b - - 0 1").unwrap();
+        let result = Search::new().best_move(&state, SearchLimits::depth(8));
+        assert_eq!(result.best_move.map(|mv| mv.to_uci(false)).as_deref(), Some("f7e8"));
+        assert!(result.score > Score::cp(-100), "{:?}", result.score);
+    }
+
+    // --- Razoring --- //
+
+    #[test]
+    fn razoring_leaves_pawn_endings_and_mate_windows_alone() {
+        let pawns = State::from_fen("8/5k2/8/3p4/3P4/3K4/8/8 w - - 0 1").unwrap();
+        let rook = State::from_fen("8/5k2/8/3p4/3P4/3K4/8/7R w - - 0 1").unwrap();
+        let (eval, alpha) = (Score::cp(-700), Score::cp(100));
+        assert!(!razoring_applies(&pawns, 1, eval, alpha));
+        assert!(razoring_applies(&rook, 1, eval, alpha));
+        assert!(razoring_applies(&rook, 3, eval, alpha));
+        assert!(!razoring_applies(&rook, 3, Score::cp(-450), alpha));
+        assert!(!razoring_applies(&rook, 1, eval, -Score::mated_in(3)));
+    }
+
+    #[test]
+    fn razoring_does_not_hide_a_mate_behind_a_lost_eval() {
+        // A queen and rook down one ply below the root, with Nf7 smothering the king. The eval
+        // is far below alpha, and quiescence never plays the quiet mate, so only the mate-score
+        // window keeps this node from being razored.
+        let mut state = State::from_fen("6rk/6pp/7N/8/8/q7/6PP/6K1 w - - 0 1").unwrap();
+        let search = Search::new();
+        let mut worker = search.worker(SearchLimits::default(), None, &[]);
+        worker.root_depth = 3;
+        let (alpha, beta) = (Score::mate_in(9), Score::INFINITY);
+        // Any ordinary alpha would razor it
+        assert!(razoring_applies(&state, 2, Score::cp(crate::eval::evaluate(&state)), Score::ZERO));
+        let mut pv = Vec::new();
+        let score = worker.negamax(&mut state, 2, 1, alpha, beta, &mut pv, true);
+        assert_eq!(score, Score::mate_in(2));
+        assert_eq!(pv.first().map(|mv| mv.to_uci(false)).as_deref(), Some("h6f7"));
+    }
+
+    #[test]
+    fn razoring_saves_nodes_without_losing_tactics() {
+        // ProbCut trades depth for time and is judged on a node budget by its own test
+        let with = SearchOptions { probcut: false, ..SearchOptions::default() };
+        let without = SearchOptions { razoring: false, ..with };
+        assert!(bench_with(with, 5).0 < bench_with(without, 5).0);
+        assert_eq!(solved_with(with), solved_with(without));
+    }
+
+    // --- ProbCut --- //
+
+    #[test]
+    fn probcut_saves_nodes_without_losing_tactics() {
+        let without = SearchOptions { probcut: false, ..SearchOptions::default() };
+        let (pruned, full) = (bench_with(SearchOptions::default(), 7).0, bench_with(without, 7).0);
+        assert!(pruned < full, "{} nodes with ProbCut, {} without", pruned, full);
+        // A cut trusts a shallow search, so ProbCut is judged on the time it frees, not by depth
+        let budget = SearchLimits::nodes(TACTICS_NODES);
+        assert_eq!(solved_within(SearchOptions::default(), budget.clone()), solved_within(without, budget));
+    }
+
+    #[test]
+    fn probcut_cuts_at_a_plausible_rate_in_the_middlegame() {
+        let search = Search::new();
+        let (mut tries, mut cuts) = (0, 0);
+        for fen in &BENCH_POSITIONS[..10] {
+            let state = State::from_fen(fen).unwrap();
+            let root_moves = RootMoves::new(&state, &[]).moves;
+            let mut worker = search.worker(SearchLimits::depth(8), None, &root_moves);
+            worker.iterate(&state, &History::new(), 0);
+            tries += worker.probcut_tries;
+            cuts += worker.probcut_cuts;
+        }
+        let rate = cuts as f64 / tries as f64;
+        assert!(tries > 100 && (0.01..0.5).contains(&rate), "{} cuts in {} tries", cuts, tries);
+    }
+
+    // --- Null Move and Late Move Reductions --- //
+
+    /// Self-play positions from `tests/fixtures/futility.epd`, each with one move clearly
+    /// better than the rest, and that move.
+    const DECISIVE: [(&str, &str); 20] = [
+        ("2R5/7p/7k/8/4pp1P/6r1/4BK2/8 w - - 0 1", "c8f8"),
+        ("8/2R4p/8/4r2k/7P/5K2/2B1p3/8 b - - 0 1", "h7h6"),
+        ("1k6/1Rp3pp/B4p2/5P1K/8/2r5/P1P3rP/8 b - - 0 1", "b8a8"),
+        ("6r1/3k4/3p1p2/1Bb5/P4K2/6rP/3Q2P1/7R b - - 0 1", "d7c8"),
+        ("8/8/1R6/3pk3/P3p3/8/P4P1P/4K3 b - - 0 1", "e5d4"),
+        ("7r/5k1p/2p3p1/2r2p2/p1P2P2/1P1B4/P5PP/3R2K1 w - - 0 1", "b3b4"),
+        ("r1b1k2r/1p3p1p/2n2p2/1q6/1b4P1/1Pp4P/2Q1NP2/1R2KB1R b Kkq g3 0 1", "a8a2"),
+        ("r3k2r/ppp2p2/3p3p/4pn2/1R2P2q/P2P4/1PPKP1P1/R1BQ1B2 b kq - 0 1", "f5e3"),
+        ("1k1r4/1pp5/8/p3p1pn/4P2p/P1PBK2P/6P1/5R2 b - - 0 1", "h5f4"),
+        ("8/kpQ5/p1b1R3/8/3P4/P1N5/1PPq4/1K6 b - - 0 1", "d2g2"),
+        ("r1b1kbr1/pp3ppp/1qNpp2n/8/P3P3/2N5/1PP2PPP/R1BQKB1R b Kq - 0 1", "b7c6"),
+        ("8/2k5/8/1PN2p1p/2K1b3/6r1/7p/5R2 b - - 0 1", "g3g1"),
+        ("8/3R4/1P3k2/3p1p2/3P1K2/8/2P5/8 w - - 0 1", "b6b7"),
+        ("5k1r/3Qbpp1/p2p3p/p1q5/4P3/5N2/2P2PPP/2R3K1 w - - 0 1", "c1b1"),
+        ("r3kb1r/2p1pp1p/ppn3p1/6B1/3Pq3/P3QNP1/1Pb2P1P/2R1KB1R w Kkq - 0 1", "e1d2"),
+        ("8/4k3/4p3/3p3R/1p1Pb3/pP1N4/P1r5/1K6 b - - 0 1", "e4d3"),
+        ("8/4k3/4p3/3p4/1p1P4/pPrb3R/P7/1K6 w - - 0 1", "h3d3"),
+        ("8/1rkp4/1p6/2p1Q3/3n1N2/p4P2/Pr6/R3K2R b K - 0 1", "c7c6"),
+        ("6R1/3k2p1/8/3P3p/4N1pP/5nP1/2r2PK1/8 b - - 0 1", "c2c1"),
+        ("r4rk1/1pq2pp1/2p2n1p/2bp4/2n1P3/1QN3P1/PPP2PBP/R1B2RK1 b - - 0 1", "c4a5"),
+    ];
+
+    #[test]
+    fn null_move_and_reductions_save_nodes_without_losing_moves() {
+        let without = SearchOptions { null_move: false, late_move_reductions: false, ..SearchOptions::default() };
+        let fens = DECISIVE.map(|(fen, _)| fen);
+        let (pruned, moves) = searched_with(SearchOptions::default(), &fens, 6);
+        let (full, unpruned) = searched_with(without, &fens, 6);
+        assert_eq!(moves, unpruned);
+        for ((fen, solution), mv) in DECISIVE.iter().zip(&moves) {
+            assert_eq!(mv.map(|mv| mv.to_uci(false)).as_deref(), Some(*solution), "{}", fen);
+        }
+        assert!(4 * pruned < full, "{} nodes with null move and LMR, {} without", pruned, full);
+    }
+
+    // --- Move Ordering --- //
+
+    #[test]
+    fn ordering_cuts_the_start_position_tree_fivefold() {
+        let unordered = SearchOptions { ordering: false, ..SearchOptions::default() };
+        let nodes = |options| {
+            let mut search = Search::new();
+            search.set_options(options);
+            search.best_move(&State::start(), SearchLimits::depth(6)).nodes
+        };
+        let (ordered, unordered) = (nodes(SearchOptions::default()), nodes(unordered));
+        assert!(unordered >= 5 * ordered, "{} nodes ordered, {} unordered", ordered, unordered);
+    }
+}
@@ -0,0 +1,90 @@
+//! Static exchange evaluation: the material balance of a capture sequence on one square.
+
+use crate::bits::SquareSet;
+use crate::board::{Board, Color, PieceType, Square};
+use crate::eval::piece_value;
+use crate::mobility::attackers_mask_through;
+use crate::mv::{Move, MoveType};
+use crate::state::State;
+
+/// Cheapest first, the order in which each side joins the exchange.
+const ATTACKER_ORDER: [PieceType; 6] = [
+    PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King,
+];
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Centipawns the side to move nets from `mv` if both sides keep recapturing on the target
+/// square with their least valuable attacker, each stopping once recapturing would lose.
+/// Sliders behind a capturing piece join as it leaves, pawns promote as they reach the last
+/// rank, and the king only recaptures onto an undefended square. Pins are not considered.
+pub fn see(state: &State, mv: Move) -> i32 {
+    let board = &state.board;
+    let (source, target) = (mv.source(), mv.target());
+    let Some(mover) = board[source] else { return 0 };
+    let mut occupied = board.occupied() - SquareSet::from_square(source);
+
+    let mut gain = [0; 32];
+    gain[0] = match mv.move_type() {
+        MoveType::Castling => return 0,
+        MoveType::EnPassant => {
+            occupied.remove(mv.en_passant_capture());
+            piece_value(PieceType::Pawn)
+        }
+        _ => board[target].map_or(0, |p| piece_value(p.piece_type())),
+    };
+    let mut on_target = mover.piece_type();
+    if mv.move_type() == MoveType::Promotion {
+        on_target = mv.promoted_type();
+        gain[0] += piece_value(on_target) - piece_value(PieceType::Pawn);
+    }
+
+    let mut side = state.to_move;
+    let mut depth = 0;
+    while depth + 1 < gain.len() {
+        side = !side;
+        let attackers = attackers_to(board, target, occupied) & board.occupancy(side);
+        let Some((square, piece_type)) = least_valuable(board, attackers, side) else { break };
+        if piece_type == PieceType::King
+            && attackers_to(board, target, occupied - SquareSet::from_square(square)).intersects(board.occupancy(!side))
+        {
+            break;
+        }
+
+        depth += 1;
+        gain[depth] = piece_value(on_target) - gain[depth - 1];
+        on_target = piece_type;
+        if piece_type == PieceType::Pawn && target.rank() == side.promotion_rank() {
+            on_target = PieceType::Queen;
+            gain[depth] += piece_value(PieceType::Queen) - piece_value(PieceType::Pawn);
+        }
+        occupied.remove(square);
+    }
+
+    // Each side may decline to recapture, so fold back from the end keeping the better option
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+    gain[0]
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+/// Pieces of both colors among `occupied` that attack `square`, so that sliders uncovered by
+/// removed pieces are included.
+fn attackers_to(board: &Board, square: Square, occupied: SquareSet) -> SquareSet {
+    let both = attackers_mask_through(board, square, Color::White, occupied)
+        | attackers_mask_through(board, square, Color::Black, occupied);
+    both & occupied
+}
+
+fn least_valuable(board: &Board, attackers: SquareSet, color: Color) -> Option<(Square, PieceType)> {
+    ATTACKER_ORDER.into_iter().find_map(|piece_type| {
+        (attackers & board.pieces_of(color, piece_type)).first().map(|square| (square, piece_type))
+    })
+}
@@ -0,0 +1,342 @@
+//! Serde support for the core types, behind the `serde` feature. Human-readable formats get the
+//! notation strings (`e4`, `N`, `e2e4`, FEN); binary formats get the packed integers.
+
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+use serde::ser::{Serialize, Serializer};
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::castling::CastlingRights;
+use crate::fen::{format_placement, parse_placement, piece_from_char, piece_to_char};
+use crate::game::Game;
+use crate::mv::{Move, MoveType};
+use crate::state::{AttackCache, State};
+use crate::zobrist::hash_state;
+
+/// Suffixes marking the move types that coordinates alone cannot tell apart from normal moves.
+const EN_PASSANT_SUFFIX: &str = "ep";
+const CASTLING_SUFFIX: &str = "castle";
+
+// ============================================================================
+// Square and Piece
+// ============================================================================
+
+/// Algebraic (`e4`) or the square index.
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_u8(self.value())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            Square::from_algebraic(&text).ok_or_else(|| D::Error::custom(format!("invalid square '{}'", text)))
+        } else {
+            let index = u8::deserialize(deserializer)?;
+            if index >= 64 { return Err(D::Error::custom(format!("square index {} out of range", index))); }
+            Ok(Square::from_index(index as usize))
+        }
+    }
+}
+
+/// The FEN letter (`N`, `n`) or the packed byte.
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_char(piece_to_char(*self))
+        } else {
+            serializer.serialize_u8(self.bits())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let c = char::deserialize(deserializer)?;
+            piece_from_char(c).ok_or_else(|| D::Error::custom(format!("invalid piece '{}'", c)))
+        } else {
+            let bits = u8::deserialize(deserializer)?;
+            Piece::from_bits(bits).ok_or_else(|| D::Error::custom(format!("invalid piece encoding {:#04x}", bits)))
+        }
+    }
+}
+
+// ============================================================================
+// Move
+// ============================================================================
+
+/// Coordinates as `e2e4` or `e7e8q`, or the packed `u16`. Without a position the move type
+/// cannot be inferred, so en passant is suffixed `ep` (`e5d6ep`) and castling, written as the
+/// king taking its rook, `castle` (`e1h1castle`).
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format_move(*self))
+        } else {
+            serializer.serialize_u16(self.bits())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            parse_move(&text).ok_or_else(|| D::Error::custom(format!("invalid move '{}'", text)))
+        } else {
+            u16::deserialize(deserializer).map(Move::from_bits)
+        }
+    }
+}
+
+fn format_move(mv: Move) -> String {
+    let coords = format!("{}{}", mv.source(), mv.target());
+    match mv.move_type() {
+        MoveType::Normal => coords,
+        MoveType::Promotion => format!("{}{}", coords, mv.promoted_type().to_char(Color::Black)),
+        MoveType::EnPassant => coords + EN_PASSANT_SUFFIX,
+        MoveType::Castling => coords + CASTLING_SUFFIX,
+    }
+}
+
+fn parse_move(text: &str) -> Option<Move> {
+    let square = |range: std::ops::Range<usize>| Square::from_algebraic(text.get(range)?);
+    let (source, target) = (square(0..2)?, square(2..4)?);
+    match text.get(4..)? {
+        "" => Some(Move::new(source, target)),
+        EN_PASSANT_SUFFIX => Some(Move::en_passant(source, target)),
+        CASTLING_SUFFIX => Some(Move::castling(source, target)),
+        promotion => {
+            let mut chars = promotion.chars();
+            let promoted_to = chars.next().and_then(PieceType::from_char)?;
+            let promotable = !matches!(promoted_to, PieceType::Pawn | PieceType::King);
+            (chars.next().is_none() && promotable).then(|| Move::promotion(source, target, promoted_to))
+        }
+    }
+}
+
+// ============================================================================
+// Board and Castling Rights
+// ============================================================================
+
+/// The FEN placement field, or one packed piece byte per square with zero for empty.
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format_placement(self))
+        } else {
+            serializer.collect_seq((0..64).map(|index| self[Square::from_index(index)].map_or(0, Piece::bits)))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            return parse_placement(&text).map_err(D::Error::custom);
+        }
+
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        if bytes.len() != 64 { return Err(D::Error::invalid_length(bytes.len(), &"64 squares")); }
+        let mut board = Board::new();
+        for (index, &bits) in bytes.iter().enumerate().filter(|&(_, &bits)| bits != 0) {
+            let piece = Piece::from_bits(bits)
+                .ok_or_else(|| D::Error::custom(format!("invalid piece encoding {:#04x}", bits)))?;
+            board.set_piece(piece, Square::from_index(index));
+        }
+        Ok(board)
+    }
+}
+
+/// The FEN castling field, or the packed `u16` with rook files. As text, Chess960 rook files
+/// only survive inside a whole `State`, since reading them back needs the board.
+impl Serialize for CastlingRights {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_u16(self.bits())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CastlingRights {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+        } else {
+            u16::deserialize(deserializer).map(CastlingRights::from_bits)
+        }
+    }
+}
+
+// ============================================================================
+// State and Game
+// ============================================================================
+
+/// FEN, or a tuple of the board, side, rights, en passant square, and clocks. The hash is
+/// never stored; it is recomputed on the way in.
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_fen())
+        } else {
+            (&self.board, self.to_move as u8, self.castling_rights, self.en_passant, self.halfmove_clock, self.fullmove_number)
+                .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let fen = String::deserialize(deserializer)?;
+            return State::from_fen(&fen).map_err(D::Error::custom);
+        }
+
+        let (board, to_move, castling_rights, en_passant, halfmove_clock, fullmove_number) =
+            <(Board, u8, CastlingRights, Option<Square>, u8, u16)>::deserialize(deserializer)?;
+        let to_move = match to_move {
+            0 => Color::White,
+            1 => Color::Black,
+            _ => return Err(D::Error::custom(format!("invalid side to move {}", to_move))),
+        };
+        let mut state = State { board, to_move, castling_rights, en_passant, halfmove_clock, fullmove_number, hash: 0, attacks: AttackCache::default() };
+        state.hash = hash_state(&state);
+        Ok(state)
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename = "Game")]
+struct GameRef<'a> {
+    initial: &'a State,
+    moves: &'a [Move],
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Game")]
+struct GameRecord {
+    initial: State,
+    moves: Vec<Move>,
+}
+
+/// The initial position and the moves played; the rest is rebuilt by replaying them, which
+/// rejects illegal moves.
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameRef { initial: self.initial(), moves: self.moves_played() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = GameRecord::deserialize(deserializer)?;
+        let mut game = Game::from_state(record.initial);
+        for mv in record.moves {
+            game.play(mv).map_err(D::Error::custom)?;
+        }
+        Ok(game)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+
+    /// The value read back from JSON and from bincode, checked against the original by `same`.
+    fn round_trip<T: Serialize + DeserializeOwned + Debug>(value: &T, same: impl Fn(&T, &T) -> bool) {
+        let json = serde_json::to_string(value).unwrap();
+        let from_json: T = serde_json::from_str(&json).unwrap();
+        assert!(same(value, &from_json), "{:?} through {}", value, json);
+        let binary = bincode::serialize(value).unwrap();
+        let from_binary: T = bincode::deserialize(&binary).unwrap();
+        assert!(same(value, &from_binary), "{:?} through {:?}", value, binary);
+    }
+
+    fn same_state(a: &State, b: &State) -> bool {
+        a.to_fen() == b.to_fen() && a.hash() == b.hash() && a.castling_rights == b.castling_rights
+    }
+
+    #[test]
+    fn squares_and_pieces_round_trip() {
+        for index in 0..64 { round_trip(&Square::from_index(index), PartialEq::eq); }
+        for color in [Color::White, Color::Black] {
+            for piece_type in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King] {
+                round_trip(&Piece::new(piece_type, color), PartialEq::eq);
+            }
+        }
+        assert_eq!(serde_json::to_string(&Square::E4).unwrap(), "\"e4\"");
+        assert_eq!(serde_json::to_string(&Piece::new(PieceType::Knight, Color::Black)).unwrap(), "\"n\"");
+        assert!(serde_json::from_str::<Square>("\"i9\"").is_err());
+        assert!(bincode::deserialize::<Square>(&[64]).is_err());
+    }
+
+    #[test]
+    fn moves_of_every_type_round_trip() {
+        let moves = [
+            Move::new(Square::E2, Square::E4),
+            Move::promotion(Square::E7, Square::F8, PieceType::Knight),
+            Move::en_passant(Square::E5, Square::D6),
+            Move::castling(Square::E1, Square::H1),
+        ];
+        for mv in moves { round_trip(&mv, PartialEq::eq); }
+        let texts: Vec<String> = moves.iter().map(|mv| serde_json::to_string(mv).unwrap()).collect();
+        assert_eq!(texts, ["\"e2e4\"", "\"e7f8n\"", "\"e5d6ep\"", "\"e1h1castle\""]);
+        assert!(serde_json::from_str::<Move>("\"e7e8k\"").is_err());
+    }
+
+    #[test]
+    fn boards_and_castling_rights_round_trip() {
+        let state = State::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        round_trip(&state.board, |a, b| format_placement(a) == format_placement(b));
+        round_trip(&state.castling_rights, PartialEq::eq);
+        round_trip(&CastlingRights::none(), PartialEq::eq);
+        assert_eq!(serde_json::to_string(&state.castling_rights).unwrap(), "\"KQkq\"");
+    }
+
+    #[test]
+    fn states_round_trip_with_en_passant_and_chess960_castling() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+        ];
+        for fen in fens {
+            let state = State::from_fen(fen).unwrap();
+            round_trip(&state, same_state);
+        }
+        assert_eq!(serde_json::to_string(&State::start()).unwrap(), format!("\"{}\"", State::start().to_fen()));
+    }
+
+    #[test]
+    fn games_round_trip_by_replaying_their_moves() {
+        let mut game = Game::from_fen("r3k2r/8/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").unwrap();
+        for text in ["e2e4", "d4e3", "e1g1", "e8c8"] {
+            game.play(Move::from_uci(text, game.current()).unwrap()).unwrap();
+        }
+        round_trip(&game, |a, b| {
+            a.moves_played() == b.moves_played() && same_state(a.initial(), b.initial()) && same_state(a.current(), b.current())
+        });
+
+        // A move that is not legal in the replay is rejected
+        let json = serde_json::to_string(&game).unwrap().replace("e2e4", "e2e5");
+        assert!(serde_json::from_str::<Game>(&json).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Strength limiting for casual play. A skill level below the top caps the search depth and
+//! then picks among the best few root moves rather than always the best, each candidate's
+//! chance shrinking with how far it scores below the top, so that the engine errs the way a
+//! weaker player does instead of dropping pieces at random. `UCI_Elo` maps onto the same
+//! levels when `UCI_LimitStrength` is on.
+
+use std::cmp::Reverse;
+
+use crate::mv::Move;
+use crate::score::Score;
+
+/// The top skill level: full strength, exactly as with no limit at all.
+pub const MAX_SKILL_LEVEL: u32 = 20;
+
+/// The range `UCI_Elo` accepts, spread evenly over the levels: the lowest is level 0, the
+/// highest full strength.
+pub const MIN_ELO: u32 = 1000;
+pub const MAX_ELO: u32 = 2800;
+
+/// From this level up, a mate in one is always played when there is one.
+pub const MATE_IN_ONE_LEVEL: u32 = 10;
+
+/// Root moves the weakened pick chooses among.
+pub(crate) const CANDIDATES: usize = 4;
+
+/// Score gap, in centipawns, beyond which the random part of the pick grows no further.
+const MAX_SPREAD: i32 = 100;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// What the strength options ask for. The default is full strength.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Strength {
+    /// `Skill Level`, 0 to `MAX_SKILL_LEVEL`.
+    pub skill_level: u32,
+    /// `UCI_LimitStrength`: when on, `elo` decides the level instead of `skill_level`.
+    pub limit_strength: bool,
+    /// `UCI_Elo`, `MIN_ELO` to `MAX_ELO`.
+    pub elo: u32,
+}
+
+// ============================================================================
+// Strength
+// ============================================================================
+
+impl Default for Strength {
+    fn default() -> Self {
+        Strength { skill_level: MAX_SKILL_LEVEL, limit_strength: false, elo: MAX_ELO }
+    }
+}
+
+impl Strength {
+    /// The level in force, `MAX_SKILL_LEVEL` meaning no limit.
+    pub fn level(&self) -> u32 {
+        if self.limit_strength {
+            let elo = self.elo.clamp(MIN_ELO, MAX_ELO);
+            (elo - MIN_ELO) * MAX_SKILL_LEVEL / (MAX_ELO - MIN_ELO)
+        } else {
+            self.skill_level.min(MAX_SKILL_LEVEL)
+        }
+    }
+
+    /// The deepest a search at this strength may go, or None at full strength.
+    pub fn depth_limit(&self) -> Option<u32> {
+        let level = self.level();
+        (level < MAX_SKILL_LEVEL).then_some(1 + level / 2)
+    }
+}
+
+// ============================================================================
+// Weakened Pick
+// ============================================================================
+
+/// Choose among the best `CANDIDATES` of `scored` root moves at `level`. Each candidate's
+/// score is raised by part of its gap to the best, more at lower levels, plus a random share
+/// of the spread between the candidates; the highest total is played. `seed` is a xorshift
+/// state advanced once per candidate. `scored` must not be empty.
+pub(crate) fn pick(scored: &mut [(Move, Score)], level: u32, seed: &mut u64) -> Move {
+    scored.sort_by_key(|&(_, score)| Reverse(score));
+    let candidates = &scored[..scored.len().min(CANDIDATES)];
+    let top = candidates[0].1.raw();
+    let spread = (top - candidates[candidates.len() - 1].1.raw()).min(MAX_SPREAD);
+    let weakness = 120 - 2 * level.min(MAX_SKILL_LEVEL) as i32;
+
+    let mut best = (candidates[0].0, i32::MIN);
+    for &(mv, score) in candidates {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        let push = (weakness * (top - score.raw()) + spread * (*seed % weakness as u64) as i32) / 128;
+        if score.raw() + push > best.1 { best = (mv, score.raw() + push); }
+    }
+    best.0
+}
@@ -1,15 +1,23 @@
 //! Chess game state.
 
-use crate::board::{Board, Color, Lateral, Piece, Square, SlotExt};
-use crate::castling::CastlingRights;
-use crate::mobility::MoveGenerator;
-use crate::mv::{Move, MoveType};
+use std::mem;
+use std::sync::OnceLock;
+
+use crate::attacks::{king_attacks, pawn_attacks};
+use crate::bits::SquareSet;
+use crate::board::{Board, Color, Lateral, Piece, PieceType, Square};
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::fen::{format_fen, parse_fen, FenError};
+use crate::history::History;
+use crate::mobility::{check_move, gives_check, pinned_pieces, AttackInfo, MoveGenerator};
+use crate::mv::{IllegalMoveError, Move, MoveType};
+use crate::zobrist::{castling_key, en_passant_key, hash_state, piece_key, SIDE_KEY};
 
 // ============================================================================
 // Type Definitions
 // ============================================================================
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct State {
     pub(crate) board: Board,
     pub(crate) to_move: Color,
@@ -17,6 +25,162 @@ pub struct State {
     pub(crate) en_passant: Option<Square>,
     pub(crate) halfmove_clock: u8,
     pub(crate) fullmove_number: u16,
+    pub(crate) hash: u64,
+    /// Filled by the first `attack_info` call and emptied by every move.
+    pub(crate) attacks: AttackCache,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate(Color /* winner */),
+    Stalemate,
+    DrawFiftyMove,
+    /// Neither side has the material to mate by any series of legal moves, however badly the
+    /// other plays: FIDE's dead position rule as it applies to material alone. KNN vs K is
+    /// not included, since a mate remains possible.
+    DrawInsufficientMaterial,
+    /// Kings and pawns only, with every pawn blocked and no king able to reach a pawn it could
+    /// take, so that nothing can ever give check: a dead position under FIDE article 5.2.2.
+    DrawDeadPosition,
+    DrawThreefoldRepetition,
+    DrawFivefoldRepetition,
+}
+
+/// A position's `AttackInfo` once computed. Clones start empty rather than copy it, since
+/// most clones are about to have a move played on them.
+#[derive(Debug, Default)]
+pub(crate) struct AttackCache(OnceLock<AttackInfo>);
+
+impl Clone for AttackCache {
+    fn clone(&self) -> Self { AttackCache::default() }
+}
+
+/// Everything `make_move` overwrites, so `unmake_move` can restore the state exactly, the
+/// attack maps already computed for it included.
+#[derive(Clone, Debug)]
+pub struct Undo {
+    mv: Move,
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u8,
+    fullmove_number: u16,
+    hash: u64,
+    attacks: AttackCache,
+}
+
+/// What `make_null_move` overwrites.
+#[derive(Clone, Debug)]
+pub struct NullUndo {
+    en_passant: Option<Square>,
+    hash: u64,
+    attacks: AttackCache,
+}
+
+// ============================================================================
+// State — Construction
+// ============================================================================
+
+impl State {
+    /// The standard initial position with White to move.
+    pub fn start() -> Self {
+        let mut state = State {
+            board: Board::start(),
+            to_move: Color::White,
+            castling_rights: CastlingRights::all(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            attacks: AttackCache::default(),
+        };
+        state.hash = hash_state(&state);
+        state
+    }
+
+    /// Chess960 starting position `number` in Scharnagl's numbering, 0 to 959; number 518 is the
+    /// standard array. None when out of range.
+    pub fn start_960(number: u16) -> Option<Self> {
+        if number >= 960 { return None; }
+        let mut rank: [Option<PieceType>; 8] = [None; 8];
+        let mut n = number as usize;
+
+        // Bishops on opposite colors first, then the queen and knights into the gaps
+        rank[2 * (n % 4) + 1] = Some(PieceType::Bishop);
+        n /= 4;
+        rank[2 * (n % 4)] = Some(PieceType::Bishop);
+        n /= 4;
+        let place = |rank: &mut [Option<PieceType>; 8], nth: usize, piece_type| {
+            let file = (0..8).filter(|&f| rank[f].is_none()).nth(nth).unwrap();
+            rank[file] = Some(piece_type);
+        };
+        place(&mut rank, n % 6, PieceType::Queen);
+        n /= 6;
+        const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+        let (first, second) = KNIGHTS[n];
+        // The second knight's index counts the gaps left before the first is placed
+        place(&mut rank, second, PieceType::Knight);
+        place(&mut rank, first, PieceType::Knight);
+        // What remains is rook, king, rook from left to right
+        for piece_type in [PieceType::Rook, PieceType::King, PieceType::Rook] { place(&mut rank, 0, piece_type); }
+
+        let mut board = Board::new();
+        let mut castling_rights = CastlingRights::all();
+        let rooks: Vec<u8> = (0..8u8).filter(|&f| rank[f as usize] == Some(PieceType::Rook)).collect();
+        for color in [Color::White, Color::Black] {
+            for (file, piece_type) in rank.iter().enumerate() {
+                let piece_type = piece_type.expect("every file is filled");
+                board.set_piece(Piece::new(piece_type, color), (color.home_rank(), file as u8));
+                board.set_piece(Piece::new(PieceType::Pawn, color), (color.pawn_rank(), file as u8));
+            }
+            castling_rights = castling_rights
+                .with_rook_file(color, CastlingSide::Queenside, rooks[0])
+                .with_rook_file(color, CastlingSide::Kingside, rooks[1]);
+        }
+
+        let mut state = State { board, castling_rights, ..Self::start() };
+        state.hash = hash_state(&state);
+        Some(state)
+    }
+
+    /// The same position with the colors exchanged: the board flipped top to bottom with every
+    /// piece changing sides, castling rights and rook files handed across, the other side to
+    /// move, and the en passant square reflected. The clocks are kept. A symmetric evaluation
+    /// scores both the same, being from the side to move's point of view.
+    pub fn color_swapped(&self) -> Self {
+        let mut castling_rights = CastlingRights::none();
+        for color in [Color::White, Color::Black] {
+            for side in [CastlingSide::Kingside, CastlingSide::Queenside] {
+                castling_rights = castling_rights.with_rook_file(!color, side, self.castling_rights.rook_file(color, side));
+                if self.castling_rights.has(color, side) { castling_rights = castling_rights.gain(!color, side); }
+            }
+        }
+
+        let mut state = State {
+            board: self.board.color_swapped(),
+            to_move: !self.to_move,
+            castling_rights,
+            en_passant: self.en_passant.map(Square::flip_rank),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: 0,
+            attacks: AttackCache::default(),
+        };
+        state.hash = hash_state(&state);
+        state
+    }
+}
+
+// ============================================================================
+// State — Hashing
+// ============================================================================
+
+impl State {
+    /// Zobrist hash of the placement, side to move, castling rights, and en passant file.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
 }
 
 // ============================================================================
@@ -26,39 +190,154 @@ pub struct State {
 impl State {
 
     // --- Move Application --- //
+    /// Consume the state and return it with the move played, including special-move board
+    /// effects, castling rights, en passant, clocks, and side to move.
     pub fn apply_move(mut self, mv: Move) -> Self {
+        self.make_move(mv);
+        self
+    }
+
+    /// Play a move in place, returning what `unmake_move` needs to take it back.
+    pub fn make_move(&mut self, mv: Move) -> Undo {
         let piece = self.board[mv.source()].unwrap();
-        let captured = self.execute_board(mv);
+        let mut undo = Undo {
+            mv,
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+            attacks: mem::take(&mut self.attacks),
+        };
+        undo.captured = self.execute_board(mv);
+        self.hash ^= self.placement_delta(mv, piece, undo.captured);
 
+        self.hash ^= castling_key(self.castling_rights) ^ en_passant_key(self.en_passant);
         self.en_passant = self.resulting_en_passant(mv, piece);
         self.castling_rights = self.resulting_castling(mv, piece);
-        self.halfmove_clock = self.resulting_halfmove(piece, captured.is_some());
-        
-        self.fullmove_number += (self.to_move == Color::Black) as u16;
+        self.halfmove_clock = self.resulting_halfmove(piece, undo.captured.is_some());
+        self.hash ^= castling_key(self.castling_rights) ^ en_passant_key(self.en_passant) ^ SIDE_KEY;
+
+        // Both clocks stop at their limits rather than wrap
+        self.fullmove_number = self.fullmove_number.saturating_add((self.to_move == Color::Black) as u16);
         self.to_move = !self.to_move;
 
-        self
+        undo
+    }
+
+    /// Take back the move recorded in `undo`, which must be the last one made on this state.
+    pub fn unmake_move(&mut self, undo: Undo) {
+        self.to_move = !self.to_move;
+        self.fullmove_number = undo.fullmove_number;
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+        self.attacks = undo.attacks;
+        self.revert_board(undo.mv, undo.captured);
+    }
+
+    /// Pass the turn without moving, for null-move pruning. Must not be used while in check.
+    pub fn make_null_move(&mut self) -> NullUndo {
+        let undo = NullUndo { en_passant: self.en_passant, hash: self.hash, attacks: mem::take(&mut self.attacks) };
+        self.hash ^= en_passant_key(self.en_passant) ^ SIDE_KEY;
+        self.en_passant = None;
+        self.to_move = !self.to_move;
+        undo
+    }
+
+    pub fn unmake_null_move(&mut self, undo: NullUndo) {
+        self.to_move = !self.to_move;
+        self.en_passant = undo.en_passant;
+        self.hash = undo.hash;
+        self.attacks = undo.attacks;
+    }
+
+    /// Whether no position before the move can recur after it: a pawn move, a capture, or a
+    /// loss of castling rights.
+    pub fn is_irreversible(&self, mv: Move) -> bool {
+        let piece = self.board[mv.source()].unwrap();
+        piece.is_pawn()
+            || mv.move_type() == MoveType::Castling
+            || self.board[mv.target()].is_some()
+            || self.resulting_castling(mv, piece) != self.castling_rights
     }
 
     fn execute_board(&mut self, mv: Move) -> Option<Piece> {
         match mv.move_type() {
             MoveType::Normal => self.board.move_piece(mv.source(), mv.target()),
             MoveType::Promotion => {
-                self.board[mv.source()].lift();
-                self.board[mv.target()].place(mv.promoted_piece(self.to_move))
+                self.board.remove_piece(mv.source());
+                self.board.set_piece(mv.promoted_piece(self.to_move), mv.target())
             }
             MoveType::EnPassant => {
                 self.board.move_piece(mv.source(), mv.target());
-                self.board[mv.en_passant_capture()].lift()
+                self.board.remove_piece(mv.en_passant_capture())
+            }
+            // Lift both pieces first: in Chess960 either may land where the other started
+            MoveType::Castling => {
+                let (rf, rt) = mv.castling_rook_squares();
+                let king = self.board.remove_piece(mv.source()).unwrap();
+                let rook = self.board.remove_piece(rf).unwrap();
+                self.board.set_piece(king, mv.castling_king_target());
+                self.board.set_piece(rook, rt);
+                None
+            }
+        }
+    }
+
+    fn revert_board(&mut self, mv: Move, captured: Option<Piece>) {
+        match mv.move_type() {
+            MoveType::Normal => {
+                self.board.move_piece(mv.target(), mv.source());
+                if let Some(victim) = captured { self.board.set_piece(victim, mv.target()); }
+            }
+            MoveType::Promotion => {
+                self.board.remove_piece(mv.target());
+                self.board.set_piece(Piece::new(PieceType::Pawn, self.to_move), mv.source());
+                if let Some(victim) = captured { self.board.set_piece(victim, mv.target()); }
+            }
+            MoveType::EnPassant => {
+                self.board.move_piece(mv.target(), mv.source());
+                if let Some(victim) = captured { self.board.set_piece(victim, mv.en_passant_capture()); }
             }
             MoveType::Castling => {
-                self.board.move_piece(mv.source(), mv.target());
                 let (rf, rt) = mv.castling_rook_squares();
-                self.board.move_piece(rf, rt)
+                let king = self.board.remove_piece(mv.castling_king_target()).unwrap();
+                let rook = self.board.remove_piece(rt).unwrap();
+                self.board.set_piece(king, mv.source());
+                self.board.set_piece(rook, rf);
             }
         }
     }
 
+    /// Piece-square keys toggled by the move's board changes, captures included.
+    fn placement_delta(&self, mv: Move, piece: Piece, captured: Option<Piece>) -> u64 {
+        let (source, target) = (mv.source(), mv.target());
+        let mut delta = piece_key(piece, source);
+        match mv.move_type() {
+            MoveType::Normal => {
+                delta ^= piece_key(piece, target);
+                if let Some(victim) = captured { delta ^= piece_key(victim, target); }
+            }
+            MoveType::Promotion => {
+                delta ^= piece_key(mv.promoted_piece(self.to_move), target);
+                if let Some(victim) = captured { delta ^= piece_key(victim, target); }
+            }
+            MoveType::EnPassant => {
+                delta ^= piece_key(piece, target);
+                if let Some(victim) = captured { delta ^= piece_key(victim, mv.en_passant_capture()); }
+            }
+            MoveType::Castling => {
+                let (rf, rt) = mv.castling_rook_squares();
+                let rook = Piece::new(PieceType::Rook, self.to_move);
+                delta ^= piece_key(piece, mv.castling_king_target()) ^ piece_key(rook, rf) ^ piece_key(rook, rt);
+            }
+        }
+        delta
+    }
+
     // --- State Derivations --- //
     fn resulting_en_passant(&self, mv: Move, piece: Piece) -> Option<Square> {
         if !piece.is_pawn() { return None; }
@@ -74,7 +353,7 @@ impl State {
     }
 
     fn resulting_halfmove(&self, piece: Piece, was_capture: bool) -> u8 {
-        if piece.is_pawn() || was_capture { 0 } else { self.halfmove_clock + 1 }
+        if piece.is_pawn() || was_capture { 0 } else { self.halfmove_clock.saturating_add(1) }
     }
 }
 
@@ -86,4 +365,377 @@ impl State {
     pub fn moves(&self) -> MoveGenerator<'_> {
         MoveGenerator::new(self)
     }
+
+    /// Attacked squares, checkers, pins, and king danger, computed on first use and kept until
+    /// the position changes, so that generation, legality, and check detection share them.
+    pub fn attack_info(&self) -> &AttackInfo {
+        self.attacks.0.get_or_init(|| AttackInfo::new(self))
+    }
+
+    /// Every square `color` attacks, whatever stands on it.
+    pub fn attacked(&self, color: Color) -> SquareSet {
+        self.attack_info().attacked(&self.board, color)
+    }
+
+    /// Pieces of `color` pinned to their king by an enemy slider.
+    pub fn pinned_pieces(&self, color: Color) -> SquareSet {
+        if color == self.to_move { self.attack_info().pinned } else { pinned_pieces(&self.board, color) }
+    }
+
+    /// Enemy pieces giving check to the side to move.
+    pub fn checkers(&self) -> SquareSet {
+        self.attack_info().checkers
+    }
+
+    /// Whether the side to move is in check. Asked again before the next move, it costs nothing.
+    pub fn in_check(&self) -> bool {
+        !self.checkers().is_empty()
+    }
+
+    /// Whether a legal move checks the opponent, without playing it.
+    pub fn gives_check(&self, mv: Move) -> bool {
+        gives_check(self, mv)
+    }
+
+    /// Whether `mv`, which may come from anywhere, is one of this position's legal moves,
+    /// encoding included. Cheaper than searching the generator's full output.
+    pub fn is_legal_move(&self, mv: Move) -> bool {
+        check_move(self, mv).is_ok()
+    }
+
+    /// The position after `mv`, or why it cannot be played here. Unlike `apply_move`, any
+    /// `Move` is safe to pass.
+    pub fn try_apply(&self, mv: Move) -> Result<State, IllegalMoveError> {
+        check_move(self, mv)?;
+        Ok(self.clone().apply_move(mv))
+    }
+}
+
+// ============================================================================
+// State — Game Status
+// ============================================================================
+
+impl State {
+    /// Whether the game has ended by mate, stalemate, the fifty-move rule, or bare material.
+    pub fn status(&self) -> GameStatus {
+        if !self.moves().any() {
+            return if self.in_check() { GameStatus::Checkmate(!self.to_move) } else { GameStatus::Stalemate };
+        }
+        if self.halfmove_clock >= 100 { return GameStatus::DrawFiftyMove; }
+        if self.is_insufficient_material() { return GameStatus::DrawInsufficientMaterial; }
+        if self.is_pawn_locked() { return GameStatus::DrawDeadPosition; }
+        GameStatus::Ongoing
+    }
+
+    /// `status`, plus repetition draws. `history` must end with this position.
+    pub fn status_with(&self, history: &History) -> GameStatus {
+        let status = self.status();
+        if status != GameStatus::Ongoing { return status; }
+        match history.count(self.hash) {
+            n if n >= 5 => GameStatus::DrawFivefoldRepetition,
+            n if n >= 3 => GameStatus::DrawThreefoldRepetition,
+            _ => GameStatus::Ongoing,
+        }
+    }
+
+    /// Whether the material alone rules out mate for both sides: K vs K, a lone minor piece, or
+    /// any number of bishops, of either side, all standing on one square color. Enough for a
+    /// draw under FIDE rules; USCF and other rule sets that also count positions without a
+    /// forced mate can build on `board` directly.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = 0;
+        let mut knights = 0;
+        let mut bishop_colors = [false; 2];
+
+        for (sq, piece) in self.board.pieces() {
+            match piece.piece_type() {
+                PieceType::King => {}
+                PieceType::Knight => { minors += 1; knights += 1; }
+                PieceType::Bishop => {
+                    minors += 1;
+                    bishop_colors[((sq.rank() + sq.file()) % 2) as usize] = true;
+                }
+                _ => return false,
+            }
+        }
+
+        minors <= 1 || (knights == 0 && !(bishop_colors[0] && bishop_colors[1]))
+    }
+
+    /// Kings and pawns only, where no pawn can ever move or capture and neither king can reach
+    /// an enemy pawn it could take. Pawns are then fixed for good and only kings move, so no
+    /// check, let alone mate, can ever happen.
+    fn is_pawn_locked(&self) -> bool {
+        let board = &self.board;
+        let kings = board.pieces_of(Color::White, PieceType::King) | board.pieces_of(Color::Black, PieceType::King);
+        let pawns = [Color::White, Color::Black].map(|color| board.pieces_of(color, PieceType::Pawn));
+        let all_pawns = pawns[0] | pawns[1];
+        if all_pawns.is_empty() || board.occupied() != kings | all_pawns || self.en_passant.is_some() { return false; }
+
+        for color in [Color::White, Color::Black] {
+            for sq in pawns[color as usize] {
+                let blocked = sq.forward(color, 1, Lateral::Straight).is_some_and(|ahead| all_pawns.contains(ahead));
+                if !blocked || pawn_attacks(color, sq).intersects(pawns[!color as usize]) { return false; }
+            }
+        }
+        [Color::White, Color::Black].into_iter()
+            .all(|color| !self.king_reach(color, &pawns).intersects(pawns[!color as usize]))
+    }
+
+    /// Squares `color`'s king could ever walk to, enemy pawns it could take included, were
+    /// every pawn to stay where it is. The other king is ignored, so this may overstate it.
+    fn king_reach(&self, color: Color, pawns: &[SquareSet; 2]) -> SquareSet {
+        let Some(king_sq) = self.board.king_square(color) else { return SquareSet::EMPTY };
+        let guarded = pawns[!color as usize].into_iter()
+            .fold(SquareSet::EMPTY, |guarded, sq| guarded | pawn_attacks(!color, sq));
+        let open = !(pawns[color as usize] | guarded);
+
+        let mut reach = SquareSet::from_square(king_sq);
+        loop {
+            let next = reach.into_iter().fold(reach, |next, sq| next | (king_attacks(sq) & open));
+            if next == reach { return reach; }
+            reach = next;
+        }
+    }
+}
+
+// ============================================================================
+// State — FEN
+// ============================================================================
+
+impl State {
+    /// Parse a position from FEN. Missing halfmove and fullmove clocks default to 0 and 1.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        parse_fen(fen)
+    }
+
+    /// Render the position as a full six-field FEN.
+    pub fn to_fen(&self) -> String {
+        format_fen(self)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attacks::piece_attacks;
+    use crate::board::Bitboards;
+
+    /// Checkers found by asking every enemy piece whether it attacks the king.
+    fn checkers_from_scratch(state: &State) -> SquareSet {
+        let king = state.board.king_square(state.to_move).unwrap();
+        state.board.pieces_of_color(!state.to_move)
+            .filter(|&(sq, piece)| piece_attacks(piece, sq, state.board.occupied()).contains(king))
+            .map(|(sq, _)| sq)
+            .collect()
+    }
+
+    #[test]
+    fn cached_checkers_match_a_scan_over_random_playouts() {
+        let starts = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ];
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        let (mut discovered, mut double) = (0, 0);
+        for playout in 0..600 {
+            let mut state = State::from_fen(starts[playout % starts.len()]).unwrap();
+            for _ in 0..120 {
+                let moves: Vec<Move> = state.moves().all().collect();
+                if moves.is_empty() { break; }
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let mv = moves[(seed % moves.len() as u64) as usize];
+
+                let before = (state.in_check(), state.checkers());
+                let undo = state.make_move(mv);
+                assert_eq!(state.checkers(), checkers_from_scratch(&state), "{}", state.to_fen());
+                assert_eq!(state.in_check(), !state.checkers().is_empty());
+                if state.in_check() && !state.checkers().contains(mv.destination()) { discovered += 1; }
+                if state.checkers().len() > 1 { double += 1; }
+
+                // Unmaking restores the parent's already computed answer
+                let checkers = state.checkers();
+                state.unmake_move(undo);
+                assert_eq!((state.in_check(), state.checkers()), before);
+                state.make_move(mv);
+                assert_eq!(state.checkers(), checkers);
+            }
+        }
+        assert!(discovered > 0 && double > 0, "{} discovered and {} double checks", discovered, double);
+    }
+
+    /// The FEN after playing a coordinate move on a FEN position.
+    fn after(fen: &str, uci: &str) -> String {
+        let state = State::from_fen(fen).unwrap();
+        let mv = Move::from_uci(uci, &state).unwrap();
+        assert!(state.is_legal_move(mv), "{} in {}", uci, fen);
+        state.apply_move(mv).to_fen()
+    }
+
+    #[test]
+    fn apply_move_handles_every_move_type() {
+        // Quiet piece move: the halfmove clock ticks, the fullmove number waits for Black
+        assert_eq!(after("4k3/8/8/8/8/8/8/4K1N1 w - - 3 10", "g1f3"), "4k3/8/8/8/8/5N2/8/4K3 b - - 4 10");
+        // Capture resets the clock; Black's move ends the full move
+        assert_eq!(after("4k3/8/8/3n4/8/8/8/3RK3 b - - 7 10", "d5c3"), "4k3/8/8/8/8/2n5/8/3RK3 w - - 8 11");
+        assert_eq!(after("4k3/8/8/3n4/8/8/8/3RK3 w - - 7 10", "d1d5"), "4k3/8/8/3R4/8/8/8/4K3 b - - 0 10");
+        // Double push sets the en passant square, which the capture then uses and clears
+        assert_eq!(after("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1", "e2e4"), "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+        assert_eq!(after("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1", "d4e3"), "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2");
+        // Promotion, quiet and capturing
+        assert_eq!(after("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "Qn2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(after("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8n"), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        // Castling moves the rook too and gives up both rights
+        assert_eq!(after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+        assert_eq!(after("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"), "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 1 2");
+    }
+
+    #[test]
+    fn castling_rights_go_with_king_and_rook_moves_and_rook_captures() {
+        assert_eq!(after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1e2"), "r3k2r/8/8/8/8/8/4K3/R6R b kq - 1 1");
+        assert_eq!(after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "a1a2"), "r3k2r/8/8/8/8/8/R7/4K2R b Kkq - 1 1");
+        assert_eq!(after("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "h1h8"), "r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 1");
+    }
+
+    #[test]
+    fn start_position_has_every_piece_home_and_twenty_moves() {
+        let state = State::start();
+        let expected = [
+            "  a b c d e f g h",
+            "8 r n b q k b n r 8",
+            "7 p p p p p p p p 7",
+            "6 . . . . . . . . 6",
+            "5 . . . . . . . . 5",
+            "4 . . . . . . . . 4",
+            "3 . . . . . . . . 3",
+            "2 P P P P P P P P 2",
+            "1 R N B Q K B N R 1",
+            "  a b c d e f g h",
+        ];
+        assert_eq!(state.board.display().ascii().to_string(), expected.join("\n"));
+        assert_eq!(state.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(MoveGenerator::new(&state).all().count(), 20);
+    }
+
+    /// Every field of a state that a move can touch, the board in both of its views.
+    #[allow(clippy::type_complexity)]
+    fn snapshot(state: &State) -> (Vec<(Square, Piece)>, Bitboards, Color, CastlingRights, Option<Square>, u8, u16, u64) {
+        (
+            state.board.pieces().collect(),
+            *state.board.bitboards(),
+            state.to_move,
+            state.castling_rights,
+            state.en_passant,
+            state.halfmove_clock,
+            state.fullmove_number,
+            state.hash,
+        )
+    }
+
+    #[test]
+    fn every_check_query_on_a_position_shares_one_king_scan() {
+        use crate::mobility::KING_SCANS;
+        use crate::search::{Search, SearchLimits};
+        let scans = || KING_SCANS.with(|scans| scans.get());
+
+        // Generation, check detection, status, and SAN all ask; the king is scanned once
+        let mut state = State::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let mut seed = 0xD1B5_4A32_D192_ED03u64;
+        for _ in 0..60 {
+            let start = scans();
+            let moves: Vec<Move> = state.moves().all().collect();
+            let _ = (state.in_check(), state.checkers(), state.status(), state.moves().any());
+            assert_eq!(scans() - start, 1, "{}", state.to_fen());
+            if moves.is_empty() { break; }
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            state.make_move(moves[(seed % moves.len() as u64) as usize]);
+        }
+
+        // A search asks at every node, once for check and again to generate moves, yet scans
+        // no more often than it visits nodes
+        let start = scans();
+        let nodes = Search::new().best_move(&State::start(), SearchLimits::depth(5)).nodes;
+        assert!(scans() - start <= nodes, "{} scans over {} nodes", scans() - start, nodes);
+    }
+
+    #[test]
+    fn unmake_restores_the_state_exactly_over_random_playouts() {
+        let starts = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        ];
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        for playout in 0..400 {
+            let mut state = State::from_fen(starts[playout % starts.len()]).unwrap();
+            for _ in 0..100 {
+                let moves: Vec<Move> = state.moves().all().collect();
+                if moves.is_empty() { break; }
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let mv = moves[(seed % moves.len() as u64) as usize];
+
+                let before = snapshot(&state);
+                let info = state.attack_info().clone();
+                let undo = state.make_move(mv);
+                state.moves().count();
+                state.unmake_move(undo);
+                assert_eq!(snapshot(&state), before, "{} after {}", state.to_fen(), mv);
+                // The parent's attack maps come back already computed, not rebuilt
+                let restored = state.attacks.0.get().expect("attack info restored with the position");
+                assert_eq!((restored.king_sq, restored.checkers, restored.pinned, restored.king_danger),
+                           (info.king_sq, info.checkers, info.pinned, info.king_danger));
+
+                if !state.in_check() {
+                    let undo = state.make_null_move();
+                    state.unmake_null_move(undo);
+                    assert_eq!(snapshot(&state), before);
+                    assert!(state.attacks.0.get().is_some());
+                }
+                state.make_move(mv);
+            }
+        }
+    }
+
+    #[test]
+    fn any_u16_is_legal_exactly_when_generated() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            "4k3/8/8/8/1b6/8/N7/R3K3 w Q - 0 1",
+        ];
+        let mut seed = 0xA076_1D64_78BD_642Fu64;
+        for fen in fens {
+            let state = State::from_fen(fen).unwrap();
+            let legal: Vec<Move> = state.moves().all().collect();
+            // Every generated move is accepted, then a flood of arbitrary encodings
+            assert!(legal.iter().all(|&mv| state.is_legal_move(mv) && state.try_apply(mv).is_ok()), "{}", fen);
+            let mut accepted = 0;
+            for _ in 0..200_000 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let mv = Move::from_bits(seed as u16);
+                let generated = legal.contains(&mv);
+                assert_eq!(state.is_legal_move(mv), generated, "{:?} in {}", mv, fen);
+                assert_eq!(state.try_apply(mv).is_ok(), generated);
+                accepted += generated as u32;
+            }
+            assert!(accepted > 0, "no legal encoding drawn in {}", fen);
+        }
+    }
 }
@@ -0,0 +1,162 @@
+//! Syzygy endgame tablebase probing, behind the `syzygy` feature. Probing goes through
+//! `shakmaty-syzygy`, with positions and moves handed across as FEN and UCI.
+
+use std::cmp::Reverse;
+use std::io;
+use std::ops::Neg;
+use std::path::Path;
+
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess};
+use shakmaty_syzygy::{Tablebase as SyzygyTables, Wdl as SyzygyWdl};
+
+use crate::mv::Move;
+use crate::state::State;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Win, draw, or loss for the side to move, under the fifty-move rule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    /// Lost, but the fifty-move rule saves the draw.
+    BlessedLoss,
+    Draw,
+    /// Won, but not before the fifty-move rule draws it.
+    CursedWin,
+    Win,
+}
+
+/// Plies to the next capture or pawn move on the optimal line, signed by the outcome for the
+/// side to move: positive when winning, negative when losing, zero when drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Dtz(pub i32);
+
+/// A set of Syzygy WDL (`.rtbw`) and DTZ (`.rtbz`) files.
+#[derive(Debug)]
+pub struct Tablebase {
+    tables: SyzygyTables<Chess>,
+}
+
+// ============================================================================
+// Tablebase — Construction
+// ============================================================================
+
+impl Tablebase {
+    /// Load the tables in each directory. Files that are not tablebases are ignored.
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> io::Result<Self> {
+        let mut tables = SyzygyTables::new();
+        for path in paths {
+            tables.add_directory(path)?;
+        }
+        Ok(Tablebase { tables })
+    }
+
+    /// Most pieces, kings included, of any loaded table; zero when none are loaded.
+    pub fn max_pieces(&self) -> usize {
+        self.tables.max_pieces()
+    }
+}
+
+// ============================================================================
+// Tablebase — Probing
+// ============================================================================
+
+impl Tablebase {
+    /// The outcome with best play, or None for positions no table covers. Right after a capture
+    /// or pawn move this needs only WDL tables; otherwise DTZ settles how the fifty-move rule
+    /// bears on it, and a rounded DTZ too close to call gives None.
+    pub fn probe_wdl(&self, state: &State) -> Option<Wdl> {
+        let position = self.position(state)?;
+        let wdl = if state.halfmove_clock == 0 {
+            self.tables.probe_wdl_after_zeroing(&position).ok()?
+        } else {
+            self.tables.probe_wdl(&position).ok()?.unambiguous()?
+        };
+        Some(Wdl::from(wdl))
+    }
+
+    /// The move that keeps the best outcome, preferring the fastest progress toward the next
+    /// zeroing move so that wins are not lost to the fifty-move rule. Needs DTZ tables.
+    pub fn probe_root(&self, state: &State) -> Option<(Move, Dtz)> {
+        self.probe_root_moves(state)?
+            .into_iter()
+            .max_by_key(|&(_, wdl, dtz)| (wdl, Reverse(dtz)))
+            .map(|(mv, _, dtz)| (mv, dtz))
+    }
+
+    /// Every legal move with the outcome and DTZ it leaves the side to move, or None unless the
+    /// tables cover every reply. Needs DTZ tables.
+    pub fn probe_root_moves(&self, state: &State) -> Option<Vec<(Move, Wdl, Dtz)>> {
+        if !self.covers(state) { return None; }
+        state.moves().all()
+            .map(|mv| {
+                let next = state.clone().apply_move(mv);
+                if !next.moves().any() {
+                    return Some(if next.in_check() { (mv, Wdl::Win, Dtz(1)) } else { (mv, Wdl::Draw, Dtz(0)) });
+                }
+                let wdl = -self.probe_wdl(&next)?;
+                let dtz = if next.halfmove_clock == 0 {
+                    Dtz(wdl.signum())
+                } else {
+                    // One ply further from the next zeroing move than the reply is
+                    let reply = self.tables.probe_dtz(&self.position(&next)?).ok()?.ignore_rounding();
+                    Dtz(-reply.add_plies(1).0)
+                };
+                Some((mv, wdl, dtz))
+            })
+            .collect()
+    }
+
+    /// Whether the position has few enough pieces to probe. Tables never cover castling rights.
+    pub fn covers(&self, state: &State) -> bool {
+        state.castling_rights.is_empty()
+            && (state.board.occupied().len() as usize) <= self.max_pieces()
+    }
+
+    fn position(&self, state: &State) -> Option<Chess> {
+        if !self.covers(state) { return None; }
+        let fen: Fen = state.to_fen().parse().ok()?;
+        fen.into_position(CastlingMode::Standard).ok()
+    }
+}
+
+impl Wdl {
+    /// 1 for wins, -1 for losses, and 0 for draws, whatever the fifty-move rule makes of them.
+    pub fn signum(self) -> i32 {
+        match self {
+            Wdl::Win | Wdl::CursedWin => 1,
+            Wdl::Draw => 0,
+            Wdl::Loss | Wdl::BlessedLoss => -1,
+        }
+    }
+}
+
+/// The same outcome from the opponent's side.
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+impl From<SyzygyWdl> for Wdl {
+    fn from(wdl: SyzygyWdl) -> Self {
+        match wdl {
+            SyzygyWdl::Loss        => Wdl::Loss,
+            SyzygyWdl::BlessedLoss => Wdl::BlessedLoss,
+            SyzygyWdl::Draw        => Wdl::Draw,
+            SyzygyWdl::CursedWin   => Wdl::CursedWin,
+            SyzygyWdl::Win         => Wdl::Win,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Time management: turning clock limits into a per-move search budget.
+
+use std::time::Duration;
+
+use crate::board::Color;
+use crate::search::SearchLimits;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A start time to measure from. `std::time::Instant` panics on `wasm32-unknown-unknown`, so
+/// there the clock is JavaScript's `Date.now()` with the `wasm` feature, and otherwise a clock
+/// that never advances, leaving only depth and node limits to end a search.
+#[derive(Copy, Clone, Debug)]
+pub struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    start_ms: f64,
+}
+
+/// Soft and hard deadlines for one search. The soft deadline stops new iterations from
+/// starting; the hard deadline aborts the iteration in progress.
+#[derive(Copy, Clone, Debug)]
+pub struct TimeManager {
+    start: Stopwatch,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+}
+
+/// Reserved per move for communication lag between engine and GUI, unless configured.
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// Moves assumed left in the game when the GUI does not send `movestogo`.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+// ============================================================================
+// Stopwatch
+// ============================================================================
+
+impl Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start() -> Self { Stopwatch { start: std::time::Instant::now() } }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn start() -> Self { Stopwatch { start_ms: now_ms() } }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn elapsed(&self) -> Duration { self.start.elapsed() }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((now_ms() - self.start_ms).max(0.0) / 1000.0)
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn now_ms() -> f64 { js_sys::Date::now() }
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+fn now_ms() -> f64 { 0.0 }
+
+// ============================================================================
+// TimeManager — Construction
+// ============================================================================
+
+impl TimeManager {
+    /// Budget a search by `color` under `limits`, starting now, keeping `overhead` of the clock
+    /// back for lag. Without a movetime or a clock for that side, neither deadline is set.
+    pub fn new(limits: &SearchLimits, color: Color, overhead: Duration) -> Self {
+        let start = Stopwatch::start();
+        if limits.infinite { return TimeManager { start, soft: None, hard: None }; }
+        if let Some(movetime) = limits.movetime {
+            return TimeManager { start, soft: Some(movetime), hard: Some(movetime) };
+        }
+
+        let (time, increment) = match color {
+            Color::White => (limits.wtime, limits.winc),
+            Color::Black => (limits.btime, limits.binc),
+        };
+        let Some(time) = time else { return TimeManager { start, soft: None, hard: None }; };
+
+        let remaining = time.saturating_sub(overhead);
+        let increment = increment.unwrap_or_default();
+        let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).clamp(1, 50);
+
+        // Never plan to spend more than half of what is left on one move
+        let hard = (remaining / moves_to_go * 4 + increment).min(remaining / 2);
+        let soft = (remaining / moves_to_go + increment * 3 / 4).min(hard);
+        TimeManager { start, soft: Some(soft), hard: Some(hard) }
+    }
+}
+
+// ============================================================================
+// TimeManager — Queries
+// ============================================================================
+
+impl TimeManager {
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Whether there is no point starting another iteration.
+    pub fn soft_expired(&self) -> bool {
+        self.soft.is_some_and(|soft| self.elapsed() >= soft)
+    }
+
+    /// Whether the search must stop immediately.
+    pub fn hard_expired(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+}
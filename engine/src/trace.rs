@@ -0,0 +1,146 @@
+//! Search tree recording for debugging, behind the `trace` feature. Without the feature the
+//! search has no hooks at all.
+
+use std::fmt::Write;
+
+use crate::mv::Move;
+use crate::score::Score;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// How a node's score compares to the window it was searched with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeType {
+    /// Inside the window: an exact score.
+    Pv,
+    /// At or above beta: a lower bound.
+    Cut,
+    /// At or below alpha: an upper bound.
+    All,
+}
+
+/// One searched node. A move searched twice, as after a reduced search that beat alpha,
+/// appears twice among its parent's children.
+#[derive(Clone, Debug)]
+pub struct TraceNode {
+    /// The move leading here; None at the root and after a null move.
+    pub mv: Option<Move>,
+    pub depth: u32,
+    pub alpha: Score,
+    pub beta: Score,
+    pub score: Score,
+    pub node_type: NodeType,
+    pub children: Vec<TraceNode>,
+}
+
+/// The tree of the last completed iteration, down to the traced ply limit.
+#[derive(Clone, Debug)]
+pub struct SearchTrace {
+    pub root: TraceNode,
+}
+
+/// Builds the tree as the search enters and leaves nodes.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tracer {
+    max_ply: u32,
+    next_move: Option<Move>,
+    stack: Vec<TraceNode>,
+    finished: Option<TraceNode>,
+}
+
+// ============================================================================
+// Tracer
+// ============================================================================
+
+impl Tracer {
+    /// Record nodes shallower than `max_ply`; zero records nothing.
+    pub(crate) fn new(max_ply: u32) -> Self {
+        Tracer { max_ply, ..Self::default() }
+    }
+
+    /// The move the next entered node is reached by.
+    pub(crate) fn next_move(&mut self, mv: Option<Move>) {
+        self.next_move = mv;
+    }
+
+    pub(crate) fn enter(&mut self, ply: u32, depth: u32, alpha: Score, beta: Score) {
+        let mv = self.next_move.take();
+        if ply >= self.max_ply { return; }
+        self.stack.push(TraceNode { mv, depth, alpha, beta, score: Score::ZERO, node_type: NodeType::Pv, children: Vec::new() });
+    }
+
+    pub(crate) fn exit(&mut self, ply: u32, score: Score) {
+        if ply >= self.max_ply { return; }
+        let mut node = self.stack.pop().expect("trace exit without enter");
+        node.score = score;
+        node.node_type = if score >= node.beta { NodeType::Cut } else if score <= node.alpha { NodeType::All } else { NodeType::Pv };
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.finished = Some(node),
+        }
+    }
+
+    /// The tree of the iteration that just completed.
+    pub(crate) fn take(&mut self) -> Option<SearchTrace> {
+        self.finished.take().map(|root| SearchTrace { root })
+    }
+}
+
+// ============================================================================
+// SearchTrace — Output
+// ============================================================================
+
+impl SearchTrace {
+    /// One node per line, indented two spaces per ply:
+    /// `e2e4 depth 3 [-32000, 32000] score 35 pv`.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        write_text(&self.root, 0, &mut text);
+        text
+    }
+
+    /// The tree as nested JSON objects, moves in long algebraic notation.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write_json(&self.root, &mut json);
+        json
+    }
+}
+
+fn write_text(node: &TraceNode, indent: usize, text: &mut String) {
+    let mv = match node.mv {
+        Some(mv) => mv.to_string(),
+        None if indent == 0 => "root".to_string(),
+        None => "null".to_string(),
+    };
+    let _ = writeln!(
+        text, "{:indent$}{} depth {} [{}, {}] score {} {}",
+        "", mv, node.depth, node.alpha.raw(), node.beta.raw(), node.score.raw(), node_type_name(node.node_type), indent = indent * 2,
+    );
+    for child in &node.children {
+        write_text(child, indent + 1, text);
+    }
+}
+
+fn write_json(node: &TraceNode, json: &mut String) {
+    let mv = node.mv.map_or("null".to_string(), |mv| format!("\"{}\"", mv));
+    let _ = write!(
+        json, "{{\"move\":{},\"depth\":{},\"alpha\":{},\"beta\":{},\"score\":{},\"type\":\"{}\",\"children\":[",
+        mv, node.depth, node.alpha.raw(), node.beta.raw(), node.score.raw(), node_type_name(node.node_type),
+    );
+    for (i, child) in node.children.iter().enumerate() {
+        if i > 0 { json.push(','); }
+        write_json(child, json);
+    }
+    json.push_str("]}");
+}
+
+fn node_type_name(node_type: NodeType) -> &'static str {
+    match node_type {
+        NodeType::Pv  => "pv",
+        NodeType::Cut => "cut",
+        NodeType::All => "all",
+    }
+}
@@ -0,0 +1,470 @@
+//! Training data from self-play: one fixed-size binary record per searched position, written
+//! by the generator as games finish, filtered, and streamed back for the tuner. A file is a
+//! header followed by the records, all little-endian:
+//!
+//! - header: `MAGIC`, `VERSION` as `u16`, the record count as `u64`
+//! - record: the packed position (28 bytes), side to move `u8`, search score `i16` for the
+//!   side to move, game result `i8` for White (1, 0, -1), and ply `u16`
+//!
+//! The packed position is the occupancy as a `u64`, a nibble per occupied square from a1
+//! upward holding the piece's color and type, the castling rights with their rook files as a
+//! `u16`, the en passant square (64 for none), and the halfmove clock.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::board::{Board, Color, Piece, Square};
+use crate::builder::validate;
+use crate::castling::CastlingRights;
+use crate::game::Game;
+use crate::score::Score;
+use crate::search::{Search, SearchLimits};
+use crate::state::{AttackCache, GameStatus, State};
+use crate::zobrist::hash_state;
+
+/// First bytes of every training file.
+pub const MAGIC: [u8; 4] = *b"BCTD";
+
+/// Bumped whenever the record layout changes; readers reject any other version.
+pub const VERSION: u16 = 1;
+
+/// Magic, version, and count.
+const HEADER_SIZE: usize = 14;
+
+/// Where the count sits in the header, patched once the writer knows it.
+const COUNT_OFFSET: u64 = 6;
+
+/// Occupancy, sixteen bytes of nibbles for up to 32 pieces, castling, en passant, and clock.
+const PACKED_SIZE: usize = 28;
+
+/// The packed position, side to move, score, result, and ply.
+pub const RECORD_SIZE: usize = PACKED_SIZE + 6;
+
+/// The en passant byte when there is no en passant square.
+const NO_EN_PASSANT: u8 = 64;
+
+/// Scores at least this far from zero are mates, or tablebase wins just short of them, which
+/// say nothing about how the evaluation should weigh the position; `filter` drops them.
+const NEAR_MATE: i32 = Score::MAX_CP - 1000;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One position from a self-play game.
+#[derive(Clone, Debug)]
+pub struct TrainingRecord {
+    /// The position searched. The fullmove number is not stored; it is rebuilt from `ply`.
+    pub state: State,
+    /// The search's score, in centipawns or as a raw mate score, for the side to move.
+    pub score: i16,
+    /// How the game ended, from White's side: 1 for a win, 0 for a draw, -1 for a loss.
+    pub result: i8,
+    /// Plies played in the game before this position.
+    pub ply: u16,
+}
+
+/// Writes the header up front and patches its count in `finish`, so records can be written as
+/// games end without holding them.
+pub struct TrainingWriter<W: Write + Seek> {
+    inner: W,
+    count: u64,
+}
+
+/// Reads one record at a time, as many as the header counts. Wrap files in a `BufReader`.
+pub struct TrainingReader<R: Read> {
+    inner: R,
+    remaining: u64,
+}
+
+/// How a self-play run is set up.
+#[derive(Clone, Debug)]
+pub struct SelfPlayOptions {
+    pub games: u32,
+    /// Depth of the search behind every move and score.
+    pub depth: u32,
+    /// Random moves that open each game, so that the games differ. They are not recorded.
+    pub random_plies: u32,
+    /// Games still going after this many plies are adjudicated drawn.
+    pub max_plies: u32,
+    /// Seeds the random openings; the same seed plays the same games.
+    pub seed: u64,
+}
+
+/// What `filter` kept and why it dropped the rest.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilterReport {
+    pub kept: u64,
+    /// Positions whose zobrist key was already seen.
+    pub duplicates: u64,
+    pub in_check: u64,
+    pub near_mate: u64,
+}
+
+// ============================================================================
+// Position Packing
+// ============================================================================
+
+fn pack(state: &State, bytes: &mut [u8]) {
+    let occupied = state.board.occupied();
+    bytes[0..8].copy_from_slice(&occupied.bits().to_le_bytes());
+    for (index, square) in occupied.into_iter().enumerate() {
+        let nibble = state.board[square].expect("occupied square").bits() & 0x0f;
+        bytes[8 + index / 2] |= nibble << (4 * (index % 2));
+    }
+    bytes[24..26].copy_from_slice(&state.castling_rights.bits().to_le_bytes());
+    bytes[26] = state.en_passant.map_or(NO_EN_PASSANT, Square::value);
+    bytes[27] = state.halfmove_clock;
+}
+
+/// The position back, checked the way a FEN would be, since the bytes may be anything.
+fn unpack(bytes: &[u8], to_move: Color, ply: u16) -> io::Result<State> {
+    let occupied = u64::from_le_bytes(bytes[0..8].try_into().expect("eight bytes"));
+    if occupied.count_ones() > 32 { return Err(invalid("more than 32 pieces")); }
+
+    let mut board = Board::new();
+    let squares = (0..64).filter(|&index| occupied & (1u64 << index) != 0);
+    for (index, square) in squares.enumerate() {
+        let nibble = (bytes[8 + index / 2] >> (4 * (index % 2))) & 0x0f;
+        let piece = Piece::from_bits(0x80 | nibble).ok_or_else(|| invalid(format!("invalid piece nibble {:#x}", nibble)))?;
+        board.set_piece(piece, Square::from_index(square));
+    }
+    let en_passant = match bytes[26] {
+        NO_EN_PASSANT => None,
+        index if index < 64 => Some(Square::from_index(index as usize)),
+        index => return Err(invalid(format!("en passant square {} out of range", index))),
+    };
+
+    let mut state = State {
+        board,
+        to_move,
+        castling_rights: CastlingRights::from_bits(u16::from_le_bytes([bytes[24], bytes[25]])),
+        en_passant,
+        halfmove_clock: bytes[27],
+        fullmove_number: 1 + ply / 2,
+        hash: 0,
+        attacks: AttackCache::default(),
+    };
+    state.hash = hash_state(&state);
+    validate(&state).map_err(|err| invalid(err.to_string()))?;
+    Ok(state)
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+// ============================================================================
+// TrainingRecord — Encoding
+// ============================================================================
+
+impl TrainingRecord {
+    pub fn to_bytes(&self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        pack(&self.state, &mut bytes[..PACKED_SIZE]);
+        bytes[28] = self.state.to_move as u8;
+        bytes[29..31].copy_from_slice(&self.score.to_le_bytes());
+        bytes[31] = self.result as u8;
+        bytes[32..34].copy_from_slice(&self.ply.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; RECORD_SIZE]) -> io::Result<Self> {
+        let to_move = match bytes[28] {
+            0 => Color::White,
+            1 => Color::Black,
+            side => return Err(invalid(format!("invalid side to move {}", side))),
+        };
+        let result = bytes[31] as i8;
+        if !(-1..=1).contains(&result) { return Err(invalid(format!("invalid result {}", result))); }
+        let ply = u16::from_le_bytes([bytes[32], bytes[33]]);
+        Ok(TrainingRecord {
+            state: unpack(&bytes[..PACKED_SIZE], to_move, ply)?,
+            score: i16::from_le_bytes([bytes[29], bytes[30]]),
+            result,
+            ply,
+        })
+    }
+}
+
+// ============================================================================
+// TrainingWriter
+// ============================================================================
+
+impl<W: Write + Seek> TrainingWriter<W> {
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+        header[0..4].copy_from_slice(&MAGIC);
+        header[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        inner.write_all(&header)?;
+        Ok(TrainingWriter { inner, count: 0 })
+    }
+
+    pub fn write(&mut self, record: &TrainingRecord) -> io::Result<()> {
+        self.inner.write_all(&record.to_bytes())?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Records written so far.
+    pub fn count(&self) -> u64 { self.count }
+
+    /// Fill in the header's count and hand back the stream, positioned at its end. A file
+    /// whose writer is dropped unfinished reads as empty.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.seek(SeekFrom::Start(COUNT_OFFSET))?;
+        self.inner.write_all(&self.count.to_le_bytes())?;
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+// ============================================================================
+// TrainingReader
+// ============================================================================
+
+impl<R: Read> TrainingReader<R> {
+    /// Read and check the header, leaving the records for the iterator.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+        inner.read_exact(&mut header)?;
+        if header[0..4] != MAGIC { return Err(invalid("not a training file")); }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != VERSION {
+            return Err(invalid(format!("training file version {}, expected {}", version, VERSION)));
+        }
+        let remaining = u64::from_le_bytes(header[6..14].try_into().expect("eight bytes"));
+        Ok(TrainingReader { inner, remaining })
+    }
+
+    /// Records not yet read.
+    pub fn remaining(&self) -> u64 { self.remaining }
+}
+
+/// Each record in turn; the first error, a short file included, ends the iteration.
+impl<R: Read> Iterator for TrainingReader<R> {
+    type Item = io::Result<TrainingRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 { return None; }
+        let mut bytes = [0; RECORD_SIZE];
+        let record = self.inner.read_exact(&mut bytes).and_then(|()| TrainingRecord::from_bytes(&bytes));
+        self.remaining = if record.is_ok() { self.remaining - 1 } else { 0 };
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, usize::try_from(self.remaining).ok())
+    }
+}
+
+// ============================================================================
+// Filtering
+// ============================================================================
+
+/// Copy the records worth training on: the first of each zobrist key, and none in check or
+/// scored near mate, since the static evaluation can judge neither.
+pub fn filter<R: Read, W: Write + Seek>(reader: TrainingReader<R>, writer: &mut TrainingWriter<W>) -> io::Result<FilterReport> {
+    let mut report = FilterReport::default();
+    let mut seen = HashSet::new();
+    for record in reader {
+        let record = record?;
+        if !seen.insert(record.state.hash()) {
+            report.duplicates += 1;
+        } else if record.state.in_check() {
+            report.in_check += 1;
+        } else if (record.score as i32).abs() >= NEAR_MATE {
+            report.near_mate += 1;
+        } else {
+            writer.write(&record)?;
+            report.kept += 1;
+        }
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// Self-Play
+// ============================================================================
+
+/// Play `options.games` games against itself, writing every searched position with the game's
+/// result once the game ends. Returns the records written.
+pub fn self_play<W: Write + Seek>(options: &SelfPlayOptions, writer: &mut TrainingWriter<W>) -> io::Result<u64> {
+    let mut search = Search::new();
+    let mut seed = options.seed | 1;
+    let start = writer.count();
+    for _ in 0..options.games {
+        search.clear_hash();
+        let mut game = Game::new();
+        for _ in 0..options.random_plies {
+            if game.status() != GameStatus::Ongoing { break; }
+            let moves: Vec<_> = game.current().moves().all().collect();
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            game.play(moves[(seed % moves.len() as u64) as usize]).expect("generated moves are legal");
+        }
+
+        let mut positions = Vec::new();
+        while game.status() == GameStatus::Ongoing && game.moves_played().len() < options.max_plies as usize {
+            let result = search.best_move_in(&game, SearchLimits::depth(options.depth));
+            let Some(mv) = result.best_move else { break };
+            positions.push((game.current().clone(), result.score.raw() as i16, game.moves_played().len() as u16));
+            game.play(mv).expect("the search plays legal moves");
+        }
+
+        let result = match game.status() {
+            GameStatus::Checkmate(Color::White) => 1,
+            GameStatus::Checkmate(Color::Black) => -1,
+            _ => 0,
+        };
+        for (state, score, ply) in positions {
+            writer.write(&TrainingRecord { state, score, result, ply })?;
+        }
+    }
+    Ok(writer.count() - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Records along seeded random playouts from the start, scored and resulted arbitrarily.
+    fn random_records(games: usize, plies: usize) -> Vec<TrainingRecord> {
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        let mut records = Vec::new();
+        for game in 0..games {
+            let mut state = State::start();
+            for ply in 0..plies {
+                let moves: Vec<_> = state.moves().all().collect();
+                if moves.is_empty() { break; }
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                records.push(TrainingRecord { state: state.clone(), score: (seed % 2001) as i16 - 1000, result: (game % 3) as i8 - 1, ply: ply as u16 });
+                state = state.apply_move(moves[(seed % moves.len() as u64) as usize]);
+            }
+        }
+        records
+    }
+
+    fn written(records: &[TrainingRecord]) -> Vec<u8> {
+        let mut writer = TrainingWriter::new(Cursor::new(Vec::new())).unwrap();
+        for record in records { writer.write(record).unwrap(); }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read(bytes: &[u8]) -> Vec<TrainingRecord> {
+        TrainingReader::new(bytes).unwrap().collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn records_survive_a_write_and_read() {
+        let records = random_records(20, 120);
+        let bytes = written(&records);
+        assert_eq!(bytes.len(), HEADER_SIZE + records.len() * RECORD_SIZE);
+        let back = read(&bytes);
+        assert_eq!(back.len(), records.len());
+        for (record, back) in records.iter().zip(&back) {
+            assert_eq!(back.state.to_fen(), record.state.to_fen());
+            assert_eq!(back.state.hash(), record.state.hash());
+            assert_eq!((back.score, back.result, back.ply), (record.score, record.result, record.ply));
+        }
+    }
+
+    #[test]
+    fn self_play_writes_readable_games() {
+        let options = SelfPlayOptions { games: 2, depth: 1, random_plies: 6, max_plies: 40, seed: 7 };
+        let mut writer = TrainingWriter::new(Cursor::new(Vec::new())).unwrap();
+        let written = self_play(&options, &mut writer).unwrap();
+        assert!(written > 0);
+        let records = read(&writer.finish().unwrap().into_inner());
+        assert_eq!(records.len() as u64, written);
+        assert!(records.iter().all(|record| record.ply >= 6 && record.ply < 40));
+    }
+
+    #[test]
+    fn a_bumped_version_is_rejected() {
+        let mut bytes = written(&random_records(1, 4));
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        let err = TrainingReader::new(bytes.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("version"), "{}", err);
+        assert!(TrainingReader::new(&b"not a training file"[..]).is_err());
+    }
+
+    #[test]
+    fn filtering_drops_repeats_checks_and_mates() {
+        let records = random_records(3, 30);
+        let quiet = TrainingRecord { state: State::start(), score: 20, result: 0, ply: 0 };
+        let check = State::from_fen("rnbqkbnr/ppp2ppp/3p4/1B2p3/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 3").unwrap();
+        let mate = State::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+        let extra = [
+            quiet.clone(),
+            TrainingRecord { state: check, score: 0, result: 0, ply: 4 },
+            TrainingRecord { state: mate, score: Score::mate_in(1).raw() as i16, result: 1, ply: 0 },
+        ];
+        let all: Vec<TrainingRecord> = records.iter().chain(&extra).chain(&records).cloned().collect();
+        let mut seen = HashSet::new();
+        let firsts: Vec<&TrainingRecord> = all.iter().filter(|record| seen.insert(record.state.hash())).collect();
+        let distinct = firsts.len() as u64;
+        let checks = firsts.iter().filter(|record| record.state.in_check()).count() as u64;
+
+        let mut writer = TrainingWriter::new(Cursor::new(Vec::new())).unwrap();
+        let report = filter(TrainingReader::new(written(&all).as_slice()).unwrap(), &mut writer).unwrap();
+        assert_eq!(report.duplicates, all.len() as u64 - distinct);
+        assert!(report.duplicates >= records.len() as u64);
+        assert_eq!((report.in_check, report.near_mate), (checks, 1));
+        let kept = read(&writer.finish().unwrap().into_inner());
+        assert_eq!(kept.len() as u64, report.kept);
+        assert_eq!(report.kept + report.in_check + report.near_mate, distinct);
+        assert_eq!(kept.iter().map(|record| record.state.hash()).collect::<HashSet<_>>().len(), kept.len());
+    }
+
+    /// A file's bytes produced on demand, never held whole, counting what has been read.
+    struct Generated {
+        header: Vec<u8>,
+        record: [u8; RECORD_SIZE],
+        len: u64,
+        position: u64,
+    }
+
+    impl Read for Generated {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut filled = 0;
+            while filled < buf.len() && self.position < self.len {
+                let position = self.position as usize;
+                buf[filled] = match position.checked_sub(HEADER_SIZE) {
+                    None => self.header[position],
+                    Some(offset) => self.record[offset % RECORD_SIZE],
+                };
+                filled += 1;
+                self.position += 1;
+            }
+            Ok(filled)
+        }
+    }
+
+    #[test]
+    fn a_large_file_is_read_a_record_at_a_time() {
+        const COUNT: u64 = 200_000;
+        let sample = written(&random_records(1, 10));
+        let mut header = sample[..HEADER_SIZE].to_vec();
+        header[6..14].copy_from_slice(&COUNT.to_le_bytes());
+        let record = sample[HEADER_SIZE + 9 * RECORD_SIZE..][..RECORD_SIZE].try_into().unwrap();
+        let len = (HEADER_SIZE + COUNT as usize * RECORD_SIZE) as u64;
+        let mut source = Generated { header, record, len, position: 0 };
+
+        let mut reader = TrainingReader::new(&mut source).unwrap();
+        assert_eq!(reader.remaining(), COUNT);
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.ply, 9);
+        assert_eq!(reader.by_ref().take(999).count(), 999);
+        assert_eq!(source.position, (HEADER_SIZE + 1000 * RECORD_SIZE) as u64);
+
+        let reader = TrainingReader::new(Generated { position: 0, ..source }).unwrap();
+        assert_eq!(reader.map(Result::unwrap).filter(|record| record.ply == 9).count() as u64, COUNT);
+    }
+}
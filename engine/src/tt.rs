@@ -0,0 +1,243 @@
+//! Transposition table keyed by Zobrist hash, shared between search threads without locks.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::mv::Move;
+use crate::score::Score;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// How a stored score relates to the true value of the position.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact.
+    Exact = 1,
+    /// The search failed high; the true score is at least this.
+    Lower = 2,
+    /// The search failed low; the true score is at most this.
+    Upper = 3,
+}
+
+/// What a probe returns. Mate scores are already relative to the probing ply.
+#[derive(Copy, Clone, Debug)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub depth: u32,
+    pub bound: Bound,
+}
+
+/// Fixed-size table with one entry per slot, replaced by depth or age. Probes and stores take
+/// `&self`, so every search thread can use one table at once.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: u8,
+}
+
+/// One entry as two atomic words: the packed data, and the key XORed with it. A write torn
+/// by another thread leaves the pair inconsistent, so it reads back as a miss rather than as a
+/// wrong entry. An empty slot has zero data, since every bound is non-zero.
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+/// The fields of an entry, packed into a slot's data word.
+#[derive(Copy, Clone)]
+struct Packed {
+    mv: u16,
+    score: i16,
+    depth: u8,
+    meta: u8,   // bound in the low 2 bits, search age above
+}
+
+const _: () = assert!(size_of::<Slot>() == 16);
+
+pub const DEFAULT_MEGABYTES: usize = 16;
+
+// ============================================================================
+// TranspositionTable — Construction
+// ============================================================================
+
+impl TranspositionTable {
+    /// A table of at most `megabytes`, rounded down to a power-of-two number of entries.
+    pub fn new(megabytes: usize) -> Self {
+        let count = (megabytes * 1024 * 1024 / size_of::<Slot>()).max(1);
+        let count = 1 << count.ilog2();
+        TranspositionTable { slots: (0..count).map(|_| Slot::default()).collect(), age: 0 }
+    }
+
+    pub fn resize(&mut self, megabytes: usize) {
+        *self = Self::new(megabytes);
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = Slot::default());
+        self.age = 0;
+    }
+
+    /// Mark the start of a new search, so entries from earlier ones lose replacement priority.
+    pub fn new_search(&mut self) {
+        self.age = (self.age + 1) & 0x3F;
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Permille of the table written during the current search, estimated from its first
+    /// thousand slots as UCI `hashfull` expects.
+    pub fn hashfull(&self) -> u32 {
+        let sample = &self.slots[..self.slots.len().min(1000)];
+        let current = sample.iter()
+            .map(|slot| Packed::unpack(slot.data.load(Ordering::Relaxed)).meta)
+            .filter(|&meta| meta != 0 && meta >> 2 == self.age)
+            .count();
+        (current * 1000 / sample.len()) as u32
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEGABYTES)
+    }
+}
+
+// ============================================================================
+// TranspositionTable — Probe & Store
+// ============================================================================
+
+impl TranspositionTable {
+    pub fn probe(&self, hash: u64, ply: u32) -> Option<TtEntry> {
+        let slot = self.load(hash)?;
+
+        Some(TtEntry {
+            best_move: (slot.mv != 0).then(|| Move::from_bits(slot.mv)),
+            score: Score::from_tt(slot.score, ply),
+            depth: slot.depth as u32,
+            bound: match slot.meta & 0b11 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+        })
+    }
+
+    /// Store a search result. The slot is kept only if it holds a deeper result for another
+    /// position from the current search.
+    pub fn store(&self, hash: u64, depth: u32, score: Score, bound: Bound, best_move: Option<Move>, ply: u32) {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let old = Packed::unpack(data);
+        let old_key = slot.check.load(Ordering::Relaxed) ^ data;
+
+        let stale = old.meta == 0 || old.meta >> 2 != self.age;
+        if !stale && old_key != hash && old.depth as u32 > depth { return; }
+
+        // Keep the old move when re-storing a position without one
+        let mv = match best_move {
+            Some(mv) => mv.bits(),
+            None if old_key == hash => old.mv,
+            None => 0,
+        };
+        let data = Packed {
+            mv,
+            score: score.to_tt(ply),
+            depth: depth.min(u8::MAX as u32) as u8,
+            meta: bound as u8 | (self.age << 2),
+        }.pack();
+        slot.data.store(data, Ordering::Relaxed);
+        slot.check.store(hash ^ data, Ordering::Relaxed);
+    }
+
+    /// The entry for `hash`, unless the slot is empty, holds another position, or was torn.
+    fn load(&self, hash: u64) -> Option<Packed> {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.check.load(Ordering::Relaxed) ^ data;
+        (data != 0 && key == hash).then(|| Packed::unpack(data))
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        hash as usize & (self.slots.len() - 1)
+    }
+}
+
+impl Packed {
+    const fn pack(self) -> u64 {
+        (self.mv as u64) | ((self.score as u16 as u64) << 16) | ((self.depth as u64) << 32) | ((self.meta as u64) << 40)
+    }
+
+    const fn unpack(data: u64) -> Self {
+        Packed { mv: data as u16, score: (data >> 16) as u16 as i16, depth: (data >> 32) as u8, meta: (data >> 40) as u8 }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square;
+
+    #[test]
+    fn resizing_changes_capacity_to_a_power_of_two() {
+        let mut tt = TranspositionTable::new(1);
+        assert_eq!(tt.len(), 1 << 16);
+        tt.resize(3);
+        assert_eq!(tt.len(), 1 << 17);
+        tt.resize(0);
+        assert_eq!(tt.len(), 1);
+    }
+
+    #[test]
+    fn entries_do_not_survive_a_resize_or_alias_other_keys() {
+        let mut tt = TranspositionTable::new(2);
+        let hashes: Vec<u64> = (1..=5000u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        for &hash in &hashes { tt.store(hash, 4, Score::cp(25), Bound::Exact, None, 0); }
+        assert!(hashes.iter().all(|&hash| tt.probe(hash, 0).is_some()));
+
+        tt.resize(1);
+        assert!(hashes.iter().all(|&hash| tt.probe(hash, 0).is_none()));
+        for &hash in &hashes { tt.store(hash, 4, Score::cp(25), Bound::Exact, None, 0); }
+        // A key sharing the low bits of a stored one lands in its slot but must not match it
+        assert!(hashes.iter().all(|&hash| tt.probe(hash ^ (1 << 63), 0).is_none()));
+    }
+
+    #[test]
+    fn hashfull_counts_only_the_current_search() {
+        let mut tt = TranspositionTable::new(1);
+        assert_eq!(tt.hashfull(), 0);
+        for index in 0..500u64 { tt.store(index, 1, Score::cp(0), Bound::Lower, None, 0); }
+        assert_eq!(tt.hashfull(), 500);
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+        tt.clear();
+        assert_eq!((tt.len(), tt.hashfull()), (1 << 16, 0));
+    }
+
+    #[test]
+    fn mates_stored_at_one_ply_read_back_relative_to_another() {
+        let tt = TranspositionTable::new(1);
+        let mv = Move::new(Square::E2, Square::E4);
+        // Mating 3 plies below a node at ply 6, and being mated 2 plies below one at ply 5
+        tt.store(0xABCD, 8, Score::mate_in(6 + 3), Bound::Exact, Some(mv), 6);
+        tt.store(0x1234, 8, Score::mated_in(5 + 2), Bound::Upper, None, 5);
+
+        let entry = tt.probe(0xABCD, 2).unwrap();
+        assert_eq!((entry.score, entry.best_move, entry.depth, entry.bound), (Score::mate_in(2 + 3), Some(mv), 8, Bound::Exact));
+        assert_eq!(tt.probe(0xABCD, 10).unwrap().score, Score::mate_in(10 + 3));
+        assert_eq!(tt.probe(0x1234, 0).unwrap().score, Score::mated_in(2));
+        assert_eq!(tt.probe(0x1234, 9).unwrap().score, Score::mated_in(9 + 2));
+    }
+}
@@ -0,0 +1,261 @@
+//! Texel tuning: fit the evaluation weights to game results by local search, minimizing the
+//! squared error between each position's result and its static evaluation mapped through a
+//! sigmoid. Quiet positions tune best, since the static evaluation cannot see exchanges.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::board::Color;
+use crate::eval::{evaluate_with, EvalParams};
+use crate::fen::FenError;
+use crate::state::State;
+use crate::training::{TrainingReader, MAGIC};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A position and the result of the game it came from.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub state: State,
+    /// From White's side: 1 for a win, 0.5 for a draw, 0 for a loss.
+    pub result: f64,
+}
+
+/// The samples of a data file, in file order.
+#[derive(Clone, Debug, Default)]
+pub struct Dataset {
+    pub samples: Vec<Sample>,
+    /// Lines that failed to parse, with their 1-based line numbers.
+    pub errors: Vec<(usize, SampleError)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SampleError {
+    /// The line has no result after the position.
+    MissingResult,
+    /// The last field is not a result.
+    BadResult(String),
+    /// The fields before the result are not a valid FEN.
+    BadPosition(FenError),
+}
+
+/// What a tuning run found.
+#[derive(Clone, Debug)]
+pub struct TuneReport {
+    pub params: EvalParams,
+    /// The sigmoid scaling constant, fitted to the starting weights.
+    pub k: f64,
+    pub initial_error: f64,
+    pub final_error: f64,
+    /// Passes over every parameter, the last of which may have found nothing to improve.
+    pub passes: u32,
+}
+
+// ============================================================================
+// Dataset Loading
+// ============================================================================
+
+impl Sample {
+    /// A FEN, four to six fields, followed by the result: `1-0`, `0-1`, `1/2-1/2`, or a
+    /// number such as `0.5`. Brackets, quotes, and a trailing `;` around the result are
+    /// ignored, so `[1.0]` and `"1-0";` both work.
+    pub fn parse(line: &str) -> Result<Self, SampleError> {
+        let (fen, result) = line.trim().rsplit_once(char::is_whitespace).ok_or(SampleError::MissingResult)?;
+        let result = parse_result(result)?;
+        let state = State::from_fen(fen.trim()).map_err(SampleError::BadPosition)?;
+        Ok(Sample { state, result })
+    }
+}
+
+fn parse_result(text: &str) -> Result<f64, SampleError> {
+    let trimmed = text.trim_matches(|c| matches!(c, '[' | ']' | '"' | ';'));
+    match trimmed {
+        "1-0" => Ok(1.0),
+        "0-1" => Ok(0.0),
+        "1/2-1/2" => Ok(0.5),
+        _ => trimmed.parse::<f64>().ok()
+            .filter(|result| (0.0..=1.0).contains(result))
+            .ok_or_else(|| SampleError::BadResult(text.to_string())),
+    }
+}
+
+/// Read one sample per line. Blank lines and `#` comments are skipped. A self-play training
+/// file, told apart by its magic, is streamed record by record instead, each game's result
+/// becoming the sample's.
+pub fn load_dataset(path: impl AsRef<Path>) -> io::Result<Dataset> {
+    let mut file = BufReader::new(File::open(path)?);
+    if file.fill_buf()?.starts_with(&MAGIC) {
+        let samples = TrainingReader::new(file)?
+            .map(|record| record.map(|record| Sample { state: record.state, result: (record.result as f64 + 1.0) / 2.0 }))
+            .collect::<io::Result<_>>()?;
+        return Ok(Dataset { samples, errors: Vec::new() });
+    }
+
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    let mut dataset = Dataset::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        match Sample::parse(line) {
+            Ok(sample) => dataset.samples.push(sample),
+            Err(err) => dataset.errors.push((index + 1, err)),
+        }
+    }
+    Ok(dataset)
+}
+
+// ============================================================================
+// Error Measurement
+// ============================================================================
+
+/// Mean squared difference between each result and the expected score the evaluation
+/// predicts for it.
+pub fn error(samples: &[Sample], params: &EvalParams, k: f64) -> f64 {
+    if samples.is_empty() { return 0.0; }
+    let total: f64 = samples.iter()
+        .map(|sample| (sample.result - expected_score(white_eval(&sample.state, params), k)).powi(2))
+        .sum();
+    total / samples.len() as f64
+}
+
+/// The `k` that minimizes the error for fixed weights, found by golden-section search.
+pub fn fit_k(samples: &[Sample], params: &EvalParams) -> f64 {
+    const RATIO: f64 = 0.618_033_988_749_895;
+    let (mut low, mut high) = (0.0, 4.0);
+    while high - low > 1e-4 {
+        let a = high - RATIO * (high - low);
+        let b = low + RATIO * (high - low);
+        if error(samples, params, a) < error(samples, params, b) { high = b; } else { low = a; }
+    }
+    (low + high) / 2.0
+}
+
+/// Expected score for White, from 0 to 1, of a centipawn evaluation from White's side.
+fn expected_score(eval: i32, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval as f64 / 400.0))
+}
+
+fn white_eval(state: &State, params: &EvalParams) -> i32 {
+    let score = evaluate_with(state, params);
+    match state.to_move { Color::White => score, Color::Black => -score }
+}
+
+// ============================================================================
+// Tuning
+// ============================================================================
+
+/// Nudge each value of `params` by one in turn, keeping any change that lowers the error, until
+/// a whole pass improves nothing or `max_passes` have run. `on_pass` hears each pass number
+/// and the error after it.
+pub fn tune(samples: &[Sample], params: EvalParams, max_passes: u32, mut on_pass: impl FnMut(u32, f64)) -> TuneReport {
+    let k = fit_k(samples, &params);
+    let initial_error = error(samples, &params, k);
+    let (mut params, mut best, mut passes) = (params, initial_error, 0);
+
+    while passes < max_passes {
+        passes += 1;
+        let mut improved = false;
+        for index in 0..params.len() {
+            let original = params.get(index);
+            for candidate in [original + 1, original - 1] {
+                params.set(index, candidate);
+                let candidate_error = error(samples, &params, k);
+                if candidate_error < best {
+                    best = candidate_error;
+                    improved = true;
+                    break;
+                }
+                params.set(index, original);
+            }
+        }
+        on_pass(passes, best);
+        if !improved { break; }
+    }
+    TuneReport { params, k, initial_error, final_error: best, passes }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+impl Display for SampleError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            SampleError::MissingResult   => write!(f, "missing result"),
+            SampleError::BadResult(text) => write!(f, "invalid result '{}'", text),
+            SampleError::BadPosition(e)  => write!(f, "invalid position: {}", e),
+        }
+    }
+}
+
+impl Error for SampleError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::DEFAULT_PARAMS;
+
+    /// Index of the pawn's material value in `EvalParams`.
+    const PAWN_VALUE: usize = 4;
+
+    /// Kings and pawns with White a pawn up, and kings and queens with White a queen up.
+    const PAWN_UP: [&str; 4] = [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/pp6/8/8/8/8/PPP5/4K3 b - - 0 1",
+        "6k1/5ppp/8/8/8/8/4PPPP/6K1 w - - 0 1",
+        "8/3k4/8/8/2P5/8/1P6/2K5 b - - 0 1",
+    ];
+    const QUEEN_UP: [&str; 2] = ["3qk3/8/8/8/8/8/8/1Q1QK3 w - - 0 1", "4k3/8/8/8/8/8/8/Q3K3 b - - 0 1"];
+
+    /// Each pawn-up position with `pawn_result`, alongside queen-up wins that anchor `k`.
+    fn samples(pawn_result: f64) -> Vec<Sample> {
+        let sample = |fen: &str, result| Sample { state: State::from_fen(fen).unwrap(), result };
+        PAWN_UP.iter().map(|fen| sample(fen, pawn_result))
+            .chain(QUEEN_UP.iter().map(|fen| sample(fen, 1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn a_pawn_that_wins_games_gains_value() {
+        let report = tune(&samples(1.0), DEFAULT_PARAMS, 1, |_, _| {});
+        assert!(report.params.piece_values[PAWN_VALUE] > DEFAULT_PARAMS.piece_values[PAWN_VALUE]);
+        assert!(report.final_error < report.initial_error);
+    }
+
+    #[test]
+    fn a_pawn_that_only_draws_loses_value() {
+        let report = tune(&samples(0.5), DEFAULT_PARAMS, 1, |_, _| {});
+        assert!(report.params.piece_values[PAWN_VALUE] < DEFAULT_PARAMS.piece_values[PAWN_VALUE]);
+        assert!(report.final_error < report.initial_error);
+    }
+
+    #[test]
+    fn a_training_file_loads_as_samples() {
+        use crate::training::{TrainingRecord, TrainingWriter};
+
+        let path = std::env::temp_dir().join(format!("blitz-tuner-{}.bin", std::process::id()));
+        let mut writer = TrainingWriter::new(File::create(&path).unwrap()).unwrap();
+        for (fen, result) in [(PAWN_UP[0], 1), (QUEEN_UP[1], -1), (PAWN_UP[1], 0)] {
+            writer.write(&TrainingRecord { state: State::from_fen(fen).unwrap(), score: 0, result, ply: 0 }).unwrap();
+        }
+        writer.finish().unwrap();
+        let dataset = load_dataset(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let dataset = dataset.unwrap();
+        assert!(dataset.errors.is_empty());
+        let results: Vec<f64> = dataset.samples.iter().map(|sample| sample.result).collect();
+        assert_eq!(results, [1.0, 0.0, 0.5]);
+        assert_eq!(dataset.samples[1].state.to_fen(), QUEEN_UP[1]);
+    }
+}
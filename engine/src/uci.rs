@@ -0,0 +1,286 @@
+//! UCI (Universal Chess Interface) front end.
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::book::{BookPolicy, PolyglotBook};
+use crate::controller::{EngineController, Report};
+use crate::eval::explain;
+use crate::game::Game;
+use crate::mv::Move;
+use crate::options::Options;
+use crate::search::{SearchInfo, SearchLimits, SearchResult};
+use crate::state::State;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// The options and what is loaded from them. A `setoption` that arrives during a search is
+/// held back until the search is over, as the hash table cannot be resized under it.
+struct Settings {
+    options: Options,
+    book: Option<PolyglotBook>,
+    /// Remembers, until `ucinewgame`, whether the game has left the book.
+    book_policy: BookPolicy,
+    pending: Vec<(String, String)>,
+}
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Run the UCI loop until `quit` or end of input. Unknown commands are ignored; `eval`, an
+/// extension, prints the evaluation of the current position term by term. Searches run on
+/// a worker thread so that `stop` and `isready` are answered while thinking; at end of input
+/// the last search is allowed to finish, or stopped if it is infinite. On wasm32 each search blocks the loop instead. The
+/// options are those of `Options::builtin`; with `OwnBook` on, a position in the `BookFile`
+/// book is answered from it without searching, as `Book Variety` and `Book Depth` direct,
+/// until the game first reaches a position the book has no move for.
+pub fn run(input: impl BufRead, output: impl Write + Send + 'static) -> io::Result<()> {
+    let mut engine = EngineController::new(output);
+    let mut settings = Settings { options: Options::builtin(), book: None, book_policy: BookPolicy::default(), pending: Vec::new() };
+    let mut infinite = false;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        settings.apply(&mut engine)?;
+
+        match tokens.next() {
+            Some("uci") => {
+                engine.send(&format!("id name blitz-chess {}", env!("CARGO_PKG_VERSION")))?;
+                engine.send("id author urav06")?;
+                for line in settings.options.uci_lines() { engine.send(&line)?; }
+                engine.send("uciok")?;
+            }
+            Some("isready") => engine.send("readyok")?,
+            Some("ucinewgame") => {
+                // Sent mid-search only by a GUI that forgot `stop`; end the search rather than hang
+                engine.stop()?;
+                *engine.game()? = Game::new();
+                engine.wait()?.clear_hash();
+                settings.book_policy.new_game();
+            }
+            Some("position") => {
+                if let Some(game) = parse_position(tokens) { *engine.game()? = game; }
+            }
+            Some("setoption") => {
+                if let Some((name, value)) = parse_setoption(tokens) { settings.set(&mut engine, name, value)?; }
+            }
+            Some("go") => {
+                engine.wait()?;
+                settings.apply(&mut engine)?;
+                let chess960 = settings.options.get::<bool>("UCI_Chess960").unwrap_or(false);
+                let state = engine.game()?.current();
+                match settings.book_move(state) {
+                    Some(mv) => {
+                        engine.send("info string book move")?;
+                        engine.send(&format_bestmove(&[], Some(mv), chess960))?;
+                    }
+                    None => {
+                        let limits = parse_go(state, tokens);
+                        infinite = limits.infinite;
+                        engine.go(limits, UciReport { chess960 }, false)?;
+                    }
+                }
+            }
+            Some("ponderhit") => engine.ponderhit(),
+            Some("stop") => engine.stop()?,
+            Some("eval") => {
+                for line in explain(engine.game()?.current()).to_string().lines() { engine.send(line)?; }
+            }
+            Some("quit") => return engine.stop(),
+            _ => {}
+        }
+    }
+    // Nothing can send `ponderhit` any more, so let a ponder search finish as if it had come
+    engine.ponderhit();
+    if infinite { engine.stop()?; }
+    engine.wait()?;
+    settings.apply(&mut engine)?;
+    Ok(())
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+impl Settings {
+    /// Queue an option and apply it at once unless a search is running.
+    fn set<W: Write + Send + 'static>(&mut self, engine: &mut EngineController<W>, name: String, value: String) -> io::Result<()> {
+        let deferred = format!("info string setoption {} deferred until the search ends", name);
+        self.pending.push((name, value));
+        if !self.apply(engine)? { engine.send(&deferred)?; }
+        Ok(())
+    }
+
+    /// Apply the queued options if no search is running, reporting bad names and values as
+    /// `info string`. Returns whether the queue could be emptied.
+    fn apply<W: Write + Send + 'static>(&mut self, engine: &mut EngineController<W>) -> io::Result<bool> {
+        if self.pending.is_empty() { return Ok(true); }
+        let Some(search) = engine.idle()? else { return Ok(false) };
+        let mut errors = Vec::new();
+        for (name, value) in self.pending.drain(..) {
+            match self.options.set(&name, &value, search) {
+                Err(err) => errors.push(format!("info string {}", err)),
+                Ok(()) if name.eq_ignore_ascii_case("BookFile") => {
+                    self.book = self.options.get::<String>("BookFile").and_then(|path| load_book(&path));
+                }
+                Ok(()) => {}
+            }
+        }
+        for line in errors { engine.send(&line)?; }
+        Ok(true)
+    }
+
+    /// The book's move for `state` under the book options, or None to search.
+    fn book_move(&mut self, state: &State) -> Option<Move> {
+        let book = self.book.as_ref().filter(|_| self.options.get::<bool>("OwnBook").unwrap_or(false))?;
+        self.book_policy.variety = self.options.get::<i64>("Book Variety").unwrap_or(0) as u32;
+        self.book_policy.max_ply = self.options.get::<i64>("Book Depth").unwrap_or(0) as u32;
+        self.book_policy.choose(book, state)
+    }
+}
+
+// ============================================================================
+// Command Parsing
+// ============================================================================
+
+/// `position startpos|fen <fen> [moves <m1> <m2> ...]`. Returns None on a malformed position;
+/// the move list stops at the first move that is not legal.
+fn parse_position<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Option<Game> {
+    let mut game = match tokens.next()? {
+        "startpos" => Game::new(),
+        "fen" => {
+            let fen: Vec<&str> = tokens.by_ref().take_while(|&t| t != "moves").collect();
+            return Game::from_fen(&fen.join(" ")).ok().map(|game| apply_moves(game, tokens));
+        }
+        _ => return None,
+    };
+    if tokens.next() == Some("moves") { game = apply_moves(game, tokens); }
+    Some(game)
+}
+
+fn apply_moves<'a>(mut game: Game, tokens: impl Iterator<Item = &'a str>) -> Game {
+    for text in tokens {
+        match parse_move(game.current(), text) {
+            Some(mv) => game.play(mv).expect("parsed moves are legal"),
+            None => break,
+        }
+    }
+    game
+}
+
+/// `setoption name <name> value <value>`, with both parts allowed to contain spaces.
+fn parse_setoption<'a>(tokens: impl Iterator<Item = &'a str>) -> Option<(String, String)> {
+    let tokens: Vec<&str> = tokens.collect();
+    let value_at = tokens.iter().position(|&t| t == "value").unwrap_or(tokens.len());
+    if tokens.first() != Some(&"name") { return None; }
+    let name = tokens[1..value_at].join(" ");
+    let value = tokens.get(value_at + 1..).map_or(String::new(), |rest| rest.join(" "));
+    Some((name, value))
+}
+
+/// `go` and its limits, with times in milliseconds. Unknown parameters are skipped; with no
+/// limit at all the search is infinite, as most engines take a bare `go`, and runs until
+/// `stop`. `searchmoves` takes every legal move that follows it.
+fn parse_go<'a>(state: &State, tokens: impl Iterator<Item = &'a str>) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let mut limited = false;
+    let mut tokens = tokens.peekable();
+
+    while let Some(token) = tokens.next() {
+        let mut value = || tokens.next().and_then(|n| n.parse::<u64>().ok());
+        match token {
+            "depth"     => limits.depth = value().map(|n| n as u32),
+            "nodes"     => limits.nodes = value(),
+            "mate"      => limits.mate = value().map(|n| n as u32),
+            "movetime"  => limits.movetime = value().map(Duration::from_millis),
+            "wtime"     => limits.wtime = value().map(Duration::from_millis),
+            "btime"     => limits.btime = value().map(Duration::from_millis),
+            "winc"      => limits.winc = value().map(Duration::from_millis),
+            "binc"      => limits.binc = value().map(Duration::from_millis),
+            "movestogo" => limits.movestogo = value().map(|n| n as u32),
+            "infinite"  => limits.infinite = true,
+            "ponder"    => { limits.ponder = true; continue; }
+            "searchmoves" => {
+                while let Some(mv) = tokens.peek().and_then(|text| parse_move(state, text)) {
+                    limits.searchmoves.push(mv);
+                    tokens.next();
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        limited = true;
+    }
+
+    if !limited { limits.infinite = true; }
+    limits
+}
+
+/// The book a `BookFile` value names; an empty value, or a file that cannot be read as a
+/// Polyglot book, leaves the engine without one.
+fn load_book(path: &str) -> Option<PolyglotBook> {
+    if path.is_empty() { return None; }
+    PolyglotBook::from_bytes(&fs::read(path).ok()?).ok()
+}
+
+// ============================================================================
+// Info Output
+// ============================================================================
+
+/// UCI wording of the search output; with `chess960` castling is the king taking its rook.
+#[derive(Copy, Clone, Debug)]
+struct UciReport {
+    chess960: bool,
+}
+
+impl Report for UciReport {
+    fn info(&self, info: &SearchInfo) -> Option<String> {
+        Some(format_info(info, self.chess960))
+    }
+
+    /// The final node count of every thread, which the last iteration's `info` line may fall
+    /// well short of, then `bestmove`.
+    fn result(&self, result: &SearchResult, _game: &Game) -> String {
+        format!("info nodes {}\n{}", result.nodes, format_bestmove(&result.pv, result.best_move, self.chess960))
+    }
+}
+
+/// `info depth 8 seldepth 14 score cp 35 nodes 123456 nps 800000 hashfull 12 tbhits 0 time 154 pv e2e4
+/// e7e5 ...`, without `pv` when there is no line, as in a position already lost.
+fn format_info(info: &SearchInfo, chess960: bool) -> String {
+    let mut line = format!(
+        "info depth {} seldepth {} score {} nodes {} nps {} hashfull {} tbhits {} time {}",
+        info.depth, info.seldepth, info.score, info.nodes, info.nodes_per_second(), info.hashfull, info.tbhits,
+        info.elapsed.as_millis(),
+    );
+    if !info.pv.is_empty() {
+        let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_uci(chess960)).collect();
+        line.push_str(&format!(" pv {}", pv.join(" ")));
+    }
+    line
+}
+
+/// `bestmove <move> [ponder <reply>]`, the reply being the second move of the line, or
+/// `bestmove 0000` without a legal move.
+fn format_bestmove(pv: &[Move], best_move: Option<Move>, chess960: bool) -> String {
+    let best = best_move.map_or("0000".to_string(), |mv| mv.to_uci(chess960));
+    match pv.get(1) {
+        Some(reply) if pv.first() == best_move.as_ref() => format!("bestmove {} ponder {}", best, reply.to_uci(chess960)),
+        _ => format!("bestmove {}", best),
+    }
+}
+
+// ============================================================================
+// Move Parsing
+// ============================================================================
+
+/// Resolve a coordinate move, accepting it only if it is legal in the position.
+fn parse_move(state: &State, text: &str) -> Option<Move> {
+    let mv = Move::from_uci(text, state).ok()?;
+    state.is_legal_move(mv).then_some(mv)
+}
@@ -0,0 +1,61 @@
+//! JavaScript bindings, behind the `wasm` feature: a game plus a search, driven by FEN and UCI
+//! move strings.
+
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+
+use crate::game::Game;
+use crate::mv::Move;
+use crate::search::{Search, SearchLimits};
+
+/// A game in progress and the search that plays in it, keeping its hash table between moves.
+#[wasm_bindgen]
+pub struct WasmEngine {
+    game: Game,
+    search: Search,
+}
+
+#[wasm_bindgen]
+impl WasmEngine {
+    /// An engine at the standard initial position.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        WasmEngine { game: Game::new(), search: Search::new() }
+    }
+
+    /// Start a new game from `fen`. On error the current game is kept.
+    pub fn set_fen(&mut self, fen: &str) -> Result<(), JsError> {
+        self.game = Game::from_fen(fen).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// The position as FEN.
+    pub fn fen(&self) -> String {
+        self.game.current().to_fen()
+    }
+
+    /// Legal moves in UCI notation, as an array of strings.
+    pub fn legal_moves(&self) -> JsValue {
+        self.game.current().moves().all()
+            .map(|mv| JsValue::from_str(&mv.to_uci(false)))
+            .collect::<Array>()
+            .into()
+    }
+
+    /// Play a UCI move, which must be legal.
+    pub fn play(&mut self, uci_move: &str) -> Result<(), JsError> {
+        let mv = Move::from_uci(uci_move, self.game.current()).map_err(|e| JsError::new(&e.to_string()))?;
+        self.game.play(mv).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Search to `depth` and return the best move in UCI notation, or `0000` when there is none.
+    /// The move is not played.
+    pub fn search(&mut self, depth: u32) -> String {
+        let result = self.search.best_move_in(&self.game, SearchLimits::depth(depth));
+        result.best_move.map_or("0000".to_string(), |mv| mv.to_uci(false))
+    }
+}
+
+impl Default for WasmEngine {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,282 @@
+//! XBoard front end: the Chess Engine Communication Protocol, version 2, for GUIs and
+//! tournament managers that do not speak UCI.
+
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::board::Color;
+use crate::controller::{EngineController, Report};
+use crate::game::Game;
+use crate::mv::Move;
+use crate::search::{SearchInfo, SearchLimits, SearchResult};
+use crate::state::{GameStatus, State};
+
+/// Depth searched when neither a clock nor a limit has been given.
+const DEFAULT_DEPTH: u32 = 5;
+
+/// Score XBoard reads as a mate, plus the moves to it.
+const XBOARD_MATE: i32 = 100_000;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// What the engine has been told about playing and thinking, apart from the game itself.
+#[derive(Clone, Debug)]
+struct Session {
+    /// In force mode the engine only records moves, for either side.
+    force: bool,
+    /// The side the engine plays when not in force mode.
+    engine_color: Color,
+    /// Whether thinking output is sent.
+    post: bool,
+    clock: Clock,
+}
+
+/// Time controls, from `level`, `st`, `sd`, and the `time` and `otim` updates.
+#[derive(Clone, Debug, Default)]
+struct Clock {
+    /// Moves per time control from `level`; zero for the whole game.
+    moves_per_control: u32,
+    base: Option<Duration>,
+    increment: Duration,
+    /// Remaining time of the engine and of its opponent.
+    time: Option<Duration>,
+    otim: Option<Duration>,
+    /// A fixed time per move, from `st`.
+    movetime: Option<Duration>,
+    /// A depth limit, from `sd`.
+    depth: Option<u32>,
+}
+
+// ============================================================================
+// Public Interface
+// ============================================================================
+
+/// Run the XBoard loop until `quit` or end of input. The engine plays Black after `new`, and
+/// replies as soon as the opponent's move arrives unless in force mode; `go` has it play the
+/// side to move. Moves go both ways in coordinate notation. At end of input the last search
+/// is allowed to finish.
+pub fn run(input: impl BufRead, output: impl Write + Send + 'static) -> io::Result<()> {
+    let mut engine = EngineController::new(output);
+    let mut session = Session { force: false, engine_color: Color::Black, post: false, clock: Clock::default() };
+
+    for line in input.lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("protover") => engine.send(&features())?,
+            Some("new") => {
+                engine.abort()?;
+                *engine.game()? = Game::new();
+                engine.wait()?.clear_hash();
+                session.force = false;
+                session.engine_color = Color::Black;
+                session.clock.depth = None;
+            }
+            Some("setboard") => {
+                engine.abort()?;
+                let fen: Vec<&str> = tokens.collect();
+                match Game::from_fen(&fen.join(" ")) {
+                    Ok(game) => *engine.game()? = game,
+                    Err(err) => engine.send(&format!("tellusererror Illegal position: {}", err))?,
+                }
+            }
+            Some("force") => {
+                engine.abort()?;
+                session.force = true;
+            }
+            Some("go") => {
+                session.force = false;
+                session.engine_color = engine.game()?.current().to_move;
+                think(&mut engine, &session)?;
+            }
+            Some("playother") => {
+                session.force = false;
+                session.engine_color = !engine.game()?.current().to_move;
+            }
+            Some("usermove") => {
+                let Some(text) = tokens.next() else { continue };
+                let game = engine.game()?;
+                match parse_move(game.current(), text) {
+                    Some(mv) => game.play(mv).expect("parsed moves are legal"),
+                    None => {
+                        engine.send(&format!("Illegal move: {}", text))?;
+                        continue;
+                    }
+                }
+                if let Some(result) = result_line(game.status()) {
+                    engine.send(&result)?;
+                } else if !session.force && game.current().to_move == session.engine_color {
+                    think(&mut engine, &session)?;
+                }
+            }
+            Some("undo") => {
+                engine.abort()?;
+                engine.game()?.undo();
+            }
+            Some("remove") => {
+                engine.abort()?;
+                let game = engine.game()?;
+                game.undo();
+                game.undo();
+            }
+            Some("?") => engine.stop()?,
+            Some("result") => {
+                engine.abort()?;
+                session.force = true;
+            }
+            Some("time") => session.clock.time = centiseconds(tokens.next()),
+            Some("otim") => session.clock.otim = centiseconds(tokens.next()),
+            Some("level") => {
+                if let Some(clock) = parse_level(tokens, &session.clock) { session.clock = clock; }
+            }
+            Some("st") => session.clock.movetime = tokens.next().and_then(|s| s.parse().ok()).map(Duration::from_secs),
+            Some("sd") => session.clock.depth = tokens.next().and_then(|d| d.parse().ok()),
+            Some("post") => session.post = true,
+            Some("nopost") => session.post = false,
+            Some("ping") => engine.send(&format!("pong {}", tokens.next().unwrap_or("")))?,
+            Some("quit") => return engine.abort(),
+            // Accepted and ignored: the engine neither ponders, randomizes, nor cares who it plays
+            Some("xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "name" | "rating") | None => {}
+            Some(command) => engine.send(&format!("Error (unknown command): {}", command))?,
+        }
+    }
+    engine.wait()?;
+    Ok(())
+}
+
+/// The `protover 2` reply. `usermove=1` and `setboard=1` ask for the unambiguous forms of the
+/// move and position commands.
+fn features() -> String {
+    format!(
+        "feature myname=\"blitz-chess {}\" ping=1 setboard=1 usermove=1 playother=1 san=0 colors=0 \
+         sigint=0 sigterm=0 reuse=1 analyze=0 variants=\"normal\" done=1",
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// Search for the engine's move, which is played and sent as soon as it is found. A finished
+/// game is announced instead.
+fn think<W: Write + Send + 'static>(engine: &mut EngineController<W>, session: &Session) -> io::Result<()> {
+    let game = engine.game()?;
+    if let Some(result) = result_line(game.status()) { return engine.send(&result); }
+    let limits = session.clock.limits(game.current());
+    engine.go(limits, XboardReport { post: session.post }, true)
+}
+
+// ============================================================================
+// Time Control
+// ============================================================================
+
+impl Clock {
+    /// Limits for the side to move, which is the engine's. `st` overrides the clock; `sd`
+    /// caps the depth either way.
+    fn limits(&self, state: &State) -> SearchLimits {
+        let mut limits = match (self.movetime, self.time.or(self.base)) {
+            (Some(movetime), _) => SearchLimits::movetime(movetime),
+            (None, Some(time)) => {
+                let mut limits = SearchLimits::default();
+                let otim = self.otim.unwrap_or(time);
+                (limits.wtime, limits.btime) = match state.to_move {
+                    Color::White => (Some(time), Some(otim)),
+                    Color::Black => (Some(otim), Some(time)),
+                };
+                (limits.winc, limits.binc) = (Some(self.increment), Some(self.increment));
+                if self.moves_per_control > 0 {
+                    let played = (state.fullmove_number as u32 - 1) % self.moves_per_control;
+                    limits.movestogo = Some(self.moves_per_control - played);
+                }
+                limits
+            }
+            (None, None) => SearchLimits::depth(self.depth.unwrap_or(DEFAULT_DEPTH)),
+        };
+        if self.depth.is_some() { limits.depth = self.depth; }
+        limits
+    }
+}
+
+/// `level <moves> <minutes>[:<seconds>] <increment>`, which also resets the clocks to the base
+/// time until the next `time` and `otim`.
+fn parse_level<'a>(mut tokens: impl Iterator<Item = &'a str>, clock: &Clock) -> Option<Clock> {
+    let moves_per_control = tokens.next()?.parse().ok()?;
+    let base = tokens.next()?;
+    let base = match base.split_once(':') {
+        Some((minutes, seconds)) => minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?,
+        None => base.parse::<u64>().ok()? * 60,
+    };
+    let increment: f64 = tokens.next()?.parse().ok()?;
+    Some(Clock {
+        moves_per_control,
+        base: Some(Duration::from_secs(base)),
+        increment: Duration::from_secs_f64(increment.max(0.0)),
+        time: None,
+        otim: None,
+        ..clock.clone()
+    })
+}
+
+/// A `time` or `otim` value, given in centiseconds.
+fn centiseconds(token: Option<&str>) -> Option<Duration> {
+    let centiseconds: u64 = token?.parse().ok()?;
+    Some(Duration::from_millis(centiseconds * 10))
+}
+
+// ============================================================================
+// Output
+// ============================================================================
+
+/// XBoard wording of the search output: `move <move>`, then the result if that ends the
+/// game, and with `post` a thinking line per iteration.
+#[derive(Copy, Clone, Debug)]
+struct XboardReport {
+    post: bool,
+}
+
+impl Report for XboardReport {
+    /// `<ply> <score> <time> <nodes> <pv>`, the time in centiseconds.
+    fn info(&self, info: &SearchInfo) -> Option<String> {
+        if !self.post { return None; }
+        let score = match info.score.mate_moves() {
+            Some(moves) => moves.signum() * (XBOARD_MATE + moves.abs()),
+            None => info.score.raw(),
+        };
+        let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_uci(false)).collect();
+        Some(format!("{} {} {} {} {}", info.depth, score, info.elapsed.as_millis() / 10, info.nodes, pv.join(" ")))
+    }
+
+    fn result(&self, result: &SearchResult, game: &Game) -> String {
+        let Some(mv) = result.best_move else { return "resign".to_string() };
+        match result_line(game.status()) {
+            Some(line) => format!("move {}\n{}", mv.to_uci(false), line),
+            None => format!("move {}", mv.to_uci(false)),
+        }
+    }
+}
+
+/// The result command for a finished game, or None while it goes on.
+fn result_line(status: GameStatus) -> Option<String> {
+    let line = match status {
+        GameStatus::Ongoing                   => return None,
+        GameStatus::Checkmate(Color::White)   => "1-0 {White mates}",
+        GameStatus::Checkmate(Color::Black)   => "0-1 {Black mates}",
+        GameStatus::Stalemate                 => "1/2-1/2 {Stalemate}",
+        GameStatus::DrawFiftyMove             => "1/2-1/2 {Fifty move rule}",
+        GameStatus::DrawInsufficientMaterial  => "1/2-1/2 {Insufficient material}",
+        GameStatus::DrawDeadPosition          => "1/2-1/2 {Dead position}",
+        GameStatus::DrawThreefoldRepetition
+        | GameStatus::DrawFivefoldRepetition  => "1/2-1/2 {Draw by repetition}",
+    };
+    Some(line.to_string())
+}
+
+// ============================================================================
+// Move Parsing
+// ============================================================================
+
+/// Resolve a coordinate move, accepting it only if it is legal in the position.
+fn parse_move(state: &State, text: &str) -> Option<Move> {
+    let mv = Move::from_uci(text, state).ok()?;
+    state.is_legal_move(mv).then_some(mv)
+}
@@ -0,0 +1,147 @@
+//! Zobrist hashing keys.
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::state::State;
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+/// Fixed seed so hashes are identical across runs, builds, and platforms.
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+const KEY_COUNT: usize = 12 * 64 + 1 + 4 + 8;
+
+/// Piece-square keys first, then side to move, castling rights, and en passant files.
+const KEYS: [u64; KEY_COUNT] = generate_keys();
+
+const SIDE_OFFSET: usize = 12 * 64;
+const CASTLING_OFFSET: usize = SIDE_OFFSET + 1;
+const EN_PASSANT_OFFSET: usize = CASTLING_OFFSET + 4;
+
+/// SplitMix64, evaluated at compile time.
+const fn generate_keys() -> [u64; KEY_COUNT] {
+    let mut keys = [0; KEY_COUNT];
+    let mut state = SEED;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+// ============================================================================
+// Key Accessors
+// ============================================================================
+
+/// Toggled whenever Black is to move.
+pub const SIDE_KEY: u64 = KEYS[SIDE_OFFSET];
+
+/// Key for a piece standing on a square.
+pub const fn piece_key(piece: Piece, square: Square) -> u64 {
+    let kind = (piece.color() as usize) * 6 + piece.piece_type() as usize;
+    KEYS[kind * 64 + square.index()]
+}
+
+/// Combined key for every right still held.
+pub const fn castling_key(rights: CastlingRights) -> u64 {
+    let mut key = 0;
+    let mut i = 0;
+    while i < 4 {
+        let color = if i < 2 { Color::White } else { Color::Black };
+        let side = if i % 2 == 0 { CastlingSide::Kingside } else { CastlingSide::Queenside };
+        if rights.has(color, side) { key ^= KEYS[CASTLING_OFFSET + i]; }
+        i += 1;
+    }
+    key
+}
+
+/// Key for the en passant file, or zero when there is no en passant square.
+pub const fn en_passant_key(en_passant: Option<Square>) -> u64 {
+    match en_passant {
+        Some(sq) => KEYS[EN_PASSANT_OFFSET + sq.file() as usize],
+        None => 0,
+    }
+}
+
+// ============================================================================
+// Full Computation
+// ============================================================================
+
+/// Hash a state from scratch. Incremental updates in `make_move` must always agree with this.
+pub fn hash_state(state: &State) -> u64 {
+    let pieces = state.board.pieces().fold(0, |hash, (sq, piece)| hash ^ piece_key(piece, sq));
+    let side = if state.to_move == Color::Black { SIDE_KEY } else { 0 };
+    pieces ^ side ^ castling_key(state.castling_rights) ^ en_passant_key(state.en_passant)
+}
+
+/// Hash the pawns alone, for caching pawn-structure evaluation.
+pub fn hash_pawns(board: &Board) -> u64 {
+    [Color::White, Color::Black].into_iter().fold(0, |hash, color| {
+        let pawn = Piece::new(PieceType::Pawn, color);
+        board.pieces_of(color, PieceType::Pawn).into_iter().fold(hash, |hash, sq| hash ^ piece_key(pawn, sq))
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mv::{Move, MoveType};
+
+    #[test]
+    fn incremental_hash_matches_a_full_rehash_for_every_move_type() {
+        let starts = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        ];
+        let mut seen = [0; 4];
+        let mut captures = 0;
+        let mut seed = 0xD1B5_4A32_D192_ED03u64;
+        for playout in 0..400 {
+            let mut state = State::from_fen(starts[playout % starts.len()]).unwrap();
+            assert_eq!(state.hash(), hash_state(&state));
+            for _ in 0..80 {
+                let moves: Vec<Move> = state.moves().all().collect();
+                if moves.is_empty() { break; }
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let mv = moves[(seed % moves.len() as u64) as usize];
+                seen[mv.move_type() as usize] += 1;
+                if state.board[mv.target()].is_some() && mv.move_type() != MoveType::Castling { captures += 1; }
+
+                state.make_move(mv);
+                assert_eq!(state.hash(), hash_state(&state), "{} after {}", state.to_fen(), mv);
+            }
+        }
+        assert!(seen.iter().all(|&count| count > 0) && captures > 0, "{:?} by move type, {} captures", seen, captures);
+    }
+
+    #[test]
+    fn positions_reached_by_different_orders_hash_alike() {
+        let play = |moves: &[&str]| {
+            let mut state = State::start();
+            for text in moves { state.make_move(Move::from_uci(text, &state).unwrap()); }
+            state
+        };
+        let a = play(&["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let b = play(&["b1c3", "b8c6", "g1f3", "g8f6"]);
+        assert_eq!(a.hash(), b.hash());
+        // The same placement with the other side to move, or without an en passant square
+        let fen = |side: &str, ep: &str| State::from_fen(&format!("4k3/8/8/3pP3/8/8/8/4K3 {} - {} 0 1", side, ep)).unwrap();
+        assert_ne!(fen("w", "-").hash(), fen("b", "-").hash());
+        assert_ne!(fen("w", "-").hash(), fen("w", "d6").hash());
+    }
+}
@@ -0,0 +1,122 @@
+//! Building an opening book from the ten-game collection in `fixtures/ten_games.pgn`: six
+//! games open 1.e4, scoring three wins and two draws for White, and four open 1.d4, scoring
+//! one win and two draws.
+
+use engine::book::{build_from_pgn, BookPolicy, PolyglotBook, MAX_BOOK_DEPTH};
+use engine::mv::Move;
+use engine::state::State;
+
+const TEN_GAMES: &str = include_str!("fixtures/ten_games.pgn");
+
+fn build(max_ply: usize, min_games: u32) -> PolyglotBook {
+    build_from_pgn(TEN_GAMES.as_bytes(), max_ply, min_games).unwrap()
+}
+
+/// The book's moves for the position after `line`, in UCI with their weights, sorted by move.
+fn book_moves(book: &PolyglotBook, line: &[&str]) -> Vec<(String, u16)> {
+    let state = after(line);
+    let mut moves: Vec<_> = book.moves(&state).into_iter().map(|(mv, weight)| (mv.to_uci(false), weight)).collect();
+    moves.sort();
+    moves
+}
+
+fn after(line: &[&str]) -> State {
+    line.iter().fold(State::start(), |state, text| {
+        let mv = Move::from_uci(text, &state).unwrap();
+        state.apply_move(mv)
+    })
+}
+
+fn entries(moves: &[(&str, u16)]) -> Vec<(String, u16)> {
+    moves.iter().map(|&(text, weight)| (text.to_string(), weight)).collect()
+}
+
+#[test]
+fn first_moves_are_weighted_by_their_results() {
+    let book = build(4, 1);
+    // Two points a win and one a draw: 3 * 2 + 2 for 1.e4, 1 * 2 + 2 for 1.d4
+    assert_eq!(book_moves(&book, &[]), entries(&[("d2d4", 4), ("e2e4", 8)]));
+    assert_eq!(book.moves(&State::start())[0].0.to_uci(false), "e2e4");
+
+    // Black's replies score from Black's side; 1...e6 lost its only game and is left out
+    assert_eq!(book_moves(&book, &["e2e4"]), entries(&[("c7c5", 2), ("e7e5", 2)]));
+    assert_eq!(book_moves(&book, &["d2d4"]), entries(&[("d7d5", 2), ("g8f6", 2)]));
+    assert_eq!(book_moves(&book, &["e2e4", "e7e5"]), entries(&[("f1c4", 1), ("g1f3", 3)]));
+}
+
+#[test]
+fn rare_moves_and_deep_plies_are_left_out() {
+    let book = build(4, 4);
+    assert_eq!(book_moves(&book, &[]), entries(&[("d2d4", 4), ("e2e4", 8)]));
+    assert!(book_moves(&book, &["e2e4"]).is_empty());
+
+    let book = build(1, 1);
+    assert_eq!(book.len(), 2);
+    assert!(book_moves(&book, &["e2e4"]).is_empty());
+}
+
+#[test]
+fn built_book_survives_the_binary_format() {
+    let book = build(4, 1);
+    assert_eq!(PolyglotBook::from_bytes(&book.to_bytes()).unwrap(), book);
+}
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// The moves `policy` picks from the position after `line` over `trials` tries, in UCI.
+fn picks(policy: &mut BookPolicy, book: &PolyglotBook, line: &[&str], trials: usize) -> Vec<String> {
+    let state = after(line);
+    (0..trials).map(|_| policy.choose(book, &state).unwrap().to_uci(false)).collect()
+}
+
+#[test]
+fn no_variety_always_plays_the_heaviest_move() {
+    let book = build(4, 1);
+    for seed in [1, 7, 0xDEAD_BEEF] {
+        let mut policy = BookPolicy::new(0, MAX_BOOK_DEPTH).with_seed(seed);
+        assert!(picks(&mut policy, &book, &[], 50).iter().all(|mv| mv == "e2e4"));
+        assert!(picks(&mut policy, &book, &["e2e4", "e7e5"], 50).iter().all(|mv| mv == "g1f3"));
+    }
+}
+
+#[test]
+fn full_variety_follows_the_weights_and_repeats_under_a_seed() {
+    let book = build(4, 1);
+    let mut policy = BookPolicy::new(100, MAX_BOOK_DEPTH).with_seed(42);
+    let moves = picks(&mut policy, &book, &[], 3000);
+    // 1.e4 weighs 8 against 1.d4's 4
+    let share = moves.iter().filter(|mv| *mv == "e2e4").count() as f64 / moves.len() as f64;
+    assert!((share - 2.0 / 3.0).abs() < 0.03, "1.e4 played {:.3} of the time", share);
+    let mut again = BookPolicy::new(100, MAX_BOOK_DEPTH).with_seed(42);
+    assert_eq!(picks(&mut again, &book, &[], 3000), moves);
+
+    // Lower variety leans further towards the heavier move
+    let mut policy = BookPolicy::new(50, MAX_BOOK_DEPTH).with_seed(42);
+    let share = picks(&mut policy, &book, &[], 3000).iter().filter(|mv| *mv == "e2e4").count() as f64 / 3000.0;
+    assert!((share - 0.8).abs() < 0.03, "1.e4 played {:.3} of the time", share);
+}
+
+#[test]
+fn the_book_is_ignored_from_its_depth_on() {
+    let book = build(4, 1);
+    let mut policy = BookPolicy::new(0, 2);
+    assert!(policy.choose(&book, &after(&["e2e4"])).is_some());
+    assert!(policy.choose(&book, &after(&["e2e4", "e7e5"])).is_none());
+    assert!(policy.out_of_book());
+
+    let mut policy = BookPolicy::new(0, 0);
+    assert!(policy.choose(&book, &State::start()).is_none());
+}
+
+#[test]
+fn leaving_the_book_lasts_until_a_new_game() {
+    let book = build(4, 1);
+    let mut policy = BookPolicy::default();
+    assert!(policy.choose(&book, &after(&["e2e3"])).is_none());
+    // 1.e3 e6 2.e4 e5 reaches the book's 1.e4 e5, but the game has left it
+    assert!(policy.choose(&book, &after(&["e2e3", "e7e6", "e3e4", "e6e5"])).is_none());
+    policy.new_game();
+    assert_eq!(policy.choose(&book, &after(&["e2e3", "e7e6", "e3e4", "e6e5"])).unwrap().to_uci(false), "g1f3");
+}
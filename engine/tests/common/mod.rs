@@ -0,0 +1,31 @@
+//! Helpers for driving the protocol front ends with scripted input.
+
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+/// An output the front ends can own while the test keeps a handle to read it back.
+#[derive(Clone, Default)]
+pub struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl SharedOutput {
+    pub fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run a front end over `script` to the end of input and return everything it wrote.
+pub fn converse(run: impl FnOnce(Cursor<Vec<u8>>, SharedOutput) -> io::Result<()>, script: &str) -> String {
+    let output = SharedOutput::default();
+    run(Cursor::new(script.as_bytes().to_vec()), output.clone()).unwrap();
+    output.text()
+}
@@ -0,0 +1,113 @@
+//! The C API called as C would call it: through raw pointers and NUL-terminated buffers.
+//! Run with `cargo test --features ffi`.
+
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use engine::ffi::*;
+
+/// A caller-owned output buffer, read back as a string once a call has filled it.
+struct Buffer([c_char; 2048]);
+
+impl Buffer {
+    fn new() -> Self {
+        Buffer([0; 2048])
+    }
+
+    fn text(&self) -> &str {
+        // SAFETY: the engine always NUL-terminates what it writes
+        unsafe { CStr::from_ptr(self.0.as_ptr()) }.to_str().unwrap()
+    }
+}
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// ============================================================================
+// Lifecycle
+// ============================================================================
+
+#[test]
+fn handles_are_created_and_freed() {
+    for _ in 0..100 {
+        let handle = bc_new_game();
+        assert!(!handle.is_null());
+        // SAFETY: a live handle, freed once
+        unsafe { bc_free(handle) };
+    }
+    // SAFETY: null is documented as ignored
+    unsafe { bc_free(ptr::null_mut()) };
+}
+
+// ============================================================================
+// Positions and Moves
+// ============================================================================
+
+#[test]
+fn a_game_is_played_through_the_c_api() {
+    let handle = bc_new_game();
+    let mut buf = Buffer::new();
+    // SAFETY: a live handle, NUL-terminated strings, and buffers of the length passed
+    unsafe {
+        assert_eq!(bc_fen(handle, buf.0.as_mut_ptr(), buf.0.len()), START_FEN.len() as i32);
+        assert_eq!(buf.text(), START_FEN);
+
+        let written = bc_legal_moves(handle, buf.0.as_mut_ptr(), buf.0.len());
+        assert_eq!(written as usize, buf.text().len());
+        assert_eq!(buf.text().split(' ').count(), 20);
+        assert!(buf.text().split(' ').any(|mv| mv == "e2e4"));
+
+        let e4 = CString::new("e2e4").unwrap();
+        assert_eq!(bc_make_move(handle, e4.as_ptr()), BC_OK);
+        bc_fen(handle, buf.0.as_mut_ptr(), buf.0.len());
+        assert_eq!(buf.text(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+        // Scholar's mate is one move away for White
+        let fen = CString::new("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+        assert_eq!(bc_set_fen(handle, fen.as_ptr()), BC_OK);
+        assert_eq!(bc_best_move(handle, 3, buf.0.as_mut_ptr(), buf.0.len()), 4);
+        assert_eq!(buf.text(), "h5f7");
+
+        let mate = CString::new("h5f7").unwrap();
+        assert_eq!(bc_make_move(handle, mate.as_ptr()), BC_OK);
+        assert_eq!(bc_legal_moves(handle, buf.0.as_mut_ptr(), buf.0.len()), 0);
+        assert_eq!(buf.text(), "");
+        assert_eq!(bc_best_move(handle, 3, buf.0.as_mut_ptr(), buf.0.len()), 4);
+        assert_eq!(buf.text(), "0000");
+
+        bc_free(handle);
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[test]
+fn bad_arguments_return_error_codes_and_leave_the_game_alone() {
+    let handle = bc_new_game();
+    let mut buf = Buffer::new();
+    // SAFETY: a live handle or null, NUL-terminated strings, and buffers of the length passed
+    unsafe {
+        let garbage = CString::new("not a fen").unwrap();
+        assert_eq!(bc_set_fen(handle, garbage.as_ptr()), BC_ERR_PARSE);
+        assert_eq!(bc_set_fen(handle, ptr::null()), BC_ERR_NULL);
+        assert_eq!(bc_set_fen(ptr::null_mut(), garbage.as_ptr()), BC_ERR_NULL);
+
+        let invalid = [0xffu8 as c_char, 0];
+        assert_eq!(bc_make_move(handle, invalid.as_ptr()), BC_ERR_UTF8);
+        assert_eq!(bc_make_move(handle, garbage.as_ptr()), BC_ERR_PARSE);
+        let illegal = CString::new("e2e5").unwrap();
+        assert_eq!(bc_make_move(handle, illegal.as_ptr()), BC_ERR_ILLEGAL);
+
+        // Room for the FEN but not its NUL
+        assert_eq!(bc_fen(handle, buf.0.as_mut_ptr(), START_FEN.len()), BC_ERR_BUFFER);
+        assert_eq!(bc_fen(handle, ptr::null_mut(), buf.0.len()), BC_ERR_NULL);
+        assert_eq!(bc_legal_moves(ptr::null(), buf.0.as_mut_ptr(), buf.0.len()), BC_ERR_NULL);
+
+        bc_fen(handle, buf.0.as_mut_ptr(), buf.0.len());
+        assert_eq!(buf.text(), START_FEN);
+        bc_free(handle);
+    }
+}
@@ -0,0 +1,46 @@
+//! A terminal game through `play::run`, with the player's side piped in.
+
+use std::io::Cursor;
+
+use engine::board::Color;
+use engine::play::{self, PlayOptions};
+use engine::search::SearchLimits;
+
+/// Play over `script` at a shallow depth and return everything printed.
+fn play(human: Color, script: &str) -> String {
+    let mut output = Vec::new();
+    let options = PlayOptions { human, limits: SearchLimits::depth(2), ascii: true };
+    play::run(Cursor::new(script), &mut output, options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn a_piped_game_runs_through_every_command() {
+    let output = play(Color::White, "e4\nfen\nKe3\nundo\nfen\nhint\nresign\ny\nquit\n");
+    // `fen` prints after the prompt, on the same line
+    let fens: Vec<&str> = output.lines()
+        .filter_map(|line| line.strip_prefix("White to move> "))
+        .filter(|text| text.contains('/'))
+        .collect();
+    assert_eq!(fens.len(), 2, "{}", output);
+
+    // The engine answers 1.e4, leaving White to play on move two; undo takes back both moves
+    assert_eq!(output.matches("blitz plays").count(), 2, "{}", output);
+    let fields: Vec<&str> = fens[0].split(' ').collect();
+    assert!(fields[0].contains("/4P3/") && fields[1] == "w" && fields[5] == "2", "{}", fens[0]);
+    assert_eq!(fens[1], "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+    assert!(output.contains("'Ke3' is not a legal move. Legal moves for your kings: Ke2"), "{}", output);
+    assert!(output.contains("Hint: "));
+    assert!(output.contains("White resigns. Black wins."));
+    assert!(output.contains("Play again with the colors reversed? [y/n] "));
+    // Reversed, the engine opens as White and the player is asked for Black's move
+    assert!(output.trim_end().ends_with("Black to move>"), "{}", output);
+}
+
+#[test]
+fn end_of_input_ends_the_session() {
+    let output = play(Color::Black, "");
+    assert_eq!(output.matches("blitz plays").count(), 1);
+    assert!(output.trim_end().ends_with("Black to move>"));
+}
@@ -0,0 +1,221 @@
+//! Move generation, SAN, game status, and the FEN after every move, checked against the
+//! `shakmaty` crate. `cargo test --features reference-check` asks it live, over a corpus and
+//! long random playouts; without the feature the corpus is checked against its recorded
+//! answers in `fixtures/reference.txt`. `BLESS=1` with the feature records them afresh.
+
+use std::collections::BTreeMap;
+
+use engine::mv::Move;
+use engine::state::{GameStatus, State};
+
+const RECORDING: &str = include_str!("fixtures/reference.txt");
+
+/// Openings, castling through and out of check, en passant with pins, promotions, checks of
+/// every kind, mates, and stalemates.
+const CORPUS: [&str; 24] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "8/8/8/KPp4r/8/8/8/7k w - c6 0 2",
+    "8/8/3k4/8/2pP4/8/8/3KB3 b - d3 0 1",
+    "4k3/8/8/8/1b6/8/N7/R3K3 w Q - 0 1",
+    "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+    "r3k2r/8/8/8/4r3/8/8/R3K2R w KQkq - 0 1",
+    "r3k2r/8/8/8/8/8/5n2/R3K2R w KQkq - 0 1",
+    "1r2k2r/8/8/8/8/8/8/R3K2R b KQk - 0 1",
+    "4k3/1P6/8/8/8/8/6p1/4K2R w K - 0 1",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "4k3/8/8/8/8/8/8/4K2R b K - 0 1",
+    "3k4/8/8/B7/8/8/8/3RK3 b - - 0 1",
+    "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4",
+    "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+    "k7/8/1Q6/8/8/8/8/6K1 b - - 0 1",
+    "8/8/8/8/8/5k2/6q1/7K w - - 0 1",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+];
+
+/// What one engine says about a position: whether it is check, mate, or stalemate, and the
+/// SAN and resulting FEN of each legal move, keyed by the move in UCI.
+#[derive(Debug, Default, PartialEq)]
+struct Verdict {
+    status: String,
+    moves: BTreeMap<String, (String, String)>,
+}
+
+// ============================================================================
+// Verdicts
+// ============================================================================
+
+fn ours(fen: &str) -> Verdict {
+    let state = State::from_fen(fen).unwrap();
+    let status = match state.status() {
+        GameStatus::Checkmate(_) => "checkmate",
+        GameStatus::Stalemate => "stalemate",
+        _ if state.in_check() => "check",
+        _ => "ongoing",
+    };
+    let moves = state.moves().all()
+        .map(|mv: Move| (mv.to_uci(false), (mv.to_san(&state), state.clone().apply_move(mv).to_fen())))
+        .collect();
+    Verdict { status: status.to_string(), moves }
+}
+
+/// Every difference between the two verdicts on `fen`, naming the move and both answers.
+fn mismatches(fen: &str, ours: &Verdict, reference: &Verdict) -> Vec<String> {
+    let mut found = Vec::new();
+    if ours.status != reference.status {
+        found.push(format!("{}: status {} here, {} in the reference", fen, ours.status, reference.status));
+    }
+    for (uci, answer) in &ours.moves {
+        match reference.moves.get(uci) {
+            None => found.push(format!("{}: {} generated here but illegal in the reference", fen, uci)),
+            Some(expected) if expected != answer => {
+                found.push(format!("{}: {} gives {:?} here, {:?} in the reference", fen, uci, answer, expected));
+            }
+            Some(_) => {}
+        }
+    }
+    for uci in reference.moves.keys().filter(|uci| !ours.moves.contains_key(*uci)) {
+        found.push(format!("{}: {} legal in the reference but not generated here", fen, uci));
+    }
+    found
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Blocks of `fen <FEN>`, `status <status>`, then `move <uci> <san> <FEN after>` for each
+/// move, separated by blank lines.
+fn parse_recording(text: &str) -> Vec<(String, Verdict)> {
+    text.split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut fen = String::new();
+            let mut verdict = Verdict::default();
+            for line in block.lines() {
+                let (key, rest) = line.split_once(' ').unwrap();
+                match key {
+                    "fen" => fen = rest.to_string(),
+                    "status" => verdict.status = rest.to_string(),
+                    "move" => {
+                        let mut fields = rest.splitn(3, ' ');
+                        let (uci, san, after) = (fields.next().unwrap(), fields.next().unwrap(), fields.next().unwrap());
+                        verdict.moves.insert(uci.to_string(), (san.to_string(), after.to_string()));
+                    }
+                    _ => panic!("unexpected line in the recording: {}", line),
+                }
+            }
+            (fen, verdict)
+        })
+        .collect()
+}
+
+#[test]
+fn the_recorded_corpus_passes_clean() {
+    let recorded = parse_recording(RECORDING);
+    assert!(recorded.len() >= CORPUS.len());
+    assert!(CORPUS.iter().all(|fen| recorded.iter().any(|(recorded, _)| recorded == fen)), "recording is stale");
+    let found: Vec<String> = recorded.iter().flat_map(|(fen, reference)| mismatches(fen, &ours(fen), reference)).collect();
+    assert!(found.is_empty(), "{}", found.join("\n"));
+}
+
+// ============================================================================
+// Live Reference
+// ============================================================================
+
+#[cfg(feature = "reference-check")]
+mod live {
+    use super::*;
+
+    use shakmaty::fen::Fen;
+    use shakmaty::san::SanPlus;
+    use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+
+    /// Plies per random playout, and how many of the playout positions go into the recording.
+    const PLAYOUT_PLIES: usize = 80;
+    const RECORDED_PLAYOUT_POSITIONS: usize = 24;
+
+    fn reference(fen: &str) -> Verdict {
+        let fen: Fen = fen.parse().unwrap();
+        let position: Chess = fen.into_position(CastlingMode::Standard).unwrap();
+        let status = if position.is_checkmate() {
+            "checkmate"
+        } else if position.is_stalemate() {
+            "stalemate"
+        } else if position.is_check() {
+            "check"
+        } else {
+            "ongoing"
+        };
+        let moves = position.legal_moves().into_iter()
+            .map(|mv| {
+                let uci = mv.to_uci(CastlingMode::Standard).to_string();
+                let san = SanPlus::from_move(position.clone(), mv).to_string();
+                let after = position.clone().play(mv).unwrap();
+                (uci, (san, Fen::from_position(&after, EnPassantMode::Always).to_string()))
+            })
+            .collect();
+        Verdict { status: status.to_string(), moves }
+    }
+
+    /// Positions along seeded random playouts from each corpus position, picking among the
+    /// legal moves in UCI order so that the walk does not depend on generation order.
+    fn playout_positions() -> Vec<String> {
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        let mut positions = Vec::new();
+        for fen in CORPUS {
+            let mut state = State::from_fen(fen).unwrap();
+            for _ in 0..PLAYOUT_PLIES {
+                let mut moves: Vec<Move> = state.moves().all().collect();
+                if state.status() != GameStatus::Ongoing { break; }
+                moves.sort_by_key(|mv| mv.to_uci(false));
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                state = state.apply_move(moves[(seed % moves.len() as u64) as usize]);
+                positions.push(state.to_fen());
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn the_corpus_and_random_playouts_match_the_reference() {
+        let positions: Vec<String> = CORPUS.iter().map(|fen| fen.to_string()).chain(playout_positions()).collect();
+        assert!(positions.len() > 1000);
+        let found: Vec<String> = positions.iter().flat_map(|fen| mismatches(fen, &ours(fen), &reference(fen))).collect();
+        assert!(found.is_empty(), "{} mismatches:\n{}", found.len(), found.join("\n"));
+    }
+
+    #[test]
+    fn the_recording_is_what_the_reference_says() {
+        let step = playout_positions().len() / RECORDED_PLAYOUT_POSITIONS;
+        let positions: Vec<String> = CORPUS.iter().map(|fen| fen.to_string())
+            .chain(playout_positions().into_iter().step_by(step).take(RECORDED_PLAYOUT_POSITIONS))
+            .collect();
+        let recording: String = positions.iter()
+            .map(|fen| {
+                let verdict = reference(fen);
+                let mut block = format!("fen {}\nstatus {}\n", fen, verdict.status);
+                for (uci, (san, after)) in &verdict.moves {
+                    block.push_str(&format!("move {} {} {}\n", uci, san, after));
+                }
+                block
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if std::env::var_os("BLESS").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/reference.txt");
+            std::fs::write(path, &recording).unwrap();
+        } else {
+            assert!(recording == RECORDING, "fixtures/reference.txt is out of date; rerun with BLESS=1");
+        }
+    }
+}
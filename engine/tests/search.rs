@@ -0,0 +1,281 @@
+//! Searches run through the public `Search` interface.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use engine::game::Game;
+use engine::mv::Move;
+use engine::score::Score;
+use engine::search::{Search, SearchLimits};
+use engine::skill::{Strength, MATE_IN_ONE_LEVEL, MAX_ELO, MAX_SKILL_LEVEL};
+use engine::state::{GameStatus, State};
+
+// ============================================================================
+// Time Management
+// ============================================================================
+
+#[test]
+fn movetime_search_returns_on_time_with_a_move() {
+    let mut search = Search::new();
+    let fens = ["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4"];
+    for fen in fens {
+        let state = State::from_fen(fen).unwrap();
+        let start = Instant::now();
+        let result = search.best_move(&state, SearchLimits::movetime(Duration::from_millis(100)));
+        let elapsed = start.elapsed();
+        assert!(elapsed < Duration::from_millis(150), "{} took {:?}", fen, elapsed);
+        assert!(result.best_move.is_some_and(|mv| state.is_legal_move(mv)));
+    }
+}
+
+// ============================================================================
+// Repetition
+// ============================================================================
+
+#[test]
+fn positions_played_before_the_root_count_as_repetitions() {
+    // White, a queen down, can only save the game by returning to a position already played
+    let mut game = Game::from_fen("3qk3/8/8/8/8/8/8/4K1N1 w - - 0 1").unwrap();
+    for text in ["g1f3", "e8e7", "f3g1", "e7e8"] {
+        game.play(Move::from_uci(text, game.current()).unwrap()).unwrap();
+    }
+    let mut search = Search::new();
+    let fresh = search.best_move(game.current(), SearchLimits::depth(4));
+    assert!(fresh.score < Score::cp(-500), "{:?}", fresh.score);
+
+    search.clear_hash();
+    let seeded = search.best_move_in(&game, SearchLimits::depth(4));
+    assert_eq!(seeded.score, Score::ZERO);
+    assert_eq!(seeded.best_move.map(|mv| mv.to_uci(false)).as_deref(), Some("g1f3"));
+}
+
+// ============================================================================
+// Threads
+// ============================================================================
+
+/// Win At Chess positions with their solutions, as UCI moves.
+const TACTICS: [(&str, &str); 9] = [
+    ("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1", "g3g6"),
+    ("5rk1/1ppb3p/p1pb4/6q1/3P1p1r/2P1R2P/PP1BQ1P1/5RKN w - - 0 1", "e3g3"),
+    ("r1bq2rk/pp3pbp/2p1p1pQ/7P/3P4/2PB1N2/PP3PPR/2KR4 w - - 0 1", "h6h7"),
+    ("5k2/6pp/p1qN4/1p1p4/3P4/2PKP2Q/PP3r2/3R4 b - - 0 1", "c6c4"),
+    ("7k/p7/1R5K/6r1/6p1/6P1/8/8 w - - 0 1", "b6b7"),
+    ("rnbqkb1r/pppp1ppp/8/4P3/6n1/7P/PPPNPPP1/R1BQKBNR b KQkq - 0 1", "g4e3"),
+    ("r4q1k/p2bR1rp/2p2Q1N/5p2/5p2/2P5/PP3PPP/R5K1 w - - 0 1", "e7f7"),
+    ("3q1rk1/p4pp1/2pb3p/3p4/6Pr/1PNQ4/P1PB1PP1/4RRK1 b - - 0 1", "d6h2"),
+    ("2br2k1/2q3rn/p2NppQ1/2p1P3/Pp5R/4P3/1P3PPP/3R2K1 w - - 0 1", "h4h7"),
+];
+
+#[test]
+fn four_threads_find_the_same_tactics_as_one() {
+    let timed = |threads, state: &State| {
+        let mut search = Search::new();
+        search.set_threads(threads);
+        let start = Instant::now();
+        let result = search.best_move(state, SearchLimits::depth(10));
+        (result, start.elapsed())
+    };
+    let (mut single_nodes, mut single_time) = (0, Duration::ZERO);
+    let (mut parallel_nodes, mut parallel_time) = (0, Duration::ZERO);
+    for (fen, solution) in TACTICS {
+        let state = State::from_fen(fen).unwrap();
+        let (single, elapsed) = timed(1, &state);
+        (single_nodes, single_time) = (single_nodes + single.nodes, single_time + elapsed);
+        let (parallel, elapsed) = timed(4, &state);
+        (parallel_nodes, parallel_time) = (parallel_nodes + parallel.nodes, parallel_time + elapsed);
+        assert_eq!(single.best_move.map(|mv| mv.to_uci(false)).as_deref(), Some(solution), "{}", fen);
+        assert_eq!(parallel.best_move, single.best_move, "{}", fen);
+    }
+
+    let single_nps = single_nodes as f64 / single_time.as_secs_f64();
+    let parallel_nps = parallel_nodes as f64 / parallel_time.as_secs_f64();
+    // Four threads should come near four times the speed, though no more than there are cores
+    // to run them: on one core they only share its time
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get()).min(4);
+    assert!(parallel_nps > 0.6 * cores as f64 * single_nps, "{:.0} nps on one thread, {:.0} on four over {} cores", single_nps, parallel_nps, cores);
+}
+
+#[test]
+fn node_limit_binds_the_main_thread_and_the_count_covers_every_thread() {
+    let state = State::start();
+    let single = Search::new().best_move(&state, SearchLimits::nodes(100_000));
+    assert!(single.nodes <= 100_000, "{}", single.nodes);
+
+    let mut search = Search::new();
+    search.set_threads(4);
+    let parallel = search.best_move(&state, SearchLimits::nodes(100_000));
+    assert!(parallel.best_move.is_some_and(|mv| state.is_legal_move(mv)));
+    assert!(parallel.nodes > 100_000, "{}", parallel.nodes);
+}
+
+// ============================================================================
+// Mates
+// ============================================================================
+
+/// White mates in three at best: Ra6, and after ...f6 Bxf6+ the rook comes down to a8.
+const MATE_IN_3: &str = "r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1";
+
+#[test]
+fn mate_in_three_is_reported_with_the_full_line() {
+    let state = State::from_fen(MATE_IN_3).unwrap();
+    let result = Search::new().best_move(&state, SearchLimits::depth(7));
+    assert_eq!(result.score.mate_moves(), Some(3));
+    assert_eq!(result.score.to_string(), "mate 3");
+    assert_eq!(result.pv.len(), 5, "{:?}", result.pv);
+}
+
+#[test]
+fn mating_line_is_legal_and_ends_in_mate() {
+    let mut state = State::from_fen(MATE_IN_3).unwrap();
+    let result = Search::new().best_move(&state, SearchLimits::depth(7));
+    for mv in result.pv {
+        assert!(state.is_legal_move(mv), "{} in {}", mv.to_uci(false), state.to_fen());
+        state = state.apply_move(mv);
+    }
+    assert!(matches!(state.status(), GameStatus::Checkmate(_)), "{}", state.to_fen());
+}
+
+#[test]
+fn mate_in_two_is_found_at_depth_three() {
+    // Nf6+ gxf6, Bxf7#
+    let state = State::from_fen("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1").unwrap();
+    let result = Search::new().best_move(&state, SearchLimits::depth(3));
+    assert_eq!(result.score.mate_moves(), Some(2));
+    assert_eq!(result.best_move.map(|mv| mv.to_uci(false)).as_deref(), Some("d5f6"));
+}
+
+// ============================================================================
+// Determinism
+// ============================================================================
+
+#[test]
+fn fixed_depth_searches_repeat_node_for_node() {
+    let state = State::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    let first = Search::new().best_move(&state, SearchLimits::depth(6));
+    let second = Search::new().best_move(&state, SearchLimits::depth(6));
+    assert_eq!(first.nodes, second.nodes);
+    assert_eq!(first.best_move, second.best_move);
+    assert_eq!(first.score, second.score);
+}
+
+// ============================================================================
+// Quiescence
+// ============================================================================
+
+#[test]
+fn quiescence_sees_the_recapture_past_the_horizon() {
+    // Qxd5 wins a pawn at depth 1, and loses the queen to Rxd5 the ply after
+    let state = State::from_fen("3r2k1/5ppp/8/3p4/8/8/5PPP/3Q2K1 w - - 0 1").unwrap();
+    let greedy = Move::from_uci("d1d5", &state).unwrap();
+    for depth in [1, 2] {
+        let result = Search::new().best_move(&state, SearchLimits::depth(depth));
+        assert_ne!(result.best_move, Some(greedy), "depth {}", depth);
+        assert!(result.score > Score::ZERO, "depth {}: {:?}", depth, result.score);
+    }
+}
+
+// ============================================================================
+// Endgames
+// ============================================================================
+
+#[test]
+fn a_protected_passer_outside_the_kings_reach_runs_at_low_depth() {
+    // c5 is defended by b4 and Black's king is too far to catch it once it moves
+    let state = State::from_fen("8/6k1/8/2P5/1P6/8/8/6K1 w - - 0 1").unwrap();
+    for depth in 1..=3 {
+        let result = Search::new().best_move(&state, SearchLimits::depth(depth));
+        assert_eq!(result.best_move.map(|mv| mv.to_uci(false)).as_deref(), Some("c5c6"), "depth {}", depth);
+    }
+}
+
+#[test]
+fn a_won_ending_deep_into_the_fifty_moves_resets_the_clock() {
+    // A queen up, the engine would rather herd the king at a fresh clock; at 70 the fading
+    // evaluation makes the pawn push that restarts it worth more
+    let state = State::from_fen("8/8/8/3k4/8/8/P7/1Q2K3 w - - 70 80").unwrap();
+    let mv = Search::new().best_move(&state, SearchLimits::depth(4)).best_move.unwrap();
+    let after = state.clone().apply_move(mv).to_fen();
+    assert_eq!(after.split(' ').nth(4), Some("0"), "{}", mv.to_uci(false));
+}
+
+// ============================================================================
+// Statistics
+// ============================================================================
+
+#[test]
+fn seldepth_is_never_below_the_iteration_depth() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let mut search = Search::new();
+        let sink = Arc::clone(&infos);
+        search.set_info_callback(move |info| sink.lock().unwrap().push(info));
+        search.best_move(&State::from_fen(fen).unwrap(), SearchLimits::depth(7));
+
+        let infos = infos.lock().unwrap();
+        assert_eq!(infos.len(), 7, "{}", fen);
+        assert!(infos.iter().all(|info| info.seldepth >= info.depth), "{}", fen);
+        assert!(infos.iter().all(|info| info.tbhits == 0), "{}", fen);
+        // Captures and checks past the horizon reach beyond it somewhere
+        assert!(infos.iter().any(|info| info.seldepth > info.depth), "{}", fen);
+    }
+}
+
+// ============================================================================
+// Skill
+// ============================================================================
+
+/// A search at `level`.
+fn at_level(level: u32) -> Search {
+    let mut search = Search::new();
+    search.set_strength(Strength { skill_level: level, ..Strength::default() });
+    search
+}
+
+#[test]
+fn the_lowest_level_spreads_its_moves() {
+    let state = State::start();
+    let mut search = at_level(0);
+    let mut played = HashSet::new();
+    for _ in 0..60 {
+        let mv = search.best_move(&state, SearchLimits::infinite()).best_move.unwrap();
+        assert!(state.is_legal_move(mv));
+        played.insert(mv.to_uci(false));
+    }
+    assert!(played.len() >= 3, "{:?}", played);
+}
+
+#[test]
+fn the_top_level_is_full_strength() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ];
+    for fen in fens {
+        let state = State::from_fen(fen).unwrap();
+        let full = Search::new().best_move(&state, SearchLimits::depth(6));
+        let top = at_level(MAX_SKILL_LEVEL).best_move(&state, SearchLimits::depth(6));
+        assert_eq!((top.best_move, top.score, top.nodes, top.pv), (full.best_move, full.score, full.nodes, full.pv), "{}", fen);
+    }
+    let mut top_elo = Search::new();
+    top_elo.set_strength(Strength { limit_strength: true, elo: MAX_ELO, ..Strength::default() });
+    assert_eq!(top_elo.strength().depth_limit(), None);
+}
+
+#[test]
+fn a_mate_in_one_is_never_missed_from_its_level() {
+    // Rd8 mates; Rd7 and the pawn moves only look plausible
+    let state = State::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+    for level in MATE_IN_ONE_LEVEL..MAX_SKILL_LEVEL {
+        let mut search = at_level(level);
+        for _ in 0..20 {
+            assert_eq!(search.best_move(&state, SearchLimits::infinite()).best_move.unwrap().to_uci(false), "d1d8", "level {}", level);
+        }
+    }
+}
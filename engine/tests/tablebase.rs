@@ -0,0 +1,84 @@
+//! Probing Syzygy files through the `syzygy` feature, against the tables in
+//! `fixtures/syzygy`. They were solved for these tests rather than copied from the published
+//! set, and every position in them was read back through `shakmaty-syzygy` and checked against
+//! the solver's values.
+
+#![cfg(feature = "syzygy")]
+
+use std::sync::Arc;
+
+use engine::search::{Search, SearchLimits};
+use engine::state::State;
+use engine::tablebase::{Tablebase, Wdl};
+
+/// The bundled tables: KQvK, and KRvK and KQvKR for the four-piece positions.
+fn tablebase() -> Tablebase {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/syzygy");
+    let tablebase = Tablebase::new([path]).expect("the bundled tables are not readable");
+    assert_eq!(tablebase.max_pieces(), 4, "tables missing from {}", path);
+    tablebase
+}
+
+#[test]
+fn king_and_queen_beat_a_lone_king() {
+    let tablebase = tablebase();
+    let white = State::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    let black = State::from_fen("8/8/8/4k3/8/8/8/KQ6 b - - 0 1").unwrap();
+    assert_eq!(tablebase.probe_wdl(&white), Some(Wdl::Win));
+    assert_eq!(tablebase.probe_wdl(&black), Some(Wdl::Loss));
+}
+
+#[test]
+fn root_probe_keeps_the_win() {
+    let tablebase = tablebase();
+    let state = State::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    let (mv, dtz) = tablebase.probe_root(&state).unwrap();
+    assert!(dtz.0 > 0, "{:?}", dtz);
+    assert_eq!(tablebase.probe_wdl(&state.clone().apply_move(mv)), Some(Wdl::Loss));
+}
+
+#[test]
+fn search_with_tables_plays_a_winning_move() {
+    let tablebase = tablebase();
+    let tablebase = Arc::new(tablebase);
+    let state = State::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    let mut search = Search::new();
+    search.set_tablebase(Some(Arc::clone(&tablebase)));
+    let mv = search.best_move(&state, SearchLimits::depth(4)).best_move.unwrap();
+    assert!(state.is_legal_move(mv));
+    assert_eq!(tablebase.probe_wdl(&state.clone().apply_move(mv)), Some(Wdl::Loss));
+}
+
+#[test]
+fn a_won_root_searches_only_the_moves_that_make_progress() {
+    let tablebase = Arc::new(tablebase());
+    // A lone king, and a rook that the queen wins with a skewer
+    for fen in ["8/8/8/4k3/8/8/8/KQ6 w - - 0 1", "8/8/8/3k4/8/8/6r1/KQ6 w - - 0 1"] {
+        let state = State::from_fen(fen).unwrap();
+        let moves = tablebase.probe_root_moves(&state).unwrap();
+        let fastest = moves.iter().filter(|&&(_, wdl, _)| wdl == Wdl::Win).map(|&(_, _, dtz)| dtz).min().unwrap();
+
+        let mut search = Search::new();
+        search.set_tablebase(Some(Arc::clone(&tablebase)));
+        let mv = search.best_move(&state, SearchLimits::depth(4)).best_move.unwrap();
+        assert!(moves.contains(&(mv, Wdl::Win, fastest)), "{}: {}", fen, mv);
+        // Black's longest resistance after it is shorter than White's distance before
+        let (_, reply) = tablebase.probe_root(&state.clone().apply_move(mv)).unwrap();
+        assert!(reply.0 < 0 && -reply.0 < fastest.0, "{}: {:?} after {:?}", fen, reply, fastest);
+    }
+}
+
+#[test]
+fn a_drawn_root_never_plays_a_losing_move() {
+    let tablebase = Arc::new(tablebase());
+    // Taking the queen is the only move that does not lose: with the king, and with the rook
+    // when the white king recaptures
+    for (fen, draw) in [("8/8/8/8/8/8/1k6/1Q4K1 b - - 0 1", "b2b1"), ("3r4/8/8/8/3Q4/4K3/8/k7 b - - 0 1", "d8d4")] {
+        let state = State::from_fen(fen).unwrap();
+        assert_eq!(tablebase.probe_wdl(&state), Some(Wdl::Draw), "{}", fen);
+        let mut search = Search::new();
+        search.set_tablebase(Some(Arc::clone(&tablebase)));
+        let mv = search.best_move(&state, SearchLimits::depth(1)).best_move.unwrap();
+        assert_eq!(mv.to_uci(false), draw, "{}", fen);
+    }
+}
@@ -0,0 +1,208 @@
+//! Scripted UCI sessions through `uci::run`.
+
+mod common;
+
+use common::converse;
+use engine::uci;
+
+#[test]
+fn setoption_during_a_search_is_deferred_not_blocking() {
+    let output = converse(uci::run, "position startpos\ngo infinite\nsetoption name Hash value 32\nisready\nstop\n");
+    assert!(output.contains("info string setoption Hash deferred until the search ends"));
+    let ready = output.find("readyok").expect("isready answered during the search");
+    let best = output.find("bestmove").expect("stop answered");
+    assert!(ready < best);
+}
+
+#[test]
+fn unknown_setoption_is_reported() {
+    let output = converse(uci::run, "setoption name Foo Bar value 3\nisready\n");
+    assert!(output.contains("info string unknown option 'Foo Bar'"));
+    assert!(output.ends_with("readyok\n"));
+}
+
+#[test]
+fn ucinewgame_during_an_infinite_search_stops_it() {
+    let output = converse(uci::run, "position startpos\ngo infinite\nucinewgame\nisready\n");
+    let best = output.find("bestmove").expect("the search was stopped");
+    assert!(best < output.find("readyok").unwrap());
+}
+
+#[test]
+fn bare_go_searches_until_stopped() {
+    let output = converse(uci::run, "position startpos\ngo\nisready\n");
+    // Only end of input stops it, so the search is still running when `isready` is answered
+    let ready = output.find("readyok").unwrap();
+    assert!(output.find("bestmove").is_some_and(|best| best > ready), "{}", output);
+}
+
+// ============================================================================
+// Pondering
+// ============================================================================
+
+#[test]
+fn ponder_result_waits_for_ponderhit() {
+    let output = converse(uci::run, "position startpos moves e2e4 e7e5\ngo ponder depth 3\nisready\nponderhit\n");
+    // The search is done long before `ponderhit`, yet its move is held back until then
+    let ready = output.find("readyok").unwrap();
+    let best = output.find("bestmove").expect("ponderhit releases the move");
+    assert!(ready < best, "{}", output);
+    assert_eq!(output.matches("bestmove").count(), 1);
+}
+
+#[test]
+fn stop_ends_a_ponder_search_on_a_miss() {
+    let output = converse(uci::run, "position startpos\ngo ponder wtime 60000 btime 60000\nisready\nstop\nisready\n");
+    let best = output.find("bestmove").expect("stop answered");
+    assert!(output.find("readyok").unwrap() < best, "{}", output);
+    let line = output[best..].lines().next().unwrap();
+    let fields: Vec<&str> = line.split(' ').collect();
+    assert!(fields.len() == 2 || (fields.len() == 4 && fields[2] == "ponder"), "{}", line);
+}
+
+#[test]
+fn bestmove_names_the_expected_reply_to_ponder_on() {
+    let output = converse(uci::run, "position startpos\ngo depth 4\n");
+    let line = output.lines().find(|line| line.starts_with("bestmove")).unwrap();
+    let fields: Vec<&str> = line.split(' ').collect();
+    assert_eq!(fields.len(), 4, "{}", line);
+    assert_eq!(fields[2], "ponder");
+    // The ponder move is the second move of the last reported line
+    let pv = output.lines().rev().find(|line| line.starts_with("info depth")).unwrap();
+    let pv: Vec<&str> = pv.split(" pv ").nth(1).unwrap().split(' ').collect();
+    assert_eq!((pv[0], pv[1]), (fields[1], fields[3]));
+}
+
+// ============================================================================
+// Search Limits
+// ============================================================================
+
+/// The `nodes` count of each `info depth` line.
+fn info_nodes(output: &str) -> Vec<u64> {
+    output.lines()
+        .filter(|line| line.starts_with("info depth"))
+        .map(|line| info_field(line, "nodes"))
+        .collect()
+}
+
+/// The value after `name` in an `info` line.
+fn info_field(line: &str, name: &str) -> u64 {
+    line.split(&format!(" {} ", name)).nth(1).unwrap().split(' ').next().unwrap().parse().unwrap()
+}
+
+#[test]
+fn info_lines_report_seldepth_and_tbhits() {
+    let output = converse(uci::run, "position startpos\ngo depth 5\n");
+    let lines: Vec<&str> = output.lines().filter(|line| line.starts_with("info depth")).collect();
+    assert_eq!(lines.len(), 5, "{}", output);
+    for line in lines {
+        assert!(info_field(line, "seldepth") >= info_field(line, "depth"), "{}", line);
+        assert_eq!(info_field(line, "tbhits"), 0, "{}", line);
+    }
+}
+
+#[test]
+fn go_nodes_stays_within_the_limit() {
+    // Exact on one thread; the slack is the batch of nodes between checks of the limit and clock
+    const CHECK_BATCH: u64 = 2048;
+    for limit in [5_000, 50_000] {
+        let output = converse(uci::run, &format!("position startpos\ngo nodes {}\n", limit));
+        let nodes = info_nodes(&output);
+        assert!(!nodes.is_empty(), "{}", output);
+        assert!(nodes.iter().all(|&n| n <= limit + CHECK_BATCH), "{:?} against {}", nodes, limit);
+        assert!(output.contains("bestmove "));
+    }
+}
+
+#[test]
+fn go_mate_reports_the_mate_it_finds() {
+    let script = "position fen r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1\ngo mate 2\n";
+    let output = converse(uci::run, script);
+    let last = output.lines().rev().find(|line| line.starts_with("info depth")).unwrap();
+    assert!(last.contains(" score mate 2 "), "{}", output);
+    assert!(output.contains("bestmove d5f6"), "{}", output);
+}
+
+// ============================================================================
+// Threads
+// ============================================================================
+
+#[test]
+fn go_nodes_on_four_threads_counts_every_thread() {
+    // The limit binds the main thread, and the final count adds the helpers' nodes to it
+    const THREADS: u64 = 4;
+    const LIMIT: u64 = 400_000;
+    let script = format!("setoption name Threads value {}\nposition startpos\ngo nodes {}\n", THREADS, LIMIT);
+    let output = converse(uci::run, &script);
+    let total = output.lines().find_map(|line| line.strip_prefix("info nodes ")).unwrap().parse::<u64>().unwrap();
+    assert!((LIMIT..=2 * THREADS * LIMIT).contains(&total), "{} nodes against {}", total, LIMIT);
+    assert!(info_nodes(&output).iter().all(|&nodes| nodes <= total));
+
+    let best = output.lines().find_map(|line| line.strip_prefix("bestmove ")).unwrap();
+    let best = best.split(' ').next().unwrap();
+    let start = engine::state::State::start();
+    assert!(engine::mv::Move::from_uci(best, &start).is_ok_and(|mv| start.is_legal_move(mv)), "{}", best);
+}
+
+#[test]
+fn one_thread_again_searches_deterministically() {
+    // Each later `go` waits for the search before it, and applies the deferred options first
+    let search = "setoption name Clear Hash\nposition startpos\ngo depth 7\n";
+    let parallel = "setoption name Threads value 4\nposition startpos\ngo nodes 100000\n";
+    let script = format!("{}setoption name Threads value 1\n{}{}", parallel, search, search);
+    let output = converse(uci::run, &script);
+    let runs: Vec<&str> = output.split("bestmove").collect();
+    assert_eq!(runs.len(), 4, "{}", output);
+    // Everything but the clock repeats
+    let lines = |run: &str| -> Vec<String> {
+        run.lines()
+            .filter(|line| line.starts_with("info depth"))
+            .map(|line| format!("{} pv {}", line.split(" nps ").next().unwrap(), line.split(" pv ").nth(1).unwrap()))
+            .collect()
+    };
+    assert_eq!(lines(runs[1]).len(), 7);
+    assert_eq!(lines(runs[1]), lines(runs[2]));
+}
+
+// ============================================================================
+// Opening Book
+// ============================================================================
+
+/// Run `script` with `OwnBook` on and the ten-game fixture as the `BookFile`.
+fn converse_with_book(name: &str, script: &str) -> String {
+    let book = engine::book::build_from_pgn(include_str!("fixtures/ten_games.pgn").as_bytes(), 4, 1).unwrap();
+    let path = std::env::temp_dir().join(format!("{}-{}.bin", name, std::process::id()));
+    std::fs::write(&path, book.to_bytes()).unwrap();
+    let setup = format!("setoption name OwnBook value true\nsetoption name BookFile value {}\n", path.display());
+    let output = converse(uci::run, &(setup + script));
+    std::fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn book_moves_are_played_until_the_game_leaves_the_book() {
+    // 1.e3 e6 2.e4 e5 transposes into the book's 1.e4 e5, which only a new game plays from
+    let output = converse_with_book(
+        "uci-sticky-book",
+        "position startpos\ngo depth 1\n\
+         position startpos moves e2e3 e7e6\ngo depth 1\n\
+         position startpos moves e2e3 e7e6 e3e4 e6e5\ngo depth 1\n\
+         ucinewgame\nposition startpos moves e2e3 e7e6 e3e4 e6e5\ngo depth 1\n",
+    );
+    let answers: Vec<&str> = output.lines().filter(|line| *line == "info string book move" || line.starts_with("bestmove")).collect();
+    assert_eq!(answers.len(), 6, "{}", output);
+    assert_eq!(answers[..2], ["info string book move", "bestmove e2e4"]);
+    assert!(answers[2].starts_with("bestmove") && answers[3].starts_with("bestmove"));
+    assert_eq!(answers[4..], ["info string book move", "bestmove g1f3"]);
+}
+
+#[test]
+fn book_depth_hands_over_to_the_search() {
+    let output = converse_with_book(
+        "uci-book-depth",
+        "setoption name Book Depth value 2\n\
+         position startpos moves e2e4\ngo depth 1\nposition startpos moves e2e4 e7e5\ngo depth 1\n",
+    );
+    assert_eq!(output.matches("info string book move").count(), 1, "{}", output);
+    assert_eq!(output.matches("bestmove").count(), 2, "{}", output);
+}
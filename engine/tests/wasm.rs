@@ -0,0 +1,40 @@
+//! The JavaScript bindings, run inside a wasm runtime with
+//! `wasm-pack test --node --features wasm`.
+
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use engine::wasm::WasmEngine;
+use js_sys::Array;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn a_game_is_played_through_the_bindings() {
+    let mut engine = WasmEngine::new();
+    assert_eq!(engine.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let moves = Array::from(&engine.legal_moves());
+    assert_eq!(moves.length(), 20);
+    assert!(moves.iter().any(|mv| mv.as_string().as_deref() == Some("e2e4")));
+
+    engine.play("e2e4").unwrap();
+    assert_eq!(engine.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert!(engine.play("e2e4").is_err());
+}
+
+#[wasm_bindgen_test]
+fn search_finds_mate_and_reports_none_once_mated() {
+    let mut engine = WasmEngine::new();
+    engine.set_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+    assert_eq!(engine.search(3), "h5f7");
+    engine.play("h5f7").unwrap();
+    assert_eq!(Array::from(&engine.legal_moves()).length(), 0);
+    assert_eq!(engine.search(3), "0000");
+}
+
+#[wasm_bindgen_test]
+fn a_bad_fen_keeps_the_current_game() {
+    let mut engine = WasmEngine::new();
+    engine.play("d2d4").unwrap();
+    let before = engine.fen();
+    assert!(engine.set_fen("not a fen").is_err());
+    assert_eq!(engine.fen(), before);
+}
@@ -0,0 +1,58 @@
+//! Scripted XBoard sessions through `xboard::run`.
+
+mod common;
+
+use common::converse;
+use engine::mv::Move;
+use engine::state::State;
+use engine::xboard;
+
+/// The `move` lines of a session, without the command.
+fn moves(output: &str) -> Vec<&str> {
+    output.lines().filter_map(|line| line.strip_prefix("move ")).collect()
+}
+
+#[test]
+fn engine_replies_as_black_and_posts_its_thinking() {
+    let output = converse(xboard::run, "xboard\nprotover 2\nnew\nsd 3\npost\nusermove e2e4\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("feature myname=\"blitz-chess "), "{}", output);
+    assert!(lines[0].ends_with(" done=1"));
+
+    // `<ply> <score> <time> <nodes> <pv>` for each of the three plies, then the move
+    let thinking: Vec<&str> = lines.iter().copied().filter(|line| !line.starts_with("move ")).skip(1).collect();
+    assert_eq!(thinking.len(), 3, "{}", output);
+    for (ply, line) in thinking.iter().enumerate() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        assert_eq!(fields[0], (ply + 1).to_string());
+        assert!(fields[1..4].iter().all(|field| field.parse::<i64>().is_ok()), "{}", line);
+        assert_eq!(fields.len(), 4 + ply + 1, "{}", line);
+    }
+    let replies = moves(&output);
+    assert_eq!(replies.len(), 1);
+    assert_eq!(thinking[2].split(' ').nth(4), Some(replies[0]));
+}
+
+#[test]
+fn force_mode_records_moves_until_go() {
+    let script = "new\nforce\nusermove e2e4\nusermove e7e5\nusermove e7e5\nnopost\nsd 2\ngo\nping 7\n";
+    let output = converse(xboard::run, script);
+    assert!(output.contains("Illegal move: e7e5"), "{}", output);
+    // Only `go` makes the engine move, for the side to move, which is White
+    let replies = moves(&output);
+    assert_eq!(replies.len(), 1, "{}", output);
+    let state = State::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2").unwrap();
+    assert!(Move::from_uci(replies[0], &state).is_ok_and(|mv| state.is_legal_move(mv)), "{}", replies[0]);
+    assert!(output.contains("pong 7"));
+}
+
+#[test]
+fn setboard_to_a_mate_in_one_ends_the_game() {
+    let script = "new\nsetboard r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4\nsd 3\ngo\n";
+    let output = converse(xboard::run, script);
+    assert!(output.ends_with("move h5f7\n1-0 {White mates}\n"), "{}", output);
+
+    let output = converse(xboard::run, "new\nsetboard not a position\nfoo\n");
+    assert!(output.starts_with("tellusererror Illegal position: "), "{}", output);
+    assert!(output.ends_with("Error (unknown command): foo\n"));
+}